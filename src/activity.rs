@@ -0,0 +1,118 @@
+//! `ds activity --since 7d`: the files of a tree modified within a time window,
+//! grouped by day with a count and total size for each, for incident
+//! timelines and checking that backups ran.
+
+use crate::color::ColorScheme;
+use chrono::{DateTime, Local, NaiveDate, TimeDelta, TimeZone};
+use ds::{DirLister, Entry, Units, escape_control, human_readable_size};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tabled::{
+    Table, Tabled,
+    settings::{Style, object::Columns, object::Rows},
+};
+
+#[derive(Tabled)]
+struct Line {
+    #[tabled(rename = "Day")]
+    day: String,
+    #[tabled(rename = "Time")]
+    time: String,
+    #[tabled(rename = "File")]
+    file: String,
+    #[tabled(rename = "Size")]
+    size: String,
+}
+
+/// The start of the window: a span back from now like `30m`, `12h`, `7d` or
+/// `2w`, or a date like `2024-05-01`.
+pub fn parse_since(text: &str) -> Result<DateTime<Local>, String> {
+    let invalid = || format!("invalid time '{text}' (expected like 12h, 7d, 2w or 2024-05-01)");
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?;
+        return Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .ok_or_else(invalid);
+    }
+    let digits = text
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (number, unit) = text.split_at(digits);
+    let number: i64 = number.parse().map_err(|_| invalid())?;
+    let span = match unit {
+        "m" => TimeDelta::try_minutes(number),
+        "h" => TimeDelta::try_hours(number),
+        "d" => TimeDelta::try_days(number),
+        "w" => TimeDelta::try_weeks(number),
+        _ => None,
+    };
+    Ok(Local::now() - span.ok_or_else(invalid)?)
+}
+
+/// None when nothing was modified since `since`.
+pub fn render(
+    path: &Path,
+    since: DateTime<Local>,
+    show_hidden: bool,
+    units: Units,
+    colors: &ColorScheme,
+) -> Option<Table> {
+    let lister = DirLister {
+        all: show_hidden,
+        ..DirLister::default()
+    };
+    // Day -> (time, path, bytes), days and files newest first
+    let mut days: BTreeMap<NaiveDate, Vec<(DateTime<Local>, String, u64)>> = BTreeMap::new();
+    for (_, Entry { path: file, meta }) in lister.recursive(lister.list(path), usize::MAX) {
+        let Ok(modified) = meta.modified() else {
+            continue;
+        };
+        let modified: DateTime<Local> = modified.into();
+        if !meta.is_file() || modified < since {
+            continue;
+        }
+        let relative = file.strip_prefix(path).unwrap_or(&file);
+        let name = escape_control(&relative.to_string_lossy());
+        days.entry(modified.date_naive())
+            .or_default()
+            .push((modified, name, meta.len()));
+    }
+    if days.is_empty() {
+        return None;
+    }
+
+    let mut lines = Vec::new();
+    let mut totals = Vec::new();
+    for (day, mut files) in days.into_iter().rev() {
+        files.sort_by_key(|(time, ..)| std::cmp::Reverse(*time));
+        let bytes = files.iter().map(|(_, _, bytes)| bytes).sum();
+        totals.push(lines.len());
+        lines.push(Line {
+            day: day.format("%a %Y-%m-%d").to_string(),
+            time: String::new(),
+            file: match files.len() {
+                1 => "1 file".into(),
+                count => format!("{count} files"),
+            },
+            size: human_readable_size(bytes, units),
+        });
+        lines.extend(files.into_iter().map(|(time, file, bytes)| Line {
+            day: String::new(),
+            time: time.format("%H:%M").to_string(),
+            file,
+            size: human_readable_size(bytes, units),
+        }));
+    }
+
+    let mut table = Table::new(lines);
+    table.with(Style::empty());
+    table.modify(Columns::one(1), colors.time.clone());
+    table.modify(Columns::last(), colors.size.clone());
+    // Day totals stand out from their files
+    for row in totals {
+        table.modify(Rows::one(row + 1), colors.kind.clone());
+    }
+    table.modify(Rows::first(), colors.dim.clone());
+    Some(table)
+}
@@ -0,0 +1,270 @@
+//! `ds baseline`: a record of the mode, owner and content hash of every entry
+//! in a tree, and a check of the tree against it, for noticing tampering on
+//! small servers.
+
+use crate::cache::Cache;
+use crate::color::ColorScheme;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use ds::{DirLister, Entry, group_and_owner_mode, permission_string};
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File, Metadata};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use tabled::{
+    Table, Tabled,
+    settings::{Style, object::Columns, object::Rows},
+};
+
+#[derive(Debug, Tabled)]
+struct Deviation {
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Change")]
+    change: &'static str,
+    #[tabled(rename = "Baseline")]
+    before: String,
+    #[tabled(rename = "Now")]
+    after: String,
+}
+
+fn sha256(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// What is recorded of one entry: its type, mode, owner, content (the hash
+/// of a file, the target of a symlink) and modification time.
+fn record(entry: &Entry, cache: Option<&Cache>) -> Value {
+    let Entry { path: file, meta } = entry;
+    let owner = group_and_owner_mode(meta);
+    let (kind, content) = if meta.file_type().is_symlink() {
+        let target = fs::read_link(file).unwrap_or_default();
+        ("symlink", json!(target.to_string_lossy()))
+    } else if meta.is_dir() {
+        ("dir", Value::Null)
+    } else {
+        // Unreadable files are recorded as such rather than left out
+        let hashed = || json!(sha256(file).unwrap_or_else(|err| format!("unreadable ({err})")));
+        let hash = match cache {
+            // Unreadable files aren't cached, they're tried again next time
+            Some(cache) => cache
+                .get("sha256", meta, || sha256(file).ok().map(Value::from))
+                .unwrap_or_else(hashed),
+            None => hashed(),
+        };
+        ("file", hash)
+    };
+    json!({
+        "type": kind,
+        "mode": format!("{:o}", meta.mode() & 0o7777),
+        "uid": meta.uid(),
+        "gid": meta.gid(),
+        "owner": format!("{}:{}", owner.owner, owner.group),
+        "content": content,
+        "mtime": mtime(meta),
+    })
+}
+
+/// The modification time to the nanosecond, in UTC so baselines compare the
+/// same across time zones.
+fn mtime(meta: &Metadata) -> Value {
+    match DateTime::<Utc>::from_timestamp(meta.mtime(), meta.mtime_nsec() as u32) {
+        Some(time) => json!(time.to_rfc3339_opts(SecondsFormat::Nanos, true)),
+        None => Value::Null,
+    }
+}
+
+/// A recorded modification time as local time, to the nanosecond since
+/// touched files often differ by less than a second.
+fn describe_mtime(record: &Value) -> String {
+    DateTime::parse_from_rfc3339(&text(&record["mtime"]))
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S%.9f")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// Records of every entry under `path` by their path relative to it, except
+/// the baseline `file` itself when it's kept in the tree. Hashes come from
+/// `cache` for files that haven't changed since they were hashed.
+fn scan(
+    path: &Path,
+    show_hidden: bool,
+    file: &Path,
+    cache: Option<&Cache>,
+) -> BTreeMap<String, Value> {
+    let absolute = |path: &Path| {
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+        let dir = dir.unwrap_or(Path::new(".")).canonicalize().ok()?;
+        Some(dir.join(path.file_name()?))
+    };
+    let baseline = absolute(file);
+    let lister = DirLister {
+        all: show_hidden,
+        ..DirLister::default()
+    };
+    lister
+        .recursive(lister.list(path), usize::MAX)
+        .into_iter()
+        .filter(|(_, entry)| baseline.is_none() || absolute(&entry.path) != baseline)
+        .map(|(_, entry)| {
+            let relative = entry.path.strip_prefix(path).unwrap_or(&entry.path);
+            (
+                relative.to_string_lossy().into_owned(),
+                record(&entry, cache),
+            )
+        })
+        .collect()
+}
+
+pub fn create(
+    path: &Path,
+    file: &Path,
+    show_hidden: bool,
+    cache: Option<&Cache>,
+) -> Result<usize, String> {
+    let entries = scan(path, show_hidden, file, cache);
+    if ds::interrupted() {
+        return Err("interrupted, a partial baseline would flag the rest as added".into());
+    }
+    let count = entries.len();
+    let baseline = json!({
+        "root": path.canonicalize().unwrap_or(path.to_path_buf()).to_string_lossy(),
+        "all": show_hidden,
+        "entries": Map::from_iter(entries),
+    });
+    let text = serde_json::to_string_pretty(&baseline).map_err(|err| err.to_string())?;
+    fs::write(file, text + "\n").map_err(|err| format!("can't write {}: {err}", file.display()))?;
+    Ok(count)
+}
+
+/// A mode as stored in the baseline, shown like the Permission column.
+fn describe_mode(record: &Value) -> String {
+    let kind = match record["type"].as_str() {
+        Some("dir") => 'd',
+        Some("symlink") => 'l',
+        _ => '-',
+    };
+    let mode = record["mode"]
+        .as_str()
+        .and_then(|mode| u32::from_str_radix(mode, 8).ok())
+        .unwrap_or(0);
+    permission_string(kind, mode)
+}
+
+fn text(value: &Value) -> String {
+    value.as_str().map(String::from).unwrap_or_default()
+}
+
+/// The entries of `path` that differ from the baseline in `file`; None when
+/// nothing changed. With `verify_mtime`, files whose content changed while
+/// their modification time didn't, or the other way round, are listed too:
+/// the sign of a tool putting timestamps back, or touching files it didn't
+/// change.
+pub fn verify(
+    path: &Path,
+    file: &Path,
+    verify_mtime: bool,
+    colors: &ColorScheme,
+) -> Result<Option<Table>, String> {
+    let baseline =
+        fs::read_to_string(file).map_err(|err| format!("can't read {}: {err}", file.display()))?;
+    let baseline: Value = serde_json::from_str(&baseline)
+        .map_err(|err| format!("{} isn't a baseline: {err}", file.display()))?;
+    let Some(recorded) = baseline["entries"].as_object() else {
+        return Err(format!("{} has no entries", file.display()));
+    };
+    // Hashed afresh, a cache would hide changes that kept the modification time
+    let current = scan(path, baseline["all"].as_bool().unwrap_or(false), file, None);
+    if ds::interrupted() {
+        return Err("interrupted before every entry was checked".into());
+    }
+
+    let mut deviations = Vec::new();
+    for (name, before) in recorded {
+        let Some(after) = current.get(name) else {
+            deviations.push(Deviation {
+                path: name.clone(),
+                change: "removed",
+                before: text(&before["type"]),
+                after: String::new(),
+            });
+            continue;
+        };
+
+        let mut changed = |change, before: String, after: String| {
+            deviations.push(Deviation {
+                path: name.clone(),
+                change,
+                before,
+                after,
+            })
+        };
+        if before["type"] != after["type"] {
+            changed("type", text(&before["type"]), text(&after["type"]));
+            continue;
+        }
+        if before["mode"] != after["mode"] {
+            changed("mode", describe_mode(before), describe_mode(after));
+        }
+        if before["uid"] != after["uid"] || before["gid"] != after["gid"] {
+            changed("owner", text(&before["owner"]), text(&after["owner"]));
+        }
+        let content_changed = before["content"] != after["content"];
+        if verify_mtime && before["type"] == "file" {
+            if before["mtime"].is_null() {
+                return Err(format!(
+                    "{} records no modification times, create it again",
+                    file.display()
+                ));
+            }
+            let mtime_changed = before["mtime"] != after["mtime"];
+            if content_changed && !mtime_changed {
+                changed("mtime kept", describe_mtime(before), describe_mtime(after));
+            } else if mtime_changed && !content_changed {
+                changed("mtime only", describe_mtime(before), describe_mtime(after));
+            }
+        }
+        if content_changed {
+            // Hashes are long, a prefix tells them apart
+            let short = |value: &Value| text(value).chars().take(16).collect();
+            changed(
+                "content",
+                short(&before["content"]),
+                short(&after["content"]),
+            );
+        }
+    }
+    for (name, after) in &current {
+        if !recorded.contains_key(name) {
+            deviations.push(Deviation {
+                path: name.clone(),
+                change: "added",
+                before: String::new(),
+                after: text(&after["type"]),
+            });
+        }
+    }
+
+    if deviations.is_empty() {
+        return Ok(None);
+    }
+    deviations.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut table = Table::new(deviations);
+    table.with(Style::empty());
+    table.modify(Columns::one(1), colors.alert.clone());
+    table.modify(Columns::one(2), colors.permission.clone());
+    table.modify(Columns::one(3), colors.alert.clone());
+    table.modify(Rows::first(), colors.dim.clone());
+    Ok(Some(table))
+}
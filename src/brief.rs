@@ -0,0 +1,108 @@
+//! `--output brief`: a short plain-text summary of a listing, narrow enough to
+//! paste into chat or a monitoring alert.
+
+use chrono::{DateTime, Local};
+use ds::{Entry, Row, Types, Units, escape_control, human_readable_size, is_broken_link};
+use std::fs;
+use tabled::grid::util::string::get_text_width;
+
+/// Names are cut to this many columns so every line stays narrow
+const NAME_WIDTH: usize = 40;
+/// How many entries each section shows at most
+const SHOWN: usize = 5;
+
+/// `name` cut to `width` columns, ending in `…` when it's longer.
+pub fn shorten(name: &str, width: usize) -> String {
+    if get_text_width(name) <= width {
+        return name.to_string();
+    }
+    let mut short = String::new();
+    for c in name.chars() {
+        if get_text_width(&short) + get_text_width(&c.to_string()) >= width {
+            break;
+        }
+        short.push(c);
+    }
+    short + "…"
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    match count {
+        1 => format!("1 {one}"),
+        _ => format!("{count} {many}"),
+    }
+}
+
+/// The summary of the rows, and the largest, latest and broken entries when
+/// their metadata is known (`entries` is empty for git revisions).
+pub fn render(path: &str, rows: &[Row], entries: &[Entry], units: Units) -> String {
+    let count =
+        |kind: fn(&Types) -> bool| rows.iter().filter(|(basic, ..)| kind(&basic.types)).count();
+    let sizes: Vec<(u64, &str)> = rows
+        .iter()
+        .filter(|(basic, ..)| matches!(basic.types, Types::File))
+        .filter_map(|(basic, _, binary, ..)| Some((binary.size.parse().ok()?, basic.name.as_str())))
+        .collect();
+    let total: u64 = sizes.iter().map(|(size, _)| size).sum();
+
+    let mut text = format!("{path}\n");
+    text.push_str(&format!(
+        "{}: {}, {}, {}, {} in files\n",
+        plural(rows.len(), "entry", "entries"),
+        plural(count(|kind| matches!(kind, Types::Dir)), "dir", "dirs"),
+        plural(count(|kind| matches!(kind, Types::File)), "file", "files"),
+        plural(
+            count(|kind| matches!(kind, Types::Symlink)),
+            "symlink",
+            "symlinks"
+        ),
+        human_readable_size(total, units),
+    ));
+
+    let mut largest = sizes;
+    largest.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+    if !largest.is_empty() {
+        text.push_str("\nLargest\n");
+        for (size, name) in largest.iter().take(SHOWN) {
+            let size = human_readable_size(*size, units);
+            text.push_str(&format!("  {size:>6}  {}\n", shorten(name, NAME_WIDTH)));
+        }
+    }
+
+    let mut latest: Vec<(DateTime<Local>, &Row)> = entries
+        .iter()
+        .zip(rows)
+        .filter_map(|(entry, row)| Some((entry.meta.modified().ok()?.into(), row)))
+        .collect();
+    latest.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+    if !latest.is_empty() {
+        text.push_str("\nLatest changes\n");
+        for (time, (basic, ..)) in latest.iter().take(SHOWN) {
+            let time = time.format("%Y-%m-%d %H:%M");
+            text.push_str(&format!("  {time}  {}\n", shorten(&basic.name, NAME_WIDTH)));
+        }
+    }
+
+    let broken: Vec<String> = entries
+        .iter()
+        .filter(|entry| is_broken_link(&entry.path, &entry.meta))
+        .map(|entry| {
+            let target = fs::read_link(&entry.path).unwrap_or_default();
+            format!(
+                "{} -> {}",
+                escape_control(&entry.name().to_string_lossy()),
+                escape_control(&target.to_string_lossy())
+            )
+        })
+        .collect();
+    if !broken.is_empty() {
+        text.push_str("\nBroken links\n");
+        for link in broken.iter().take(SHOWN) {
+            text.push_str(&format!("  {}\n", shorten(link, NAME_WIDTH)));
+        }
+        if broken.len() > SHOWN {
+            text.push_str(&format!("  and {} more\n", broken.len() - SHOWN));
+        }
+    }
+    text
+}
@@ -0,0 +1,90 @@
+//! Size budgets for subdirectories, declared in a `.ds-budget.toml` file:
+//!
+//! ```toml
+//! assets = "50M"
+//! "docs/images" = "5M"
+//! ```
+
+use crate::color::ColorScheme;
+use ds::{Units, dir_size, human_readable_size};
+use std::fs;
+use std::path::Path;
+use tabled::{
+    Table, Tabled,
+    settings::{Style, object::Columns, object::Rows},
+};
+
+pub const FILE_NAME: &str = ".ds-budget.toml";
+
+#[derive(Debug, Tabled, Clone)]
+struct Budget {
+    #[tabled(rename = "Directory")]
+    directory: String,
+    #[tabled(rename = "Used")]
+    used: String,
+    #[tabled(rename = "Budget")]
+    budget: String,
+    #[tabled(rename = "Usage")]
+    usage: String,
+}
+
+/// Parse sizes like `512`, `50M`, `1.5G` or `200KiB` (powers of 1024).
+fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let digits = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(digits);
+    let number: f64 = number.parse().ok()?;
+
+    let unit = unit.trim().trim_end_matches("iB").trim_end_matches('B');
+    let exponent = match unit.to_ascii_uppercase().as_str() {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        "P" => 5,
+        _ => return None,
+    };
+    Some((number * 1024f64.powi(exponent)) as u64)
+}
+
+pub fn render(path: &Path, units: Units, colors: &ColorScheme) -> Result<Table, String> {
+    let text = fs::read_to_string(path.join(FILE_NAME)).map_err(|err| err.to_string())?;
+    let limits: toml::Table = text
+        .parse()
+        .map_err(|err: toml::de::Error| err.to_string())?;
+
+    let mut rows = Vec::new();
+    let mut overruns = Vec::new();
+    for (directory, limit) in &limits {
+        let limit = limit
+            .as_str()
+            .and_then(parse_size)
+            .or_else(|| limit.as_integer().map(|bytes| bytes as u64))
+            .ok_or(format!("invalid size for '{directory}'"))?;
+        let used = dir_size(&path.join(directory));
+
+        if used > limit {
+            overruns.push(rows.len());
+        }
+        rows.push(Budget {
+            directory: directory.clone(),
+            used: human_readable_size(used, units),
+            budget: human_readable_size(limit, units),
+            usage: format!("{:.0}%", used as f64 / limit.max(1) as f64 * 100.0),
+        });
+    }
+
+    let mut table = Table::new(rows);
+    table.with(Style::empty());
+    table.modify(Columns::one(1), colors.size.clone());
+    table.modify(Columns::one(2), colors.size.clone());
+    table.modify(Columns::last(), colors.permission.clone());
+    for row in overruns {
+        table.modify(Rows::one(row + 1), colors.alert.clone());
+    }
+    table.modify(Rows::first(), colors.dim.clone());
+    Ok(table)
+}
@@ -0,0 +1,134 @@
+//! `--cache`: directory totals, match counts, the hashes of `ds baseline
+//! create --cache` and the listings `ds daemon` indexes, kept between runs in
+//! `$XDG_CACHE_HOME/ds/cache.json`, or `~/.cache/ds/cache.json`, so going over
+//! the same tree again is quick.
+//! Values are keyed by the device, inode and modification time of the entry
+//! they were worked out for.
+//!
+//! A directory's modification time only changes when entries are added to it,
+//! removed or renamed, so its cached total misses files growing further down.
+//! That's why it's opt-in: `cache = true` in config.toml turns it on for good,
+//! `--no-cache` off for one run, and `ds cache clear` forgets everything.
+
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::env;
+use std::fs::{self, Metadata};
+use std::io::ErrorKind;
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Past this many values, only the ones used by the last run are kept
+const LIMIT: usize = 200_000;
+
+fn file() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".cache")))?;
+    Some(dir.join("ds").join("cache.json"))
+}
+
+pub struct Cache {
+    /// What was read from the file, then what this run worked out on top
+    values: Mutex<Map<String, Value>>,
+    /// The keys this run looked up or added
+    used: Mutex<Vec<String>>,
+    /// Whether anything was added since the values were read or saved
+    changed: AtomicBool,
+}
+
+impl Cache {
+    /// The cache as the last run left it, empty when there's none or it can't
+    /// be read.
+    pub fn load() -> Cache {
+        let values = file()
+            .and_then(|file| fs::read_to_string(file).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Cache {
+            values: Mutex::new(values),
+            used: Mutex::default(),
+            changed: AtomicBool::new(false),
+        }
+    }
+
+    /// The `kind` of value of the entry with `meta`, like `size`, worked out
+    /// with `compute` unless it's cached already. What couldn't be worked out,
+    /// or only partly because the run was interrupted, isn't kept.
+    pub fn get(
+        &self,
+        kind: &str,
+        meta: &Metadata,
+        compute: impl FnOnce() -> Option<Value>,
+    ) -> Option<Value> {
+        let key = format!(
+            "{kind}:{}:{}:{}.{:09}",
+            meta.dev(),
+            meta.ino(),
+            meta.mtime(),
+            meta.mtime_nsec()
+        );
+        if let Ok(mut used) = self.used.lock() {
+            used.push(key.clone());
+        }
+        let cached = self
+            .values
+            .lock()
+            .ok()
+            .and_then(|values| values.get(&key).cloned());
+        if cached.is_some() {
+            return cached;
+        }
+        let value = compute()?;
+        if !ds::interrupted()
+            && let Ok(mut values) = self.values.lock()
+        {
+            values.insert(key, value.clone());
+            self.changed.store(true, Ordering::Relaxed);
+        }
+        Some(value)
+    }
+
+    /// Write the values back for the next run when anything was added. What's
+    /// used is counted afresh from there, so `ds daemon` can save after each
+    /// of its passes. Failing to is no reason to fail the listing, so errors
+    /// are ignored.
+    pub fn save(&self) {
+        let (Some(file), Ok(mut values), Ok(mut used)) =
+            (file(), self.values.lock(), self.used.lock())
+        else {
+            return;
+        };
+        let used = std::mem::take(&mut *used);
+        if !self.changed.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        if values.len() > LIMIT {
+            let used: HashSet<String> = used.into_iter().collect();
+            values.retain(|key, _| used.contains(key));
+        }
+        if let Some(dir) = file.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(text) = serde_json::to_string(&*values) {
+            // Written aside and renamed so concurrent runs never read half of it
+            let partial = file.with_extension(format!("json.{}", std::process::id()));
+            if fs::write(&partial, text).is_ok() {
+                let _ = fs::rename(&partial, &file);
+            }
+        }
+    }
+}
+
+/// Remove the cache file. Returns it, or None when there was nothing to remove.
+pub fn clear() -> Result<Option<PathBuf>, String> {
+    let file = file().ok_or("no home directory to keep the cache in")?;
+    match fs::remove_file(&file) {
+        Ok(()) => Ok(Some(file)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(format!("{}: {err}", file.display())),
+    }
+}
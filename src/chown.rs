@@ -0,0 +1,145 @@
+//! Dry run of `chown <spec>` over a directory's entries.
+//!
+//! The spec follows chown: `owner`, `owner:group`, `:group`, or `owner:` for the
+//! owner's login group. Names and numeric ids are both accepted.
+
+use crate::color::ColorScheme;
+use ds::{DirLister, SortField, group_and_owner_mode};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use tabled::{
+    Table, Tabled,
+    settings::{Style, object::Columns, object::Rows},
+};
+use users::{get_group_by_gid, get_group_by_name, get_user_by_name, get_user_by_uid};
+
+#[derive(Debug, Tabled, Clone)]
+struct Change {
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Owner")]
+    owner: String,
+    #[tabled(rename = "New Owner")]
+    new_owner: String,
+    #[tabled(rename = "Group")]
+    group: String,
+    #[tabled(rename = "New Group")]
+    new_group: String,
+}
+
+/// A resolved user or group: its id and the name to show.
+type Id = (u32, String);
+
+fn user(text: &str) -> Result<Id, String> {
+    match text.parse::<u32>() {
+        Ok(uid) => Ok((
+            uid,
+            get_user_by_uid(uid)
+                .map(|user| user.name().to_string_lossy().into_owned())
+                .unwrap_or(text.into()),
+        )),
+        Err(_) => get_user_by_name(text)
+            .map(|user| (user.uid(), text.into()))
+            .ok_or(format!("unknown user '{text}'")),
+    }
+}
+
+fn group(text: &str) -> Result<Id, String> {
+    match text.parse::<u32>() {
+        Ok(gid) => Ok((
+            gid,
+            get_group_by_gid(gid)
+                .map(|group| group.name().to_string_lossy().into_owned())
+                .unwrap_or(text.into()),
+        )),
+        Err(_) => get_group_by_name(text)
+            .map(|group| (group.gid(), text.into()))
+            .ok_or(format!("unknown group '{text}'")),
+    }
+}
+
+/// The owner and group a spec sets, `None` for the ones it leaves alone.
+fn parse_spec(spec: &str) -> Result<(Option<Id>, Option<Id>), String> {
+    let (owner, group_part) = match spec.split_once(':') {
+        Some((owner, group)) => (owner, Some(group)),
+        None => (spec, None),
+    };
+    let owner = (!owner.is_empty()).then(|| user(owner)).transpose()?;
+
+    let group = match group_part {
+        Some("") => match &owner {
+            // `owner:` means the owner's login group
+            Some((uid, name)) => {
+                let gid = get_user_by_uid(*uid)
+                    .map(|user| user.primary_group_id())
+                    .ok_or(format!("'{name}' has no login group"))?;
+                Some(self::group(&gid.to_string())?)
+            }
+            None => None,
+        },
+        Some(name) => Some(self::group(name)?),
+        None => None,
+    };
+
+    if owner.is_none() && group.is_none() {
+        return Err(format!("invalid spec '{spec}'"));
+    }
+    Ok((owner, group))
+}
+
+/// The entries of `path` whose owner or group `chown <spec>` would change, or
+/// `None` when it wouldn't change anything.
+pub fn render(
+    path: &Path,
+    spec: &str,
+    show_hidden: bool,
+    colors: &ColorScheme,
+) -> Result<Option<Table>, String> {
+    if !path.is_dir() {
+        return Err(format!("'{}' is not a directory", path.display()));
+    }
+    let (owner, group) = parse_spec(spec)?;
+
+    let mut rows = Vec::new();
+    for entry in (DirLister {
+        all: show_hidden,
+        sort: SortField::FileType,
+        ..DirLister::default()
+    })
+    .list(path)
+    {
+        // chown follows symlinks unless given -h
+        let meta = fs::metadata(&entry.path).unwrap_or(entry.meta.clone());
+        let owner_changes = owner.as_ref().filter(|(uid, _)| *uid != meta.uid());
+        let group_changes = group.as_ref().filter(|(gid, _)| *gid != meta.gid());
+        if owner_changes.is_none() && group_changes.is_none() {
+            continue;
+        }
+
+        let current = group_and_owner_mode(&meta);
+        rows.push(Change {
+            name: entry.name().to_string_lossy().into_owned(),
+            new_owner: owner_changes
+                .map(|(_, name)| name.clone())
+                .unwrap_or("-".into()),
+            new_group: group_changes
+                .map(|(_, name)| name.clone())
+                .unwrap_or("-".into()),
+            owner: current.owner,
+            group: current.group,
+        });
+    }
+
+    if rows.is_empty() {
+        return Ok(None);
+    }
+    let mut table = Table::new(rows);
+    table.with(Style::empty());
+    table.modify(Columns::one(1), colors.owner.clone());
+    table.modify(Columns::one(2), colors.permission.clone());
+    table.modify(Columns::one(3), colors.owner.clone());
+    table.modify(Columns::one(4), colors.permission.clone());
+    table.modify(Rows::first(), colors.dim.clone());
+    Ok(Some(table))
+}
@@ -0,0 +1,108 @@
+//! `--copy`: the listing, or the listed paths, on the system clipboard.
+//!
+//! On X11 and Wayland what's copied is served by the program that copied it
+//! and goes away when that program exits. So `ds` starts itself again in the
+//! background, and that copy keeps serving the text until something else is
+//! copied.
+//!
+//! Where that clipboard is out of reach, like over SSH or in tmux,
+//! `DS_COPY_COMMAND` names a shell command that gets the text on stdin
+//! instead, like `tmux load-buffer -`.
+
+use arboard::{Clipboard, SetExtLinux};
+use std::ffi::OsStr;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+
+/// Set on the background copy of `ds` that serves the clipboard
+const SERVE: &str = "DS_CLIPBOARD_SERVE";
+
+/// Copies with a command of the user's instead of the system clipboard
+const COMMAND: &str = "DS_COPY_COMMAND";
+
+/// Give `text` to the `DS_COPY_COMMAND` command.
+fn pipe(command: &OsStr, text: &str) -> Result<(), String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("can't start {COMMAND}: {err}"))?;
+    let written = child
+        .stdin
+        .take()
+        .ok_or("the copy command takes no input")?
+        .write_all(text.as_bytes());
+    let status = child.wait().map_err(|err| err.to_string())?;
+    written.map_err(|err| err.to_string())?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(format!("{COMMAND} exited with {status}")),
+    }
+}
+
+/// Put `text` on the clipboard, for as long as nothing else replaces it.
+pub fn copy(text: &str) -> Result<(), String> {
+    if let Some(command) = std::env::var_os(COMMAND).filter(|command| !command.is_empty()) {
+        return pipe(&command, text);
+    }
+    let program = std::env::current_exe().map_err(|err| err.to_string())?;
+    let mut child = Command::new(program)
+        .env(SERVE, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        // Out of the way of Ctrl-C in the terminal it was started from
+        .process_group(0)
+        .spawn()
+        .map_err(|err| format!("can't start the clipboard helper: {err}"))?;
+    child
+        .stdin
+        .take()
+        .ok_or("the clipboard helper takes no input")?
+        .write_all(text.as_bytes())
+        .map_err(|err| err.to_string())?;
+
+    // One line once the text is on the clipboard, or why it couldn't be
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or("the clipboard helper has no output")?;
+    let mut answer = String::new();
+    BufReader::new(stdout)
+        .read_line(&mut answer)
+        .map_err(|err| err.to_string())?;
+    match answer.trim_end() {
+        "ok" => Ok(()),
+        "" => Err("the clipboard helper exited".into()),
+        err => Err(err.to_string()),
+    }
+}
+
+/// Serve the clipboard when this is the background copy started by [`copy`]:
+/// the text comes on stdin, and the answer goes to stdout before waiting for
+/// the clipboard to be taken over. False in any other run of `ds`.
+pub fn serve() -> bool {
+    if std::env::var_os(SERVE).is_none() {
+        return false;
+    }
+    let mut text = String::new();
+    let _ = io::stdin().read_to_string(&mut text);
+    let mut stdout = io::stdout();
+    let answer = Clipboard::new().and_then(|mut clipboard| {
+        clipboard.set_text(text.clone())?;
+        Ok(clipboard)
+    });
+    match answer {
+        Ok(mut clipboard) => {
+            let _ = writeln!(stdout, "ok");
+            let _ = stdout.flush();
+            let _ = clipboard.set().wait().text(text);
+        }
+        Err(err) => {
+            let _ = writeln!(stdout, "{err}");
+        }
+    }
+    true
+}
@@ -0,0 +1,574 @@
+//! Colors of the listing, named after what they mark rather than their hue, so
+//! palettes can swap them as a whole.
+
+use clap::ValueEnum;
+use ds::Types;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::os::fd::AsRawFd;
+use tabled::settings::Color;
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum Palette {
+    #[default]
+    Default,
+    /// Safe for red-green color blindness (weak green cones)
+    Deuteranopia,
+    /// Safe for red-green color blindness (weak red cones)
+    Protanopia,
+}
+
+/// When to color the output, as in `--color`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum ColorMode {
+    /// Color terminals, unless the environment says otherwise
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Background {
+    Dark,
+    Light,
+}
+
+pub fn stdout_is_terminal() -> bool {
+    // SAFETY: isatty only inspects the descriptor.
+    unsafe { libc::isatty(libc::STDOUT_FILENO) != 0 }
+}
+
+/// Whether to color the output. `always` and `never` decide alone; `auto`
+/// follows the CLICOLOR conventions: `CLICOLOR_FORCE` (other than `0`) always
+/// colors, `NO_COLOR` and `CLICOLOR=0` never do, and otherwise only terminals
+/// are colored, so piped output has no escape codes.
+pub fn enabled(mode: ColorMode) -> bool {
+    let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    match mode {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto if var("CLICOLOR_FORCE").is_some_and(|value| value != "0") => true,
+        ColorMode::Auto if var("NO_COLOR").is_some() => false,
+        ColorMode::Auto if var("CLICOLOR").is_some_and(|value| value == "0") => false,
+        ColorMode::Auto => stdout_is_terminal(),
+    }
+}
+
+/// How many colors the terminal shows, fewest first.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Depth {
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// The terminal's color depth: `COLORTERM=truecolor` (or `24bit`) and
+/// `TERM=*-direct` mean 24-bit colors, `TERM=*-256color` means 256, and
+/// anything else is assumed to know only the 16 basic colors.
+pub fn depth() -> Depth {
+    let var = |name| std::env::var(name).unwrap_or_default();
+    let term = var("TERM");
+    if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") || term.ends_with("-direct") {
+        Depth::TrueColor
+    } else if term.contains("256color") {
+        Depth::Ansi256
+    } else {
+        Depth::Ansi16
+    }
+}
+
+/// The 16 basic colors as xterm shows them by default, to tell the RGB of the
+/// first 16 of the 256 colors.
+const BASIC: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Levels of each channel in the 6x6x6 cube of the 256 colors
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r, g, b): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let channel = |one: u8, other: u8| (one as i32 - other as i32).pow(2) as u32;
+    channel(r, r2) + channel(g, g2) + channel(b, b2)
+}
+
+/// The RGB of one of the 256 colors.
+fn rgb_of(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASIC[index as usize],
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE[(index / 36) as usize],
+                CUBE[(index / 6 % 6) as usize],
+                CUBE[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let gray = 8 + 10 * (index - 232);
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// The nearest of the 256 colors, from the cube or the gray ramp.
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    (16..=255)
+        .min_by_key(|index| distance(rgb, rgb_of(*index)))
+        .unwrap_or(16)
+}
+
+/// The basic color of the same hue: each channel rounded on or off, made
+/// bright when the color is, and black when it's nearly so. Nearest by
+/// distance would turn most muted colors gray.
+fn nearest_16((r, g, b): (u8, u8, u8)) -> u8 {
+    let on = |channel: u8| (channel >= 128) as u8;
+    let brightness = (r.max(g).max(b) as u32 * 2 + 127) / 255;
+    match brightness {
+        0 => 0,
+        1 => on(r) | on(g) << 1 | on(b) << 2,
+        _ => 8 + (on(r) | on(g) << 1 | on(b) << 2),
+    }
+}
+
+/// SGR codes with the 24-bit and 256-color ones (`38;2;r;g;b`, `48;5;n`...)
+/// swapped for their nearest equivalents at `depth`.
+fn downgrade_codes(codes: &str, depth: Depth) -> String {
+    let codes: Vec<&str> = codes.split(';').collect();
+    let number = |at: usize| codes.get(at).and_then(|code| code.parse::<u8>().ok());
+    let mut result = Vec::new();
+    let mut at = 0;
+    while at < codes.len() {
+        let (layer @ ("38" | "48"), Some(kind)) = (codes[at], codes.get(at + 1)) else {
+            result.push(codes[at].to_string());
+            at += 1;
+            continue;
+        };
+        let (rgb, index, width) = match (*kind, number(at + 2), number(at + 3), number(at + 4)) {
+            ("2", Some(r), Some(g), Some(b)) => ((r, g, b), None, 5),
+            ("5", Some(index), ..) => (rgb_of(index), Some(index), 3),
+            // Not a color this understands, kept as it is
+            _ => {
+                result.push(codes[at].to_string());
+                at += 1;
+                continue;
+            }
+        };
+        let code = match (depth, index) {
+            (Depth::TrueColor, _) | (Depth::Ansi256, Some(_)) => codes[at..at + width].join(";"),
+            (Depth::Ansi256, None) => format!("{layer};5;{}", nearest_256(rgb)),
+            (Depth::Ansi16, _) => {
+                let basic = match index {
+                    Some(index) if index < 16 => index,
+                    _ => nearest_16(rgb),
+                };
+                // 30-37 and 90-97 for text, 40-47 and 100-107 for backgrounds
+                let base = if layer == "38" { 30 } else { 40 };
+                match basic {
+                    0..=7 => format!("{}", base + basic),
+                    _ => format!("{}", base + 60 + basic - 8),
+                }
+            }
+        };
+        result.push(code);
+        at += width;
+    }
+    result.join(";")
+}
+
+/// `color` with its codes downgraded to `depth`; other sequences are kept.
+fn downgrade(color: &Color, depth: Depth) -> Color {
+    let prefix = color.get_prefix();
+    match prefix
+        .strip_prefix("\x1b[")
+        .and_then(|codes| codes.strip_suffix('m'))
+    {
+        Some(codes) => Color::new(
+            format!("\x1b[{}m", downgrade_codes(codes, depth)),
+            color.get_suffix().to_string(),
+        ),
+        None => color.clone(),
+    }
+}
+
+/// The terminal's background, from `COLORFGBG` (`fg;bg` color indexes, set by
+/// rxvt, Konsole and others) or else, with `probe`, by asking the terminal
+/// with OSC 11. Dark when neither answers.
+pub fn background(probe: bool) -> Background {
+    if let Ok(colors) = std::env::var("COLORFGBG")
+        && let Some(Ok(index)) = colors.rsplit(';').next().map(str::parse::<u8>)
+    {
+        // Indexes 7 and 9-15 are white and the bright colors
+        return match index {
+            7 | 9..=15 => Background::Light,
+            _ => Background::Dark,
+        };
+    }
+
+    if !probe || !stdout_is_terminal() {
+        return Background::Dark;
+    }
+    match query_background() {
+        // Relative luminance of the reported color
+        Some((r, g, b)) if 0.2126 * r + 0.7152 * g + 0.0722 * b > 0.5 => Background::Light,
+        _ => Background::Dark,
+    }
+}
+
+/// Write `request` to the terminal and read its answer. Every terminal answers
+/// the primary device attributes request (DA1) sent after it, and answers in
+/// order, so its reply marks the end of the answer, which is empty when the
+/// request isn't supported. Nothing is left over to show up in the shell later.
+/// None without a terminal, or when it stays silent for 100ms.
+pub fn query_terminal(request: &[u8]) -> Option<Vec<u8>> {
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    let fd = tty.as_raw_fd();
+
+    // The answer arrives as input, so echo and line buffering must be off
+    // SAFETY: `termios` is filled by tcgetattr before use and restored below.
+    let saved = unsafe {
+        let mut saved: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut saved) != 0 {
+            return None;
+        }
+        let mut raw = saved;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+        libc::tcsetattr(fd, libc::TCSANOW, &raw);
+        saved
+    };
+
+    let mut answer = Vec::new();
+    let mut attributes = None;
+    if tty.write_all(&[request, b"\x1b[c"].concat()).is_ok() {
+        let mut poll = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let mut byte = [0u8; 1];
+        // SAFETY: `poll` points to a single valid pollfd.
+        while unsafe { libc::poll(&mut poll, 1, 100) } > 0 && matches!(tty.read(&mut byte), Ok(1)) {
+            answer.push(byte[0]);
+            // ESC [ ? attributes c
+            attributes = (byte[0] == b'c')
+                .then(|| answer.windows(3).rposition(|start| start == b"\x1b[?"))
+                .flatten();
+            if attributes.is_some() {
+                break;
+            }
+        }
+    }
+    // SAFETY: puts back the settings read above, and drops what a slow
+    // terminal may still be answering.
+    unsafe {
+        if attributes.is_none() {
+            libc::tcflush(fd, libc::TCIFLUSH);
+        }
+        libc::tcsetattr(fd, libc::TCSANOW, &saved);
+    }
+    answer.truncate(attributes?);
+    Some(answer)
+}
+
+/// Ask the terminal for its background color with OSC 11, as RGB in 0..=1.
+fn query_background() -> Option<(f64, f64, f64)> {
+    let answer = query_terminal(b"\x1b]11;?\x1b\\")?;
+
+    // ESC ] 11 ; rgb:RRRR/GGGG/BBBB (ST | BEL)
+    let answer = String::from_utf8_lossy(&answer);
+    let rgb = answer.split("rgb:").nth(1)?;
+    let mut channels = rgb.split(['/', '\x07', '\x1b']).take(3).map(|hex| {
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        Some(value as f64 / 16f64.powi(hex.len() as i32))
+    });
+    Some((channels.next()??, channels.next()??, channels.next()??))
+}
+
+pub struct ColorScheme {
+    /// The Type column
+    pub kind: Color,
+    /// Sizes
+    pub size: Color,
+    /// Owners, groups and other people-related columns
+    pub owner: Color,
+    /// Timestamps and dates
+    pub time: Color,
+    /// Permissions and other "as expected" values
+    pub permission: Color,
+    /// Free-form annotations like commit subjects and plugin columns
+    pub note: Color,
+    /// Project annotations
+    pub project: Color,
+    /// Crate annotations
+    pub crate_name: Color,
+    /// Values that need attention: overruns, wrong modes, churn, errors
+    pub alert: Color,
+    /// Headers, hints and entries set aside, like dependency folders
+    pub dim: Color,
+}
+
+impl ColorScheme {
+    pub fn new(palette: Palette, background: Background) -> Self {
+        // The deuteranopia and protanopia palettes use Okabe & Ito's colors
+        let purple = Color::rgb_fg(204, 121, 167);
+        let orange = Color::rgb_fg(230, 159, 0);
+        let sky_blue = Color::rgb_fg(86, 180, 233);
+        let bluish_green = Color::rgb_fg(0, 158, 115);
+        let yellow = Color::rgb_fg(240, 228, 66);
+        let blue = Color::rgb_fg(0, 114, 178);
+        let vermillion = Color::rgb_fg(213, 94, 0);
+        // Yellow can't be read on a light background, black takes its place
+        let yellow = match background {
+            Background::Dark => yellow,
+            Background::Light => Color::rgb_fg(0, 0, 0),
+        };
+
+        let dim = Color::FG_BRIGHT_BLACK;
+
+        match palette {
+            Palette::Default if background == Background::Light => ColorScheme {
+                kind: Color::FG_MAGENTA,
+                size: Color::FG_BLUE,
+                owner: Color::FG_CYAN,
+                time: Color::FG_BLACK,
+                permission: Color::FG_GREEN,
+                note: Color::FG_CYAN,
+                project: Color::FG_GREEN,
+                crate_name: Color::FG_RED,
+                alert: Color::FG_RED,
+                dim: dim.clone(),
+            },
+            Palette::Default => ColorScheme {
+                kind: Color::FG_MAGENTA,
+                size: Color::FG_BRIGHT_YELLOW,
+                owner: Color::FG_BLUE,
+                time: Color::FG_YELLOW,
+                permission: Color::FG_BRIGHT_GREEN,
+                note: Color::FG_CYAN,
+                project: Color::FG_GREEN,
+                crate_name: Color::FG_BRIGHT_RED,
+                alert: Color::FG_RED,
+                dim: dim.clone(),
+            },
+            Palette::Deuteranopia => ColorScheme {
+                kind: purple,
+                size: orange,
+                owner: sky_blue.clone(),
+                time: yellow,
+                permission: bluish_green.clone(),
+                note: sky_blue,
+                project: bluish_green,
+                crate_name: vermillion.clone(),
+                alert: vermillion,
+                dim,
+            },
+            // Reds look dark with weak red cones, so alerts use purple instead
+            Palette::Protanopia => ColorScheme {
+                kind: blue,
+                size: yellow,
+                owner: sky_blue.clone(),
+                time: orange,
+                permission: bluish_green.clone(),
+                note: sky_blue,
+                project: bluish_green,
+                crate_name: purple.clone(),
+                alert: purple,
+                dim,
+            },
+        }
+    }
+
+    /// Swap colors the terminal can't show for the nearest ones it can.
+    pub fn downgrade(&mut self, depth: Depth) {
+        for color in [
+            &mut self.kind,
+            &mut self.size,
+            &mut self.owner,
+            &mut self.time,
+            &mut self.permission,
+            &mut self.note,
+            &mut self.project,
+            &mut self.crate_name,
+            &mut self.alert,
+            &mut self.dim,
+        ] {
+            *color = downgrade(color, depth);
+        }
+    }
+
+    /// No colors at all.
+    pub fn none() -> Self {
+        ColorScheme {
+            kind: Color::empty(),
+            size: Color::empty(),
+            owner: Color::empty(),
+            time: Color::empty(),
+            permission: Color::empty(),
+            note: Color::empty(),
+            project: Color::empty(),
+            crate_name: Color::empty(),
+            alert: Color::empty(),
+            dim: Color::empty(),
+        }
+    }
+}
+
+/// Name colors from `LS_COLORS`, as set by `dircolors`, so names look the way
+/// they do in `ls`.
+#[derive(Debug, Clone, Default)]
+pub struct LsColors {
+    /// By two-letter file type key, like `di` or `ex`
+    types: HashMap<String, Color>,
+    /// By lowercase name suffix, from `*.tar.gz`-style keys
+    suffixes: Vec<(String, Color)>,
+}
+
+impl LsColors {
+    /// The colors in `LS_COLORS`, None when it's unset or empty.
+    pub fn from_env() -> Option<LsColors> {
+        let value = std::env::var("LS_COLORS").ok()?;
+        let colors = LsColors::parse(&value);
+        match colors.types.is_empty() && colors.suffixes.is_empty() {
+            true => None,
+            false => Some(colors),
+        }
+    }
+
+    /// Parse `key=SGR:key=SGR...`. Unknown keys and values that aren't SGR
+    /// codes (like `ln=target`) are skipped.
+    pub fn parse(value: &str) -> LsColors {
+        let mut colors = LsColors::default();
+        for (key, codes) in value.split(':').filter_map(|item| item.split_once('=')) {
+            if codes.is_empty() || !codes.bytes().all(|c| c.is_ascii_digit() || c == b';') {
+                continue;
+            }
+            let color = Color::new(format!("\x1b[{codes}m"), "\x1b[0m");
+            match key.strip_prefix('*') {
+                Some(suffix) => {
+                    // Later keys win, as with the type keys
+                    let suffix = suffix.to_lowercase();
+                    colors.suffixes.retain(|(known, _)| *known != suffix);
+                    colors.suffixes.push((suffix, color));
+                }
+                None => {
+                    colors.types.insert(key.to_string(), color);
+                }
+            }
+        }
+        // The longest matching suffix wins, so `*.tar.gz` beats `*.gz`
+        colors
+            .suffixes
+            .sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+        colors
+    }
+
+    /// Swap colors the terminal can't show for the nearest ones it can.
+    pub fn downgrade(&mut self, depth: Depth) {
+        let colors = self
+            .types
+            .values_mut()
+            .chain(self.suffixes.iter_mut().map(|(_, color)| color));
+        for color in colors {
+            *color = downgrade(color, depth);
+        }
+    }
+
+    /// The color of a name, by its type and mode like `ls` does: special modes
+    /// first, then executables, then the name's suffix for regular files.
+    /// None leaves the name in the terminal's default color.
+    pub fn color(&self, name: &str, kind: &Types, mode: u32, broken: bool) -> Option<Color> {
+        let get = |key: &str| self.types.get(key).filter(|color| !is_reset(color));
+        let by_type = match kind {
+            Types::Dir if mode & 0o1002 == 0o1002 => get("tw").or(get("ow")).or(get("di")),
+            Types::Dir if mode & 0o0002 != 0 => get("ow").or(get("di")),
+            Types::Dir if mode & 0o1000 != 0 => get("st").or(get("di")),
+            Types::Dir => get("di"),
+            Types::Symlink if broken => get("or").or(get("ln")),
+            Types::Symlink => get("ln"),
+            Types::Fifo => get("pi"),
+            Types::Socket => get("so"),
+            Types::BlockDev => get("bd"),
+            Types::CharDev => get("cd"),
+            Types::File if mode & 0o4000 != 0 && get("su").is_some() => get("su"),
+            Types::File if mode & 0o2000 != 0 && get("sg").is_some() => get("sg"),
+            Types::File if mode & 0o111 != 0 && get("ex").is_some() => get("ex"),
+            Types::File => {
+                let name = name.to_lowercase();
+                let suffix = self
+                    .suffixes
+                    .iter()
+                    .find(|(suffix, _)| name.ends_with(suffix));
+                match suffix {
+                    Some((_, color)) if is_reset(color) => None,
+                    Some((_, color)) => Some(color),
+                    None => get("fi"),
+                }
+            }
+        };
+        by_type.cloned()
+    }
+}
+
+/// The SGR codes of a color written for people, like `bold blue`,
+/// `bright-red`, `#e69f00`, `208` (of the 256 colors) or raw codes like
+/// `1;34`. None when a word isn't understood.
+pub fn sgr(spec: &str) -> Option<String> {
+    if !spec.is_empty() && spec.bytes().all(|c| c.is_ascii_digit() || c == b';') {
+        return Some(match spec.parse::<u8>() {
+            Ok(index) => format!("38;5;{index}"),
+            Err(_) => spec.to_string(),
+        });
+    }
+    let colors = [
+        "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    ];
+    let mut codes = Vec::new();
+    for word in spec.split_whitespace() {
+        let word = word.to_lowercase();
+        let code = match word.as_str() {
+            "bold" => "1".to_string(),
+            "dim" => "2".to_string(),
+            "italic" => "3".to_string(),
+            "underline" => "4".to_string(),
+            "reverse" => "7".to_string(),
+            hex if hex.starts_with('#') && hex.len() == 7 && hex.is_ascii() => {
+                let channel = |at: usize| u8::from_str_radix(&hex[at..at + 2], 16).ok();
+                format!("38;2;{};{};{}", channel(1)?, channel(3)?, channel(5)?)
+            }
+            name => match name.strip_prefix("bright-") {
+                Some(name) => format!("{}", 90 + colors.iter().position(|known| *known == name)?),
+                None => format!("{}", 30 + colors.iter().position(|known| *known == name)?),
+            },
+        };
+        codes.push(code);
+    }
+    match codes.is_empty() {
+        true => None,
+        false => Some(codes.join(";")),
+    }
+}
+
+/// `0` and `00` mean the default color.
+fn is_reset(color: &Color) -> bool {
+    matches!(color.get_prefix(), "\x1b[0m" | "\x1b[00m")
+}
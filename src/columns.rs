@@ -0,0 +1,431 @@
+//! The columns of the listing table: each enabled option contributes a header
+//! and one cell per entry, so options combine freely and a new column only
+//! needs a `Field` and the arm of [`field`] producing its cells.
+
+use crate::cache::Cache;
+use crate::color::ColorScheme;
+use crate::dedupe::Seen;
+use crate::git::{self, Commit};
+use crate::{effective, libs, matches, mounts, pkg, plugin, project, reflink, spark, systemd};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use ds::{Column, Entry, Row, Units, escape_control};
+use regex::bytes::Regex;
+use std::cell::OnceCell;
+use std::fs::{self, Metadata};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+/// The columns a listing can have, selectable with `--columns` and `--format`.
+/// The options adding a column, like `--git`, add its field.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Field {
+    Name,
+    Type,
+    Size,
+    Binary,
+    Owner,
+    Group,
+    Modified,
+    Accessed,
+    Created,
+    #[value(alias = "perm")]
+    Permission,
+    /// When the metadata last changed, `ctime`
+    #[value(alias = "ctime")]
+    Changed,
+    Inode,
+    /// The number of hard links
+    Links,
+    Uid,
+    Gid,
+    /// The permission bits in octal, like 755
+    Mode,
+    /// Where a symlink points
+    Target,
+    /// Whether a file is a Git LFS pointer
+    Lfs,
+    #[value(alias = "cargo")]
+    Crate,
+    Project,
+    LinkTarget,
+    #[value(alias = "match-count")]
+    Matches,
+    #[value(alias = "age-spark")]
+    Ages,
+    /// The other paths of an entry, with --dedupe
+    AlsoAt,
+    #[value(alias = "reflink")]
+    SharedExtents,
+    Badges,
+    #[value(alias = "libs")]
+    Libraries,
+    #[value(alias = "pkg")]
+    Package,
+    Mount,
+    UnitState,
+    Effective,
+    Git,
+    LastCommit,
+    Author,
+    CommitDate,
+    Churn,
+}
+
+/// What the columns of a listing are worked out from.
+pub struct Context<'a> {
+    /// The listed directory
+    pub path: &'a Path,
+    /// The entries of the rows, none for revisions and saved listings, which
+    /// have no metadata
+    pub entries: &'a [Entry],
+    pub rows: &'a [Row],
+    pub units: Units,
+    /// Whether hidden files are listed
+    pub all: bool,
+    pub colors: &'a ColorScheme,
+    /// Which rows are Git LFS pointers
+    pub lfs: &'a [bool],
+    pub seen: Option<&'a Seen>,
+    pub cache: Option<&'a Cache>,
+    /// What the matches column counts, from --match-count
+    pub pattern: Option<&'a Regex>,
+    /// The months the churn column goes back, from --churn
+    pub months: Option<u32>,
+    /// The last commits, looked up once for the three columns of them
+    pub commits: OnceCell<Result<Vec<Option<Commit>>, String>>,
+}
+
+impl Context<'_> {
+    fn paths(&self) -> Vec<PathBuf> {
+        self.entries
+            .iter()
+            .map(|entry| entry.path.clone())
+            .collect()
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|entry| entry.name().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// `cell` of each entry's last commit, empty where there's none.
+    fn commits(&self, cell: impl Fn(&Commit) -> String) -> Result<Vec<String>, String> {
+        let commits = self
+            .commits
+            .get_or_init(|| git::last_commits(self.path, &self.names()))
+            .as_ref()
+            .map_err(|err| format!("Can't read git history ({err})"))?;
+        Ok(commits
+            .iter()
+            .map(|commit| commit.as_ref().map(&cell).unwrap_or_default())
+            .collect())
+    }
+}
+
+/// The cells of one of the fields of `rows`, without colors.
+fn cells(rows: &[Row], cell: impl Fn(&Row) -> String) -> Vec<String> {
+    rows.iter().map(cell).collect()
+}
+
+/// A cell worked out from the metadata of each entry.
+fn metadata(entries: &[Entry], cell: impl Fn(&Metadata) -> String) -> Vec<String> {
+    entries.iter().map(|entry| cell(&entry.meta)).collect()
+}
+
+/// The column of `field`, or why it couldn't be worked out.
+pub fn field(field: Field, context: &Context) -> Result<Column, String> {
+    let Context {
+        rows,
+        entries,
+        units,
+        colors,
+        ..
+    } = *context;
+    let column = match field {
+        Field::Name => Column::plain("Name", cells(rows, |(basic, ..)| basic.name.clone())),
+        Field::Type => Column::new(
+            "Type",
+            cells(rows, |(basic, ..)| basic.types.to_string()),
+            colors.kind.clone(),
+        ),
+        // Label the unit system so copied or shared listings stay unambiguous
+        Field::Size => Column::new(
+            format!("Size ({units})"),
+            cells(rows, |(_, size, ..)| size.size.clone()),
+            colors.size.clone(),
+        ),
+        Field::Binary => Column::new(
+            "Binary",
+            cells(rows, |(_, _, binary, ..)| binary.size.clone()),
+            colors.size.clone(),
+        ),
+        Field::Owner => Column::new(
+            "Owner",
+            cells(rows, |(_, _, _, owner, ..)| owner.owner.clone()),
+            colors.owner.clone(),
+        ),
+        Field::Group => Column::new(
+            "Group",
+            cells(rows, |(_, _, _, owner, ..)| owner.group.clone()),
+            colors.owner.clone(),
+        ),
+        Field::Modified => Column::new(
+            "Date Modified",
+            cells(rows, |(.., mac, _)| mac.modified.clone()),
+            colors.time.clone(),
+        ),
+        Field::Accessed => Column::new(
+            "Date Accessed",
+            cells(rows, |(.., mac, _)| mac.accessed.clone()),
+            colors.time.clone(),
+        ),
+        Field::Created => Column::new(
+            "Date Created",
+            cells(rows, |(.., mac, _)| mac.created.clone()),
+            colors.time.clone(),
+        ),
+        Field::Permission => Column::new(
+            "Permission",
+            cells(rows, |(.., permission)| permission.permission.clone()),
+            colors.permission.clone(),
+        ),
+        Field::Changed => Column::new(
+            "Date Changed",
+            metadata(entries, |meta| {
+                DateTime::<Utc>::from_timestamp(meta.ctime(), meta.ctime_nsec() as u32)
+                    .map(|date| date.format("%a %b %e %Y").to_string())
+                    .unwrap_or_default()
+            }),
+            colors.time.clone(),
+        ),
+        Field::Inode => Column::plain("Inode", metadata(entries, |meta| meta.ino().to_string())),
+        Field::Links => Column::new(
+            "Links",
+            metadata(entries, |meta| meta.nlink().to_string()),
+            colors.size.clone(),
+        ),
+        Field::Uid => Column::new(
+            "UID",
+            metadata(entries, |meta| meta.uid().to_string()),
+            colors.owner.clone(),
+        ),
+        Field::Gid => Column::new(
+            "GID",
+            metadata(entries, |meta| meta.gid().to_string()),
+            colors.owner.clone(),
+        ),
+        Field::Mode => Column::new(
+            "Mode",
+            metadata(entries, |meta| {
+                format!("{:03o}", meta.permissions().mode() & 0o7777)
+            }),
+            colors.permission.clone(),
+        ),
+        Field::Target => {
+            let targets = entries
+                .iter()
+                .map(|entry| match entry.meta.file_type().is_symlink() {
+                    true => fs::read_link(&entry.path)
+                        .map(|target| escape_control(&target.to_string_lossy()))
+                        .unwrap_or_default(),
+                    false => String::new(),
+                })
+                .collect();
+            Column::new("Target", targets, colors.note.clone())
+        }
+        Field::Lfs => {
+            let badges = context
+                .lfs
+                .iter()
+                .map(|&lfs| match lfs {
+                    true => "LFS".to_string(),
+                    false => String::new(),
+                })
+                .collect();
+            Column::new("LFS", badges, colors.note.clone())
+        }
+        Field::Crate => {
+            let workspace = project::workspace_manifest(context.path);
+            let crates = entries
+                .iter()
+                .map(|entry| match entry.meta.is_dir() {
+                    true => project::cargo_annotation(&entry.path, workspace.as_ref()),
+                    false => String::new(),
+                })
+                .collect();
+            Column::new("Crate", crates, colors.crate_name.clone())
+        }
+        Field::Project => {
+            let (projects, dependencies): (Vec<String>, Vec<bool>) = entries
+                .iter()
+                .map(|entry| match entry.meta.is_dir() {
+                    true => project::project_annotation(&entry.path),
+                    false => (String::new(), false),
+                })
+                .unzip();
+            // Dependency and virtualenv folders are dimmed to set them apart
+            Column::new("Project", projects, colors.project.clone())
+                .highlight(&dependencies, colors.dim.clone())
+        }
+        Field::LinkTarget => {
+            let targets = entries
+                .iter()
+                .map(|entry| match entry.meta.file_type().is_symlink() {
+                    true => {
+                        let target = fs::read_link(&entry.path).unwrap_or_default();
+                        format!(
+                            "{} -> {}",
+                            escape_control(&entry.name().to_string_lossy()),
+                            escape_control(&target.to_string_lossy())
+                        )
+                    }
+                    false => String::new(),
+                })
+                .collect();
+            Column::new("Link Target", targets, colors.note.clone())
+        }
+        Field::Matches => {
+            let pattern = context
+                .pattern
+                .ok_or("The matches column needs --match-count")?;
+            let counts = matches::annotations(pattern, entries, context.cache);
+            let none: Vec<bool> = counts.iter().map(|count| count == "0").collect();
+            Column::new("Matches", counts, colors.size.clone()).highlight(&none, colors.dim.clone())
+        }
+        Field::Ages => Column::new(
+            spark::HEADER,
+            spark::annotations(entries, context.all),
+            colors.time.clone(),
+        ),
+        Field::AlsoAt => {
+            let others = entries
+                .iter()
+                .map(|entry| {
+                    context
+                        .seen
+                        .map(|seen| seen.others(entry))
+                        .unwrap_or_default()
+                })
+                .collect();
+            Column::new("Also At", others, colors.note.clone())
+        }
+        Field::SharedExtents => Column::new(
+            "Shared Extents",
+            reflink::annotations(&context.paths()),
+            colors.owner.clone(),
+        ),
+        Field::Badges => Column::plain(
+            "Badges",
+            entries
+                .iter()
+                .map(|entry| badges(&entry.path, &entry.meta))
+                .collect(),
+        ),
+        Field::Libraries => {
+            let (libraries, broken): (Vec<String>, Vec<bool>) =
+                libs::annotations(&context.paths()).into_iter().unzip();
+            Column::new("Libraries", libraries, colors.note.clone())
+                .highlight(&broken, colors.alert.clone())
+        }
+        Field::Package => Column::new(
+            "Package",
+            pkg::annotations(&context.paths())
+                .map_err(|err| format!("Can't look up packages ({err})"))?,
+            colors.project.clone(),
+        ),
+        Field::Mount => Column::new(
+            "Mount",
+            mounts::annotations(&context.paths())
+                .map_err(|err| format!("Can't read mounts ({err})"))?,
+            colors.note.clone(),
+        ),
+        Field::UnitState => {
+            let (states, failed): (Vec<String>, Vec<bool>) = systemd::annotations(&context.paths())
+                .map_err(|err| format!("Can't query systemd units ({err})"))?
+                .into_iter()
+                .unzip();
+            Column::new("Unit State", states, colors.permission.clone())
+                .highlight(&failed, colors.alert.clone())
+        }
+        Field::Effective => Column::new(
+            "New File / Dir / Group",
+            effective::annotations(&context.paths()),
+            colors.time.clone(),
+        ),
+        Field::Git => {
+            let statuses = git::statuses(context.path, &context.paths())
+                .map_err(|err| format!("Can't read git status ({err})"))?;
+            let changed: Vec<bool> = statuses.iter().map(|status| status != "--").collect();
+            Column::new("Git", statuses, colors.dim.clone())
+                .highlight(&changed, colors.alert.clone())
+        }
+        Field::LastCommit => Column::new(
+            "Last Commit",
+            context.commits(|commit| escape_control(&commit.short_subject()))?,
+            colors.note.clone(),
+        ),
+        Field::Author => Column::new(
+            "Author",
+            context.commits(|commit| commit.author.clone())?,
+            colors.owner.clone(),
+        ),
+        Field::CommitDate => Column::new(
+            "Commit Date",
+            context.commits(|commit| commit.date.clone())?,
+            colors.time.clone(),
+        ),
+        Field::Churn => {
+            let months = context.months.ok_or("The churn column needs --churn")?;
+            let counts = git::churn(context.path, &context.names(), months)
+                .map_err(|err| format!("Can't read git history ({err})"))?;
+            // Bars relative to the busiest entry make hotspots stand out
+            let busiest = counts.iter().copied().max().unwrap_or(0).max(1);
+            let churn = counts
+                .into_iter()
+                .map(|count| format!("{count:>4} {}", "█".repeat((count * 10).div_ceil(busiest))))
+                .collect();
+            Column::new("Churn", churn, colors.alert.clone())
+        }
+    };
+    Ok(column)
+}
+
+/// The column the `ds-column-<name>` plugin answers, see `plugin`.
+pub fn plugin(name: &str, context: &Context) -> Result<Column, String> {
+    let values = plugin::column(name, &context.paths())
+        .map_err(|err| format!("Plugin '{name}' failed ({err})"))?;
+    Ok(Column::new(name, values, context.colors.note.clone()))
+}
+
+/// Emoji badges for states otherwise only told apart by color.
+fn badges(path: &Path, meta: &Metadata) -> String {
+    let link = meta.file_type().is_symlink();
+    // access() follows links, so a dangling one would look unreadable
+    let readable = link
+        || std::ffi::CString::new(path.as_os_str().as_encoded_bytes())
+            // SAFETY: the path is NUL terminated.
+            .map(|path| unsafe { libc::access(path.as_ptr(), libc::R_OK) } == 0)
+            .unwrap_or(true);
+    let empty = match meta.is_dir() {
+        true => fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_none()),
+        false => meta.is_file() && meta.len() == 0,
+    };
+
+    let mut badges = String::new();
+    if !readable {
+        badges.push('🔒');
+    }
+    if link {
+        badges.push('🔗');
+    }
+    if meta.is_file() && meta.permissions().mode() & 0o111 != 0 {
+        badges.push('⭐');
+    }
+    if empty {
+        badges.push('🕳');
+    }
+    badges
+}
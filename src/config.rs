@@ -0,0 +1,233 @@
+//! Where ds keeps what users set up: `$XDG_CONFIG_HOME/ds`, or `~/.config/ds`,
+//! with settings in its `config.toml`. Its top-level keys are defaults for the
+//! long options of the same name, sections like `[theme]` configure the rest:
+//!
+//! ```toml
+//! all = true
+//! sort = "file-type"
+//! columns = ["name", "size", "modified"]
+//! ```
+
+use clap::parser::ValueSource;
+use clap::{Arg, ArgMatches, Command};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+pub fn dir() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".config")))?;
+    Some(dir.join("ds"))
+}
+
+/// The settings in `config.toml`, empty when there's no such file.
+pub fn load() -> Result<toml::Table, String> {
+    let Some(file) = dir().map(|dir| dir.join("config.toml")) else {
+        return Ok(toml::Table::new());
+    };
+    match fs::read_to_string(&file) {
+        Ok(text) => text
+            .parse()
+            .map_err(|err: toml::de::Error| format!("{}: {err}", file.display())),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(toml::Table::new()),
+        Err(err) => Err(format!("{}: {err}", file.display())),
+    }
+}
+
+/// The table `name` of the settings, empty when it isn't set.
+pub fn section(config: &toml::Table, name: &str) -> Result<toml::Table, String> {
+    match config.get(name) {
+        Some(toml::Value::Table(table)) => Ok(table.clone()),
+        Some(_) => Err(format!("'{name}' in config.toml must be a table")),
+        None => Ok(toml::Table::new()),
+    }
+}
+
+/// Whether `one` and `other` can't be used together, either way round or as
+/// members of the same exclusive group.
+fn conflicting(command: &Command, one: &Arg, other: &Arg) -> bool {
+    let conflicts = |one: &Arg, with: &Arg| {
+        command
+            .get_arg_conflicts_with(one)
+            .iter()
+            .any(|conflict| conflict.get_id() == with.get_id())
+    };
+    let grouped = command.get_groups().any(|group| {
+        let members: Vec<_> = group.get_args().collect();
+        !group.clone().is_multiple()
+            && members.contains(&one.get_id())
+            && members.contains(&other.get_id())
+    });
+    conflicts(one, other) || conflicts(other, one) || grouped
+}
+
+/// One option set outside the command line: its argument id and the
+/// options that set it, like `--sort=size`.
+pub struct Setting {
+    id: String,
+    args: Vec<OsString>,
+}
+
+/// The top-level settings of config.toml as options, like `--sort=size` for
+/// `sort = "size"`.
+pub fn from_config(config: &toml::Table, command: &Command) -> Result<Vec<Setting>, String> {
+    let mut settings = Vec::new();
+    for (key, value) in config {
+        if value.is_table() {
+            continue;
+        }
+        let long = key.replace('_', "-");
+        let Some(arg) = command.get_arguments().find(|arg| {
+            arg.get_long()
+                .is_some_and(|name| name.replace('_', "-") == long)
+        }) else {
+            return Err(format!("unknown setting '{key}' in config.toml"));
+        };
+
+        let option = format!("--{}", arg.get_long().unwrap_or_default());
+        let text = |value: &toml::Value| match value {
+            toml::Value::String(text) => text.clone(),
+            value => value.to_string(),
+        };
+        let args = match value {
+            toml::Value::Boolean(true) => vec![option.into()],
+            toml::Value::Boolean(false) => continue,
+            toml::Value::Array(items) => items
+                .iter()
+                .map(|item| format!("{option}={}", text(item)).into())
+                .collect(),
+            value => vec![format!("{option}={}", text(value)).into()],
+        };
+        settings.push(Setting {
+            id: arg.get_id().to_string(),
+            args,
+        });
+    }
+    Ok(settings)
+}
+
+/// Split `text` into words like a shell: on whitespace, except within single
+/// or double quotes or after a backslash.
+fn split(text: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None | Some('"'), '\\') => {
+                let escaped = chars.next().ok_or("trailing backslash")?;
+                word.get_or_insert_default().push(escaped);
+            }
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_default();
+            }
+            (Some(open), c) if c == open => quote = None,
+            (_, c) => word.get_or_insert_default().push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err("unclosed quote".into());
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// The options in the `DS_OPTS` environment variable, like
+/// `DS_OPTS="--all --sort modified"`.
+pub fn from_env(command: &Command) -> Result<Vec<Setting>, String> {
+    let Some(text) = env::var_os("DS_OPTS") else {
+        return Ok(Vec::new());
+    };
+    let text = text.to_string_lossy();
+    let words = split(&text).map_err(|err| format!("DS_OPTS: {err}"))?;
+    let matches = command
+        .clone()
+        .try_get_matches_from(std::iter::once("ds".to_string()).chain(words))
+        .map_err(|err| {
+            let err = err.to_string();
+            let first = err.lines().next().unwrap_or_default();
+            format!("DS_OPTS: {}", first.trim_start_matches("error: "))
+        })?;
+    if matches.subcommand().is_some() {
+        return Err("DS_OPTS: subcommands can't be set there".into());
+    }
+
+    // Put back as options, to be overridden like the ones in config.toml
+    let mut settings = Vec::new();
+    for arg in command.get_arguments() {
+        let id = arg.get_id().as_str();
+        if matches.value_source(id) != Some(ValueSource::CommandLine) {
+            continue;
+        }
+        let Some(long) = arg.get_long() else {
+            return Err(format!(
+                "DS_OPTS: only options can be set there, not '{id}'"
+            ));
+        };
+        let args = match arg.get_action().takes_values() {
+            true => matches
+                .get_raw(id)
+                .into_iter()
+                .flatten()
+                .map(|value| {
+                    let mut option = OsString::from(format!("--{long}="));
+                    option.push(value);
+                    option
+                })
+                .collect(),
+            false => vec![format!("--{long}").into()],
+        };
+        settings.push(Setting {
+            id: id.to_string(),
+            args,
+        });
+    }
+    Ok(settings)
+}
+
+/// The options to put before the command line for `layers` of settings, from
+/// the lowest precedence to the highest. A setting is left out when a higher
+/// layer or the command line `given` sets the same option or one it
+/// conflicts with, and under a subcommand only global options are kept.
+pub fn default_args(
+    layers: Vec<Vec<Setting>>,
+    command: &Command,
+    given: &ArgMatches,
+) -> Vec<OsString> {
+    let arg = |id: &str| command.get_arguments().find(|arg| arg.get_id() == id);
+    let mut above: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| given.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .collect();
+
+    let mut kept = Vec::new();
+    for layer in layers.iter().rev() {
+        let mut layer_args = Vec::new();
+        for setting in layer {
+            let Some(setting_arg) = arg(&setting.id) else {
+                continue;
+            };
+            let overridden = above.iter().any(|other| {
+                other.get_id() == setting_arg.get_id() || conflicting(command, setting_arg, other)
+            });
+            if overridden || (given.subcommand().is_some() && !setting_arg.is_global_set()) {
+                continue;
+            }
+            layer_args.push(setting_arg);
+            kept.push(setting);
+        }
+        above.extend(layer_args);
+    }
+    // Lower layers first, as they were given
+    kept.reverse();
+    kept.into_iter()
+        .flat_map(|setting| setting.args.iter().cloned())
+        .collect()
+}
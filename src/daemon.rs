@@ -0,0 +1,246 @@
+//! `ds daemon`: an index of what's in some directories, kept in memory and
+//! answered over HTTP, so `ds query` lists and searches them instantly however
+//! slow the disk or network share below is.
+//!
+//! Network shares don't report changes made from other machines, so rather
+//! than being told of changes the daemon looks again every few seconds. Only
+//! directories are looked at then: their listing is read again when their
+//! modification time changed, otherwise it comes from the cache, which also
+//! keeps it for the next time the daemon starts.
+//!
+//! * `GET /api/list?path=/some/dir` returns the entries of an indexed
+//!   directory as JSON.
+//! * `GET /api/search?glob=*.rs` returns the indexed paths whose names match.
+
+use crate::cache::Cache;
+use crate::serve::{percent_decode, percent_encode};
+use ds::{DirLister, Glob};
+use glob::Pattern;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Response, Server};
+
+/// The entries of each indexed directory, by name, and whether they're
+/// directories.
+type Index = BTreeMap<PathBuf, Vec<(String, bool)>>;
+
+/// The directories to index: the given ones, or the `dirs` of `[daemon]` in
+/// the settings.
+pub fn dirs(paths: Vec<PathBuf>, settings: &toml::Table) -> Result<Vec<PathBuf>, String> {
+    if !paths.is_empty() {
+        return Ok(paths);
+    }
+    let section = crate::config::section(settings, "daemon")?;
+    let dirs: Vec<PathBuf> = match section.get("dirs") {
+        Some(toml::Value::Array(dirs)) => dirs
+            .iter()
+            .map(|dir| dir.as_str().map(PathBuf::from))
+            .collect::<Option<_>>()
+            .ok_or("'dirs' of [daemon] in config.toml must be a list of paths")?,
+        Some(_) => return Err("'dirs' of [daemon] in config.toml must be a list of paths".into()),
+        None => Vec::new(),
+    };
+    match dirs.is_empty() {
+        true => Err("no directories given, nor 'dirs' of [daemon] in config.toml".into()),
+        false => Ok(dirs),
+    }
+}
+
+/// The entries of `dir`, read with `lister` unless the directory is cached
+/// as it is now.
+fn entries(dir: &Path, lister: &DirLister, cache: &Cache) -> Vec<(String, bool)> {
+    let Ok(meta) = dir.symlink_metadata() else {
+        return Vec::new();
+    };
+    cache
+        .get("listing", &meta, || {
+            let entries: Vec<(String, bool)> = lister
+                .list(dir)
+                .iter()
+                .map(|entry| {
+                    let name = entry.name().to_string_lossy().into_owned();
+                    (name, entry.meta.is_dir())
+                })
+                .collect();
+            Some(json!(entries))
+        })
+        .and_then(|entries| serde_json::from_value(entries).ok())
+        .unwrap_or_default()
+}
+
+/// Every directory under `roots`, with its entries. Hidden ones are left
+/// out unless `show_hidden`, but cached all the same.
+fn scan(roots: &[PathBuf], show_hidden: bool, cache: &Cache) -> Index {
+    let lister = DirLister {
+        all: true,
+        ..DirLister::default()
+    };
+    let mut index = Index::new();
+    let mut pending = roots.to_vec();
+    while let Some(dir) = pending.pop() {
+        let mut entries = entries(&dir, &lister, cache);
+        entries.retain(|(name, _)| show_hidden || !name.starts_with('.'));
+        // Symlinks aren't followed, so a link can't take the walk round in circles
+        pending.extend(
+            entries
+                .iter()
+                .filter(|(_, directory)| *directory)
+                .map(|(name, _)| dir.join(name)),
+        );
+        index.insert(dir, entries);
+    }
+    index
+}
+
+pub fn run(dirs: &[PathBuf], listen: &str, interval: u64, show_hidden: bool) -> Result<(), String> {
+    let roots = dirs
+        .iter()
+        .map(|dir| {
+            dir.canonicalize()
+                .map_err(|err| format!("{}: {err}", dir.display()))
+        })
+        .collect::<Result<Vec<PathBuf>, String>>()?;
+    let cache = Cache::load();
+    let index = scan(&roots, show_hidden, &cache);
+    cache.save();
+    println!("Indexed {} directories", index.len());
+    let server = Server::http(listen).map_err(|err| err.to_string())?;
+    for root in &roots {
+        println!(
+            "Answering queries about {} on http://{listen}",
+            root.display()
+        );
+    }
+
+    let index = Arc::new(RwLock::new(index));
+    let latest = Arc::clone(&index);
+    thread::spawn(move || {
+        loop {
+            thread::sleep(Duration::from_secs(interval));
+            let scanned = scan(&roots, show_hidden, &cache);
+            if let Ok(mut index) = latest.write() {
+                *index = scanned;
+            }
+            cache.save();
+        }
+    });
+
+    for request in server.incoming_requests() {
+        let url = request.url().to_string();
+        let (route, query) = url.split_once('?').unwrap_or((&url, ""));
+        let parameter = |name: &str| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+                .map(percent_decode)
+        };
+        let Ok(index) = index.read() else {
+            break;
+        };
+
+        let answer = match route {
+            "/api/list" => {
+                let dir = PathBuf::from(parameter("path").unwrap_or_default());
+                match index.get(&dir) {
+                    Some(entries) => Ok(entries
+                        .iter()
+                        .map(|(name, directory)| json!({"name": name, "directory": directory}))
+                        .collect::<Value>()),
+                    None => Err((404, format!("'{}' isn't indexed", dir.display()))),
+                }
+            }
+            "/api/search" => match Pattern::new(&parameter("glob").unwrap_or_default()) {
+                Ok(pattern) => {
+                    let glob = Glob {
+                        pattern,
+                        ignore_case: false,
+                    };
+                    Ok(index
+                        .iter()
+                        .flat_map(|(dir, entries)| {
+                            entries
+                                .iter()
+                                .filter(|(name, _)| glob.matches(name))
+                                .map(move |(name, _)| json!(dir.join(name).to_string_lossy()))
+                        })
+                        .collect::<Value>())
+                }
+                Err(err) => Err((400, format!("invalid glob ({err})"))),
+            },
+            _ => Err((404, "not found".to_string())),
+        };
+        drop(index);
+        let response = match answer {
+            Ok(json) => Response::from_string(json.to_string()),
+            Err((status, message)) => Response::from_string(message).with_status_code(status),
+        };
+        let _ = request.respond(response);
+    }
+    Ok(())
+}
+
+/// The body of the daemon's answer to `target`, or why there's none.
+fn get(connect: &str, target: &str) -> Result<Value, String> {
+    let mut stream = TcpStream::connect(connect)
+        .map_err(|err| format!("no daemon answers on {connect}: {err}"))?;
+    write!(stream, "GET {target} HTTP/1.0\r\nHost: {connect}\r\n\r\n")
+        .map_err(|err| err.to_string())?;
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .map_err(|err| err.to_string())?;
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("the daemon's answer is cut short")?;
+    if head.split_whitespace().nth(1) != Some("200") {
+        return Err(body.to_string());
+    }
+    serde_json::from_str(body).map_err(|err| err.to_string())
+}
+
+/// Print the entries of `path` as the daemon on `connect` has them, or with
+/// `glob`, the indexed paths whose names match it. Directories end in `/`.
+pub fn query(connect: &str, path: &Path, glob: Option<&str>) -> Result<(), String> {
+    let lines: Vec<String> = match glob {
+        Some(glob) => {
+            let found = get(
+                connect,
+                &format!("/api/search?glob={}", percent_encode(glob)),
+            )?;
+            found
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|path| Some(path.as_str()?.to_string()))
+                .collect()
+        }
+        None => {
+            // The daemon knows directories by their canonical path
+            let dir = path
+                .canonicalize()
+                .map_err(|err| format!("{}: {err}", path.display()))?;
+            let target = format!("/api/list?path={}", percent_encode(&dir.to_string_lossy()));
+            let entries = get(connect, &target)?;
+            entries
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry["name"].as_str()?;
+                    let suffix = if entry["directory"] == true { "/" } else { "" };
+                    Some(format!("{name}{suffix}"))
+                })
+                .collect()
+        }
+    };
+    for line in lines {
+        println!("{line}");
+    }
+    Ok(())
+}
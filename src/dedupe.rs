@@ -0,0 +1,54 @@
+//! `--dedupe`: entries reached more than once across the listed paths, through
+//! hard links or paths that overlap, are listed once, with the other paths
+//! they're at beside them.
+
+use ds::Entry;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs::Metadata;
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+
+fn key(meta: &Metadata) -> (u64, u64) {
+    (meta.dev(), meta.ino())
+}
+
+#[derive(Default)]
+pub struct Seen {
+    /// Every path of each (device, inode), in listing order
+    paths: HashMap<(u64, u64), Vec<PathBuf>>,
+    /// What's been listed already
+    listed: RefCell<HashSet<(u64, u64)>>,
+}
+
+impl Seen {
+    /// Note the entries of one of the listed paths.
+    pub fn add(&mut self, entries: &[Entry]) {
+        for entry in entries {
+            self.paths
+                .entry(key(&entry.meta))
+                .or_default()
+                .push(entry.path.clone());
+        }
+    }
+
+    /// Whether `meta` is listed for the first time, and so kept.
+    pub fn claim(&self, meta: &Metadata) -> bool {
+        self.listed.borrow_mut().insert(key(meta))
+    }
+
+    /// The other paths of an entry, joined for a column.
+    pub fn others(&self, entry: &Entry) -> String {
+        let mut others: Vec<&PathBuf> = Vec::new();
+        for other in self.paths.get(&key(&entry.meta)).into_iter().flatten() {
+            if *other != entry.path && !others.contains(&other) {
+                others.push(other);
+            }
+        }
+        others
+            .iter()
+            .map(|other| ds::escape_control(&other.to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
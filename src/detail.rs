@@ -0,0 +1,98 @@
+//! `ds FILE`: every detail of a single file as key/value lines, like `stat`,
+//! instead of a one-row table.
+
+use crate::color::ColorScheme;
+use chrono::{DateTime, Local};
+use ds::{
+    Units, entry_type, escape_control, group_and_owner_mode, human_readable_size, is_broken_link,
+    permission_mode,
+};
+use std::fs::{self, Metadata};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::time::SystemTime;
+use tabled::{
+    Table,
+    builder::Builder,
+    settings::{Color, Style, object::Columns},
+};
+
+fn time(time: Option<SystemTime>) -> String {
+    match time {
+        Some(time) => DateTime::<Local>::from(time)
+            .format("%Y-%m-%d %H:%M:%S%.9f %z")
+            .to_string(),
+        None => "-".into(),
+    }
+}
+
+pub fn render(path: &Path, meta: &Metadata, units: Units, colors: &ColorScheme) -> Table {
+    let owner = group_and_owner_mode(meta);
+    // The status change time is only exposed through the Unix metadata
+    let changed =
+        DateTime::from_timestamp(meta.ctime(), meta.ctime_nsec() as u32).map(SystemTime::from);
+    let mut lines: Vec<(&str, String, Option<&Color>)> = vec![
+        ("Path", escape_control(&path.to_string_lossy()), None),
+        ("Type", entry_type(meta).to_string(), Some(&colors.kind)),
+        (
+            "Size",
+            format!(
+                "{} ({} bytes)",
+                human_readable_size(meta.len(), units),
+                meta.len()
+            ),
+            Some(&colors.size),
+        ),
+        (
+            "Owner",
+            format!("{} ({})", owner.owner, meta.uid()),
+            Some(&colors.owner),
+        ),
+        (
+            "Group",
+            format!("{} ({})", owner.group, meta.gid()),
+            Some(&colors.owner),
+        ),
+        (
+            "Permission",
+            format!(
+                "{} ({:04o})",
+                permission_mode(meta).permission,
+                meta.mode() & 0o7777
+            ),
+            Some(&colors.permission),
+        ),
+        ("Modified", time(meta.modified().ok()), Some(&colors.time)),
+        ("Accessed", time(meta.accessed().ok()), Some(&colors.time)),
+        ("Changed", time(changed), Some(&colors.time)),
+        ("Created", time(meta.created().ok()), Some(&colors.time)),
+        ("Inode", meta.ino().to_string(), None),
+        ("Links", meta.nlink().to_string(), None),
+    ];
+    if meta.file_type().is_symlink() {
+        let target = fs::read_link(path).unwrap_or_default();
+        let color = match is_broken_link(path, meta) {
+            true => &colors.alert,
+            false => &colors.note,
+        };
+        lines.push((
+            "Target",
+            escape_control(&target.to_string_lossy()),
+            Some(color),
+        ));
+    }
+
+    let mut builder = Builder::default();
+    for (key, value, _) in &lines {
+        builder.push_record([key.to_string(), value.clone()]);
+    }
+    let mut table = builder.build();
+    table.with(Style::empty());
+    table.modify(Columns::first(), colors.dim.clone());
+    for (row, (.., color)) in lines.iter().enumerate() {
+        if let Some(color) = color {
+            table.modify((row, 1), (*color).clone());
+        }
+    }
+    table
+}
@@ -0,0 +1,191 @@
+//! `ds doctor`: checks of the terminal, locale and helper programs behind
+//! broken colors, misaligned tables and missing columns, each with a fix.
+
+use crate::color::{self, Background, ColorMode, ColorScheme};
+use crate::pkg::installed;
+use std::env;
+use tabled::{
+    Table, Tabled,
+    settings::{Style, object::Columns, object::Rows},
+};
+
+#[derive(Tabled)]
+struct Check {
+    #[tabled(rename = "Check")]
+    name: &'static str,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "Fix")]
+    fix: String,
+}
+
+impl Check {
+    fn ok(name: &'static str, status: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: status.into(),
+            fix: String::new(),
+        }
+    }
+
+    fn warn(name: &'static str, status: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: status.into(),
+            fix: fix.into(),
+        }
+    }
+}
+
+fn color_output(mode: ColorMode) -> Check {
+    let set = |name| env::var_os(name).is_some_and(|value| !value.is_empty());
+    match color::enabled(mode) {
+        true => Check::ok("Colors", "on"),
+        false if mode == ColorMode::Never => Check::warn("Colors", "off", "drop --color never"),
+        false if set("NO_COLOR") => Check::warn("Colors", "off", "unset NO_COLOR"),
+        false if !color::stdout_is_terminal() => Check::warn(
+            "Colors",
+            "off (not a terminal)",
+            "use --color always to color piped output",
+        ),
+        false => Check::warn("Colors", "off", "unset CLICOLOR or set CLICOLOR_FORCE=1"),
+    }
+}
+
+/// The color-blind palettes are drawn in 24-bit color.
+fn truecolor() -> Check {
+    match env::var("COLORTERM").as_deref() {
+        Ok("truecolor" | "24bit") => Check::ok("24-bit color", "supported"),
+        _ => Check::warn(
+            "24-bit color",
+            "not announced",
+            "set COLORTERM=truecolor if the terminal supports it, or use the default palette",
+        ),
+    }
+}
+
+fn background() -> Check {
+    let background = match color::background(true) {
+        Background::Dark => "dark",
+        Background::Light => "light",
+    };
+    match env::var_os("COLORFGBG") {
+        Some(_) => Check::ok("Background", format!("{background} (from COLORFGBG)")),
+        None => Check::ok("Background", background),
+    }
+}
+
+fn locale() -> Check {
+    // The first one set decides, as in setlocale
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_else(|| "C".into());
+    let utf8 = locale.to_lowercase().replace('-', "").contains("utf8");
+    match utf8 {
+        true => Check::ok("Locale", locale),
+        false => Check::warn(
+            "Locale",
+            format!("{locale} (not UTF-8)"),
+            "set LANG to a UTF-8 locale, like en_US.UTF-8",
+        ),
+    }
+}
+
+/// How many columns the terminal draws an emoji in, found by asking for the
+/// cursor position before and after printing one. Tables align names with
+/// wide characters assuming 2.
+fn unicode_width() -> Check {
+    if !color::stdout_is_terminal() {
+        return Check::ok("Wide characters", "skipped (not a terminal)");
+    }
+    let column = |request: &[u8]| {
+        let answer = color::query_terminal(request)?;
+        // ESC [ row ; column R
+        let answer = String::from_utf8_lossy(&answer);
+        answer
+            .rsplit(';')
+            .next()?
+            .trim_end_matches('R')
+            .parse::<usize>()
+            .ok()
+    };
+    let measured = column(b"\r\x1b[6n").zip(column("\r🕳\x1b[6n".as_bytes()));
+    // Wipe the probe
+    print!("\r\x1b[K");
+
+    match measured.map(|(before, after)| after.saturating_sub(before)) {
+        Some(2) => Check::ok("Wide characters", "2 columns"),
+        Some(width) => Check::warn(
+            "Wide characters",
+            format!("{width} columns"),
+            "use a terminal or font with Unicode 9+ widths, or expect misaligned emoji",
+        ),
+        None => Check::warn(
+            "Wide characters",
+            "unknown",
+            "the terminal didn't report its cursor position",
+        ),
+    }
+}
+
+/// Programs some options run, and the options that need them.
+fn programs() -> Vec<Check> {
+    let package_managers = ["dpkg-query", "rpm", "pacman"];
+    let mut checks = vec![
+        match installed("git") {
+            true => Check::ok("git", "found"),
+            false => Check::warn(
+                "git",
+                "missing",
+                "install git for --git-log, --churn and --at",
+            ),
+        },
+        match package_managers.iter().find(|program| installed(program)) {
+            Some(program) => Check::ok("Package manager", *program),
+            None => Check::warn(
+                "Package manager",
+                "none found",
+                "--pkg needs dpkg, rpm or pacman",
+            ),
+        },
+    ];
+    if !installed("systemctl") {
+        checks.push(Check::warn(
+            "systemctl",
+            "missing",
+            "--unit-hints needs systemd",
+        ));
+    }
+    checks
+}
+
+pub fn render(mode: ColorMode, colors: &ColorScheme) -> Table {
+    let mut checks = vec![
+        color_output(mode),
+        truecolor(),
+        background(),
+        locale(),
+        unicode_width(),
+    ];
+    checks.extend(programs());
+    let warnings: Vec<usize> = checks
+        .iter()
+        .enumerate()
+        .filter(|(_, check)| !check.fix.is_empty())
+        .map(|(row, _)| row)
+        .collect();
+
+    let mut table = Table::new(checks);
+    table.with(Style::empty());
+    table.modify(Columns::one(1), colors.permission.clone());
+    for row in warnings {
+        table.modify((row + 1, 1), colors.alert.clone());
+    }
+    table.modify(Rows::first(), colors.dim.clone());
+    table
+}
+
+/// Glyphs ds draws, to be checked by eye: no program can tell whether a font
+/// has them.
+pub const GLYPHS: &str = "Tree lines and badges should show here: ├── └── 🔒 🔗 ⭐ 🕳";
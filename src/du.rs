@@ -0,0 +1,70 @@
+//! `--total-size`: directories sized by the files below them, like `du
+//! --apparent-size`, rather than by their own few kilobytes. A spinner on the
+//! terminal tells which directory is being read while large trees add up.
+
+use crate::cache::Cache;
+use ds::{Entry, dir_size};
+use serde_json::json;
+use std::io::{IsTerminal, Write};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Listings that finish quicker than this don't flash a spinner
+const DELAY: Duration = Duration::from_millis(200);
+const FRAME: Duration = Duration::from_millis(100);
+
+/// Redraw the spinner with what's being read until `done` hangs up.
+fn spin(done: mpsc::Receiver<()>, current: &Mutex<(usize, String)>, dirs: usize) {
+    if !matches!(done.recv_timeout(DELAY), Err(RecvTimeoutError::Timeout)) {
+        return;
+    }
+    let mut stderr = std::io::stderr();
+    for frame in FRAMES.iter().cycle() {
+        if let Ok((index, name)) = current.lock().map(|current| current.clone()) {
+            let _ = write!(stderr, "\r\x1b[K{frame} Sizing {name} ({index}/{dirs})");
+            let _ = stderr.flush();
+        }
+        if !matches!(done.recv_timeout(FRAME), Err(RecvTimeoutError::Timeout)) {
+            break;
+        }
+    }
+    let _ = write!(stderr, "\r\x1b[K");
+}
+
+/// The size of everything below each directory, None for other entries.
+pub fn totals(entries: &[Entry], cache: Option<&Cache>) -> Vec<Option<u64>> {
+    let dirs = entries.iter().filter(|entry| entry.meta.is_dir()).count();
+    let current = Mutex::new((0, String::new()));
+    let (finished, done) = mpsc::channel::<()>();
+    thread::scope(|scope| {
+        if dirs > 0 && std::io::stderr().is_terminal() {
+            scope.spawn(|| spin(done, &current, dirs));
+        }
+        let mut index = 0;
+        let totals = entries
+            .iter()
+            .map(|entry| {
+                if !entry.meta.is_dir() {
+                    return None;
+                }
+                index += 1;
+                if let Ok(mut current) = current.lock() {
+                    *current = (index, ds::escape_control(&entry.path.to_string_lossy()));
+                }
+                match cache {
+                    Some(cache) => cache
+                        .get("size", &entry.meta, || Some(json!(dir_size(&entry.path))))
+                        .and_then(|size| size.as_u64()),
+                    None => Some(dir_size(&entry.path)),
+                }
+            })
+            .collect();
+        // Hanging up stops the spinner
+        drop(finished);
+        totals
+    })
+}
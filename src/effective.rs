@@ -0,0 +1,139 @@
+//! The permissions and group a new file or directory would get inside a
+//! directory, from the process umask, the directory's setgid bit and its default
+//! ACL.
+
+use ds::permission_string;
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use users::get_group_by_gid;
+
+// Modes asked for by `touch` and `mkdir`
+const NEW_FILE: u32 = 0o666;
+const NEW_DIR: u32 = 0o777;
+
+// Tags of `struct posix_acl_xattr_entry`
+pub const ACL_USER_OBJ: u16 = 0x01;
+pub const ACL_USER: u16 = 0x02;
+pub const ACL_GROUP_OBJ: u16 = 0x04;
+pub const ACL_GROUP: u16 = 0x08;
+pub const ACL_MASK: u16 = 0x10;
+pub const ACL_OTHER: u16 = 0x20;
+
+fn umask() -> u32 {
+    // Reading /proc avoids briefly changing the umask of the process
+    fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            let line = status.lines().find(|line| line.starts_with("Umask:"))?;
+            u32::from_str_radix(line["Umask:".len()..].trim(), 8).ok()
+        })
+        .unwrap_or_else(|| {
+            // SAFETY: umask can't fail, the old value is put back right away.
+            unsafe {
+                let mask = libc::umask(0);
+                libc::umask(mask);
+                mask as u32
+            }
+        })
+}
+
+/// The entries of an ACL of `path`, `system.posix_acl_access` or
+/// `system.posix_acl_default`, as (tag, permission bits, user or group id).
+/// None when it has none.
+pub fn acl(path: &Path, name: &CStr) -> Option<Vec<(u16, u32, u32)>> {
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut buffer = [0u8; 1024];
+    // SAFETY: both names are NUL terminated and `buffer` is as long as stated.
+    let length = unsafe {
+        libc::getxattr(
+            path.as_ptr(),
+            name.as_ptr(),
+            buffer.as_mut_ptr().cast(),
+            buffer.len(),
+        )
+    };
+    if length <= 4 {
+        return None;
+    }
+
+    // A u32 version, then { u16 tag, u16 perm, u32 id } entries
+    let entries = buffer[4..length as usize]
+        .chunks_exact(8)
+        .map(|entry| {
+            (
+                u16::from_le_bytes([entry[0], entry[1]]),
+                u16::from_le_bytes([entry[2], entry[3]]) as u32 & 0o7,
+                u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]),
+            )
+        })
+        .collect();
+    Some(entries)
+}
+
+/// The permission bits of the directory's default ACL, folded into a mode the
+/// way the kernel does for new entries: the group class is limited by the mask.
+fn default_acl(dir: &Path) -> Option<u32> {
+    let (mut owner, mut group, mut mask, mut other) = (0, 0, None, 0);
+    for (tag, perm, _) in acl(dir, c"system.posix_acl_default")? {
+        match tag {
+            ACL_USER_OBJ => owner = perm,
+            ACL_GROUP_OBJ => group = perm,
+            ACL_MASK => mask = Some(perm),
+            ACL_OTHER => other = perm,
+            _ => {}
+        }
+    }
+    Some(owner << 6 | mask.unwrap_or(group) << 3 | other)
+}
+
+/// `permission_string`, with the setgid bit shown in the group's execute slot.
+fn mode_string(kind: char, mode: u32) -> String {
+    let mut text = permission_string(kind, mode);
+    if mode & 0o2000 != 0 {
+        let execute = if mode & 0o10 != 0 { "s" } else { "S" };
+        text.replace_range(6..7, execute);
+    }
+    text
+}
+
+/// One annotation per path: for directories, the mode of a new file, the mode of
+/// a new subdirectory and the group both would belong to; nothing otherwise.
+pub fn annotations(paths: &[PathBuf]) -> Vec<String> {
+    let umask = umask();
+    // SAFETY: getegid can't fail.
+    let own_group = unsafe { libc::getegid() };
+
+    paths
+        .iter()
+        .map(|path| {
+            let Ok(meta) = fs::metadata(path) else {
+                return String::new();
+            };
+            if !meta.is_dir() {
+                return String::new();
+            }
+
+            // A default ACL replaces the umask
+            let acl = default_acl(path);
+            let allowed = acl.unwrap_or(!umask & 0o777);
+            let setgid = meta.permissions().mode() & 0o2000;
+            let file = NEW_FILE & allowed;
+            // Subdirectories inherit the setgid bit
+            let dir = NEW_DIR & allowed | setgid;
+            let gid = if setgid != 0 { meta.gid() } else { own_group };
+            let group = get_group_by_gid(gid)
+                .map(|group| group.name().to_string_lossy().into_owned())
+                .unwrap_or_else(|| gid.to_string());
+
+            format!(
+                "{} {} {group}{}",
+                mode_string('-', file),
+                mode_string('d', dir),
+                if acl.is_some() { " (default ACL)" } else { "" },
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+//! `--format` templates: one line per entry with `{field}` placeholders, like
+//! `{name}\t{size}\t{perm}`. Any column of `--columns` is a placeholder.
+
+use crate::columns::{self, Context, Field};
+use clap::ValueEnum;
+
+enum Piece {
+    Text(String),
+    Field(Field),
+}
+
+pub struct Template(Vec<Piece>);
+
+impl Template {
+    /// Parse a template. `\t`, `\n` and `\\` are unescaped so they can be typed
+    /// in a shell, and `{{`/`}}` stand for literal braces.
+    pub fn parse(template: &str) -> Result<Template, String> {
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('\\', Some('t')) => text.push('\t'),
+                ('\\', Some('n')) => text.push('\n'),
+                ('\\', Some('\\')) => text.push('\\'),
+                ('{', Some('{')) => text.push('{'),
+                ('}', Some('}')) => text.push('}'),
+                ('{', _) => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("unterminated placeholder '{{{name}'")),
+                        }
+                    }
+                    let field = Field::from_str(&name, true)
+                        .map_err(|_| format!("unknown placeholder '{{{name}}}'"))?;
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    pieces.push(Piece::Field(field));
+                    continue;
+                }
+                _ => {
+                    text.push(c);
+                    continue;
+                }
+            }
+            // The second character of an escape
+            chars.next();
+        }
+        pieces.push(Piece::Text(text));
+        Ok(Template(pieces))
+    }
+
+    /// One line per row, each ended by a newline, without colors.
+    pub fn render(&self, context: &Context) -> Result<Vec<String>, String> {
+        let cells: Vec<Vec<String>> = self
+            .0
+            .iter()
+            .map(|piece| match piece {
+                Piece::Field(field) => Ok(columns::field(*field, context)?.into_cells()),
+                Piece::Text(_) => Ok(Vec::new()),
+            })
+            .collect::<Result<_, String>>()?;
+
+        let mut lines = Vec::new();
+        for row in 0..context.rows.len() {
+            let mut line = String::new();
+            for (piece, cells) in self.0.iter().zip(&cells) {
+                match piece {
+                    Piece::Text(text) => line.push_str(text),
+                    // Revisions and saved listings have no metadata for some fields
+                    Piece::Field(_) => line.push_str(cells.get(row).map_or("", String::as_str)),
+                }
+            }
+            line.push('\n');
+            lines.push(line);
+        }
+        Ok(lines)
+    }
+}
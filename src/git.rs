@@ -0,0 +1,536 @@
+//! Git integration, done by running the `git` executable.
+
+use ds::{
+    Basic, Binary, Entry, GroupOwner, Mac, Permission, Row, Size, SortField, Types, Units,
+    escape_control, human_readable_size, permission_string,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Git LFS pointers are well under this size, so larger blobs aren't read.
+const LFS_POINTER_SIZE: u64 = 1024;
+
+/// Run `git` inside `dir` and return its stdout.
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|err| format!("can't run git: {err}"))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The size a Git LFS pointer file records for the real file, like
+///
+/// ```text
+/// version https://git-lfs.github.com/spec/v1
+/// oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393
+/// size 12345
+/// ```
+fn lfs_size(content: &[u8]) -> Option<u64> {
+    let content = std::str::from_utf8(content).ok()?;
+    if !content.starts_with("version https://git-lfs.github.com/spec/") {
+        return None;
+    }
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("size "))
+        .and_then(|size| size.parse().ok())
+}
+
+/// The real sizes of the files among `entries` that are Git LFS pointers,
+/// as checked out without their content (like with `GIT_LFS_SKIP_SMUDGE=1`).
+pub fn pointer_sizes(entries: &[Entry]) -> Vec<Option<u64>> {
+    entries
+        .iter()
+        .map(
+            |entry| match entry.meta.is_file() && entry.meta.len() < LFS_POINTER_SIZE {
+                true => lfs_size(&fs::read(&entry.path).ok()?),
+                false => None,
+            },
+        )
+        .collect()
+}
+
+/// The real sizes of the blobs among `objects` that are LFS pointers, read
+/// with a single `git cat-file --batch`.
+fn lfs_sizes(dir: &Path, objects: &[&str]) -> Result<HashMap<String, u64>, String> {
+    if objects.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("can't run git: {err}"))?;
+
+    // Written from another thread so a full stdout pipe can't stall both ends
+    let mut stdin = child.stdin.take().ok_or("git takes no input")?;
+    let input: String = objects.iter().map(|object| format!("{object}\n")).collect();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let stdout = child.stdout.take().ok_or("git produced no output")?;
+    let mut stdout = BufReader::new(stdout);
+    let mut sizes = HashMap::new();
+    let mut header = String::new();
+    for _ in objects {
+        header.clear();
+        if stdout
+            .read_line(&mut header)
+            .map_err(|err| err.to_string())?
+            == 0
+        {
+            break;
+        }
+        // <object> SP <type> SP <size> LF <content> LF, or <object> SP missing LF
+        let mut fields = header.split_whitespace();
+        let (Some(object), Some(_), Some(size)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let size: usize = size
+            .parse()
+            .map_err(|_| format!("bad cat-file header: {header}"))?;
+        let mut content = vec![0; size + 1];
+        stdout
+            .read_exact(&mut content)
+            .map_err(|err| err.to_string())?;
+        if let Some(real) = lfs_size(&content[..size]) {
+            sizes.insert(object.to_string(), real);
+        }
+    }
+    let _ = writer.join();
+    let _ = child.wait();
+    Ok(sizes)
+}
+
+/// The root of the repository `path` is in, and the path of `path` in it,
+/// like `src/bin`. It needn't exist any more, as when it was deleted since
+/// the revision listed: the repository is found from the nearest directory
+/// above it that does.
+fn locate(path: &Path) -> Result<(PathBuf, String), String> {
+    let mut existing = path.to_path_buf();
+    let mut missing = Vec::new();
+    while !existing.is_dir() {
+        let (Some(name), Some(parent)) = (existing.file_name(), existing.parent()) else {
+            return Err(format!("{} doesn't exist", path.display()));
+        };
+        missing.push(name.to_string_lossy().into_owned());
+        existing = match parent.as_os_str().is_empty() {
+            true => PathBuf::from("."),
+            false => parent.to_path_buf(),
+        };
+    }
+    let root = git(&existing, &["rev-parse", "--show-toplevel"])?;
+    let mut inside = git(&existing, &["rev-parse", "--show-prefix"])?
+        .trim_end_matches('\n')
+        .to_string();
+    for name in missing.iter().rev() {
+        inside.push_str(name);
+        inside.push('/');
+    }
+    Ok((
+        PathBuf::from(root.trim_end_matches('\n')),
+        inside.trim_end_matches('/').to_string(),
+    ))
+}
+
+/// Rows for the directory's contents as recorded in the commit `rev`, each
+/// with whether it is a Git LFS pointer, whose row has the size of the real
+/// file.
+pub fn tree_rows(
+    path: &Path,
+    rev: &str,
+    show_hidden: bool,
+    directories_only: bool,
+    sort: SortField,
+    reverse: bool,
+    units: Units,
+) -> Result<Vec<(Row, bool)>, String> {
+    // The tree of the directory in the revision, so names come without the
+    // path in front
+    let (root, inside) = locate(path)?;
+    let listing = git(&root, &["ls-tree", "-z", "-l", &format!("{rev}:{inside}")])?;
+
+    let objects: Vec<(String, &str, u32, &str, Option<u64>)> = listing
+        .split('\0')
+        .filter_map(|record| {
+            // <mode> SP <type> SP <object> SP <size> TAB <path>
+            let (info, name) = record.split_once('\t')?;
+            let mut fields = info.split_whitespace();
+            let mode = u32::from_str_radix(fields.next()?, 8).ok()?;
+            let kind = fields.next()?;
+            let object = fields.next()?;
+            let size = fields.next()?.parse().ok();
+            Some((name.to_string(), kind, mode, object, size))
+        })
+        .filter(|(name, kind, ..)| {
+            (show_hidden || !name.starts_with('.')) && (!directories_only || *kind != "blob")
+        })
+        .collect();
+
+    let pointers: Vec<&str> = objects
+        .iter()
+        .filter(|(_, kind, _, _, size)| {
+            *kind == "blob" && size.is_some_and(|size| size < LFS_POINTER_SIZE)
+        })
+        .map(|(_, _, _, object, _)| *object)
+        .collect();
+    let lfs = lfs_sizes(&root, &pointers)?;
+    let mut objects: Vec<(String, &str, u32, Option<u64>, bool)> = objects
+        .into_iter()
+        .map(|(name, kind, mode, object, size)| match lfs.get(object) {
+            Some(real) => (name, kind, mode, Some(*real), true),
+            None => (name, kind, mode, size, false),
+        })
+        .collect();
+
+    // Only name, size, extension and type are recorded in a tree
+    match sort {
+        SortField::Size => objects.sort_by_key(|(_, _, _, size, _)| size.unwrap_or(0)),
+        SortField::Extension => objects.sort_by(|a, b| {
+            let ext = |name: &str| {
+                Path::new(name)
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            };
+            ext(&a.0).cmp(&ext(&b.0))
+        }),
+        SortField::FileType => {
+            objects.sort_by_key(|(name, kind, ..)| (*kind == "blob", name.clone()))
+        }
+        SortField::None => {}
+        _ => objects.sort_by(|a, b| a.0.cmp(&b.0)),
+    }
+    if reverse {
+        objects.reverse();
+    }
+
+    Ok(objects
+        .into_iter()
+        .map(|(name, kind, mode, size, lfs)| {
+            let row = (
+                Basic {
+                    name: escape_control(&name),
+                    types: match (kind, mode & 0o170000) {
+                        ("blob", 0o120000) => Types::Symlink,
+                        ("blob", _) => Types::File,
+                        _ => Types::Dir,
+                    },
+                },
+                Size {
+                    size: size
+                        .map(|bytes| human_readable_size(bytes, units))
+                        .unwrap_or("-".into()),
+                },
+                Binary {
+                    size: size.map(|bytes| bytes.to_string()).unwrap_or("-".into()),
+                },
+                GroupOwner {
+                    owner: "-".into(),
+                    group: "-".into(),
+                },
+                Mac {
+                    modified: String::default(),
+                    accessed: String::default(),
+                    created: String::default(),
+                },
+                Permission {
+                    permission: match mode & 0o170000 {
+                        0o120000 => permission_string('l', 0o777),
+                        0o160000 => permission_string('m', 0),
+                        0o040000 => permission_string('d', 0o755),
+                        _ => permission_string('-', mode),
+                    },
+                },
+            );
+            (row, lfs)
+        })
+        .collect())
+}
+
+/// The last commit touching a listed entry.
+pub struct Commit {
+    pub subject: String,
+    pub author: String,
+    pub date: String,
+}
+
+/// Subjects are cut to this many characters, the width `git log --oneline`
+/// conventions aim for, so the column doesn't push the rest off screen.
+const SUBJECT_WIDTH: usize = 50;
+
+impl Commit {
+    /// The subject, cut short with an ellipsis when it's longer than usual.
+    pub fn short_subject(&self) -> String {
+        match self.subject.chars().count() > SUBJECT_WIDTH {
+            true => {
+                let subject: String = self.subject.chars().take(SUBJECT_WIDTH - 1).collect();
+                format!("{}…", subject.trim_end())
+            }
+            false => self.subject.clone(),
+        }
+    }
+}
+
+/// Find the last commit touching each of `names` (entries of `dir`) with a single
+/// walk over the history, stopping as soon as every entry has been seen.
+pub fn last_commits(dir: &Path, names: &[String]) -> Result<Vec<Option<Commit>>, String> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--relative",
+            "--name-only",
+            "--date=short",
+            "--format=%x00%s%x1f%an%x1f%ad",
+            "--",
+            ".",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("can't run git: {err}"))?;
+
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), i))
+        .collect();
+    let mut commits: Vec<Option<Commit>> = names.iter().map(|_| None).collect();
+    let mut remaining = names.len();
+    let mut current: Option<(String, String, String)> = None;
+
+    let stdout = child.stdout.take().ok_or("git produced no output")?;
+    for line in BufReader::new(stdout).lines() {
+        if remaining == 0 {
+            break;
+        }
+        let line = line.map_err(|err| err.to_string())?;
+
+        if let Some(header) = line.strip_prefix('\0') {
+            let mut fields = header.split('\x1f').map(String::from);
+            current = Some((
+                fields.next().unwrap_or_default(),
+                fields.next().unwrap_or_default(),
+                fields.next().unwrap_or_default(),
+            ));
+        } else if let Some((subject, author, date)) = &current {
+            // Files in subdirectories count towards their top-level entry
+            let top = line.split('/').next().unwrap_or_default();
+            if let Some(&i) = index.get(top)
+                && commits[i].is_none()
+            {
+                commits[i] = Some(Commit {
+                    subject: subject.clone(),
+                    author: author.clone(),
+                    date: date.clone(),
+                });
+                remaining -= 1;
+            }
+        }
+    }
+
+    if remaining == 0 {
+        // Everything is known, the rest of the history isn't needed
+        let _ = child.kill();
+        let _ = child.wait();
+    } else {
+        let status = child.wait().map_err(|err| err.to_string())?;
+        if !status.success() {
+            return Err("not a git repository".into());
+        }
+    }
+    Ok(commits)
+}
+
+/// Number of commits in the last `months` months touching each of `names`
+/// (entries of `dir`).
+pub fn churn(dir: &Path, names: &[String], months: u32) -> Result<Vec<usize>, String> {
+    let since = format!("--since={months} months ago");
+    let log = git(
+        dir,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--relative",
+            "--name-only",
+            "--format=%x00",
+            &since,
+            "--",
+            ".",
+        ],
+    )?;
+
+    let index: HashMap<&str, usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), i))
+        .collect();
+    let mut counts = vec![0; names.len()];
+
+    for commit in log.split('\0').skip(1) {
+        // A commit touching several files of one directory counts once for it
+        let mut touched: Vec<usize> = commit
+            .lines()
+            .filter_map(|line| index.get(line.split('/').next()?).copied())
+            .collect();
+        touched.sort_unstable();
+        touched.dedup();
+        for i in touched {
+            counts[i] += 1;
+        }
+    }
+
+    Ok(counts)
+}
+
+/// How much a status letter says, so a directory shows its most pressing one
+const STATUS_PRIORITY: &str = "-INTARMDU";
+
+/// The two-letter status of each of `paths` (entries of `dir`, at any depth),
+/// like `eza --git`: what is staged, then what isn't, each one of `M`odified,
+/// `N`ew, `D`eleted, `R`enamed, `T`ype changed, `U`nmerged, `I`gnored or `-`.
+/// Directories show the most pressing status of what's inside them.
+pub fn statuses(dir: &Path, paths: &[PathBuf]) -> Result<Vec<String>, String> {
+    let prefix = git(dir, &["rev-parse", "--show-prefix"])?;
+    let prefix = prefix.trim_end_matches('\n');
+    let status = git(
+        dir,
+        &[
+            "status",
+            "--porcelain",
+            "-z",
+            "--ignored",
+            "--untracked-files=normal",
+            "--",
+            ".",
+        ],
+    )?;
+
+    // (path relative to `dir`, staged, unstaged); untracked and ignored
+    // directories end with a slash
+    let mut changes: Vec<(&str, char, char)> = Vec::new();
+    let mut records = status.split('\0');
+    while let Some(record) = records.next() {
+        let (Some(code), Some(path)) = (record.get(..2), record.get(3..)) else {
+            continue;
+        };
+        let letter = |letter: Option<char>| match letter {
+            Some(' ') | None => '-',
+            Some('A' | 'C') => 'N',
+            Some(letter) => letter,
+        };
+        // Untracked and ignored files aren't in the index, only the work tree
+        let (staged, unstaged) = match code {
+            "??" => ('-', 'N'),
+            "!!" => ('-', 'I'),
+            code => (letter(code.chars().next()), letter(code.chars().nth(1))),
+        };
+        // Renames and copies are followed by the path they came from
+        if record.starts_with(['R', 'C']) {
+            records.next();
+        }
+        if let Some(path) = path.strip_prefix(prefix) {
+            changes.push((path, staged, unstaged));
+        }
+    }
+
+    let pressing = |a: char, b: char| {
+        let rank = |letter| STATUS_PRIORITY.find(letter).unwrap_or(0);
+        if rank(b) > rank(a) { b } else { a }
+    };
+    Ok(paths
+        .iter()
+        .map(|path| {
+            let relative = path.strip_prefix(dir).unwrap_or(path).to_string_lossy();
+            let inside: Vec<_> = changes
+                .iter()
+                .filter(|(changed, ..)| {
+                    changed
+                        .strip_prefix(relative.as_ref())
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+                })
+                .collect();
+            // Otherwise it may lie inside an untracked or ignored directory,
+            // which is empty once `dir` itself is that directory
+            let matched = match inside.is_empty() {
+                true => changes
+                    .iter()
+                    .filter(|(changed, ..)| {
+                        changed.is_empty()
+                            || changed.ends_with('/') && relative.starts_with(changed)
+                    })
+                    .collect(),
+                false => inside,
+            };
+            let (mut staged, mut unstaged) = ('-', '-');
+            for (_, letter_staged, letter_unstaged) in matched {
+                staged = pressing(staged, *letter_staged);
+                unstaged = pressing(unstaged, *letter_unstaged);
+            }
+            format!("{staged}{unstaged}")
+        })
+        .collect())
+}
+
+/// The branch of the repository `dir` is in, how far it's ahead of and behind
+/// its upstream, and `*` when there are uncommitted changes, like
+/// `main ↑2 ↓1 *`. None outside a repository.
+pub fn header(dir: &Path) -> Option<String> {
+    let status = git(dir, &["status", "--porcelain=v2", "--branch"]).ok()?;
+    let (mut head, mut oid, mut ahead_behind) = (None, None, None);
+    let mut dirty = false;
+    for line in status.lines() {
+        match line.strip_prefix("# ") {
+            Some(header) => match header.split_once(' ') {
+                Some(("branch.head", value)) => head = Some(value),
+                Some(("branch.oid", value)) => oid = Some(value),
+                Some(("branch.ab", value)) => ahead_behind = Some(value),
+                _ => {}
+            },
+            None => dirty = true,
+        }
+    }
+
+    let mut header = match (head, oid) {
+        (Some("(detached)"), Some(oid)) => {
+            format!("detached at {}", oid.get(..7).unwrap_or(oid))
+        }
+        (Some(head), _) => escape_control(head),
+        (None, _) => return None,
+    };
+    // `+ahead -behind`, only there when the branch has an upstream
+    if let Some((ahead, behind)) = ahead_behind.and_then(|value| value.split_once(' ')) {
+        let ahead = ahead.trim_start_matches('+');
+        let behind = behind.trim_start_matches('-');
+        if ahead != "0" {
+            header.push_str(&format!(" ↑{ahead}"));
+        }
+        if behind != "0" {
+            header.push_str(&format!(" ↓{behind}"));
+        }
+    }
+    if dirty {
+        header.push_str(" *");
+    }
+    Some(header)
+}
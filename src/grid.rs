@@ -0,0 +1,70 @@
+//! `--grid`: only the names, packed into as many columns as fit the terminal,
+//! filled top to bottom like `ls`.
+
+use crate::color::ColorScheme;
+use ds::{Row, Types};
+use tabled::grid::util::string::get_text_width;
+
+/// Spaces between two columns
+const GAP: usize = 2;
+
+/// The width of the terminal on stdout, or `COLUMNS`, or 80.
+pub fn terminal_width() -> usize {
+    // SAFETY: TIOCGWINSZ only fills in the winsize it's given.
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0
+        && size.ws_col > 0
+    {
+        return size.ws_col as usize;
+    }
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(80)
+}
+
+/// The widths of the columns when `widths` are laid out in `rows` rows.
+fn column_widths(widths: &[usize], rows: usize) -> Vec<usize> {
+    widths
+        .chunks(rows)
+        .map(|column| column.iter().copied().max().unwrap_or(0))
+        .collect()
+}
+
+pub fn render(rows: &[Row], width: usize, colors: &ColorScheme) -> String {
+    let widths: Vec<usize> = rows
+        .iter()
+        .map(|(basic, ..)| get_text_width(&basic.name))
+        .collect();
+
+    // The fewest rows whose columns fit, one name per line if none do
+    let height = (1..=rows.len())
+        .find(|&height| {
+            let columns = column_widths(&widths, height);
+            columns.iter().sum::<usize>() + GAP * columns.len().saturating_sub(1) <= width
+        })
+        .unwrap_or(rows.len());
+    let columns = column_widths(&widths, height.max(1));
+
+    let mut output = String::new();
+    for line in 0..height {
+        for (column, column_width) in columns.iter().enumerate() {
+            let index = column * height + line;
+            let Some((basic, ..)) = rows.get(index) else {
+                break;
+            };
+            // Directories stand out the way they do in `ls`
+            let name = match basic.types {
+                Types::Dir => colors.kind.colorize(&basic.name),
+                _ => basic.name.clone(),
+            };
+            output.push_str(&name);
+            if rows.get(index + height).is_some() {
+                let padding = column_width - widths[index] + GAP;
+                output.push_str(&" ".repeat(padding));
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
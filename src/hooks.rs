@@ -0,0 +1,126 @@
+//! `[hooks]` in config.toml: a Rhai script with a say in which entries are
+//! listed and what color their names are, for logic no option covers, like
+//! highlighting the files a build manifest mentions.
+//!
+//! ```toml
+//! [hooks]
+//! script = '''
+//! fn filter(entry) { entry.type != "socket" && entry.bytes > 0 }
+//! fn color(entry) { if entry.name.ends_with(".rs") { "bold #dea584" } }
+//! '''
+//! ```
+//!
+//! Both functions are optional and get each entry as a map of the fields of
+//! `--output json`, with `path` as listed. `filter` returns `true` to list an
+//! entry or `false` to leave it out, `color` a color written as in `[theme]`,
+//! or nothing for the usual one.
+
+use crate::color::sgr;
+use crate::config;
+use crate::json;
+use ds::{Entry, Units};
+use rhai::{AST, Dynamic, Engine, Scope};
+use serde_json::Value;
+use std::path::Path;
+use tabled::settings::Color;
+
+#[derive(Default)]
+pub struct Hooks {
+    engine: Engine,
+    /// The compiled script, None when there's none
+    script: Option<AST>,
+}
+
+impl Hooks {
+    /// The hooks in the `[hooks]` section of `config`.
+    pub fn load(config: &toml::Table) -> Result<Hooks, String> {
+        let mut hooks = Hooks::default();
+        for (key, value) in config::section(config, "hooks")? {
+            let source = match (key.as_str(), value.as_str()) {
+                ("script", Some(source)) => source,
+                ("script", None) => return Err("the hook script must be a string".into()),
+                _ => return Err(format!("unknown hook setting '{key}'")),
+            };
+            hooks.script = Some(
+                hooks
+                    .engine
+                    .compile(source)
+                    .map_err(|err| err.to_string())?,
+            );
+        }
+        Ok(hooks)
+    }
+
+    /// What the script's function `name` returns for each entry, with sizes
+    /// in `units`. None when it has no such function.
+    fn call(
+        &self,
+        name: &str,
+        entries: &[Entry],
+        units: Units,
+    ) -> Result<Option<Vec<Dynamic>>, String> {
+        let Some(script) = self.script.as_ref().filter(|script| {
+            script
+                .iter_functions()
+                .any(|function| function.name == name)
+        }) else {
+            return Ok(None);
+        };
+        let Value::Array(fields) = json::entries(Path::new(""), entries, units) else {
+            unreachable!("entries are an array");
+        };
+        let mut scope = Scope::new();
+        fields
+            .iter()
+            .map(|fields| {
+                let entry = rhai::serde::to_dynamic(fields).map_err(|err| err.to_string())?;
+                self.engine
+                    .call_fn::<Dynamic>(&mut scope, script, name, (entry,))
+                    .map_err(|err| err.to_string())
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
+    /// Whether each entry is kept, None without a filter function.
+    pub fn filter(&self, entries: &[Entry], units: Units) -> Result<Option<Vec<bool>>, String> {
+        self.call("filter", entries, units)?
+            .map(|values| {
+                values
+                    .into_iter()
+                    .map(|value| {
+                        value
+                            .as_bool()
+                            .map_err(|kind| format!("expected true or false, got {kind}"))
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+
+    /// The color of each entry's name, None without a color function.
+    pub fn colors(
+        &self,
+        entries: &[Entry],
+        units: Units,
+    ) -> Result<Option<Vec<Option<Color>>>, String> {
+        self.call("color", entries, units)?
+            .map(|values| {
+                values
+                    .into_iter()
+                    .map(|value| {
+                        if value.is_unit() {
+                            return Ok(None);
+                        }
+                        let spec = value
+                            .into_string()
+                            .map_err(|kind| format!("expected a color or nothing, got {kind}"))?;
+                        sgr(&spec)
+                            .map(|codes| Some(Color::new(format!("\x1b[{codes}m"), "\x1b[0m")))
+                            .ok_or_else(|| format!("invalid color '{spec}'"))
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+}
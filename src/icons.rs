@@ -0,0 +1,147 @@
+//! `--icons`: a Nerd Font glyph before each name, chosen by type, well-known
+//! name and extension. Extensions can be added or remapped in the `[icons]`
+//! table of `~/.config/ds/config.toml`:
+//!
+//! ```toml
+//! [icons]
+//! nix = "\uf313"
+//! tf = "\U000f1062"
+//! ```
+
+use crate::config;
+use ds::Types;
+use std::collections::HashMap;
+
+const DIR: &str = "\u{f07b}";
+const SYMLINK: &str = "\u{f0c1}";
+const FIFO: &str = "\u{f0ec}";
+const SOCKET: &str = "\u{f1e6}";
+const DEVICE: &str = "\u{f0a0}";
+const EXECUTABLE: &str = "\u{f489}";
+const FILE: &str = "\u{f15b}";
+
+/// Directories and files recognized by their whole name
+const NAMES: &[(&str, &str)] = &[
+    (".git", "\u{e5fb}"),
+    ("node_modules", "\u{e5fa}"),
+    (".gitignore", "\u{f1d3}"),
+    (".gitattributes", "\u{f1d3}"),
+    (".gitmodules", "\u{f1d3}"),
+    ("Cargo.toml", "\u{e7a8}"),
+    ("Cargo.lock", "\u{e7a8}"),
+    ("Dockerfile", "\u{f308}"),
+    ("Makefile", "\u{f0ad}"),
+    ("LICENSE", "\u{f02d}"),
+];
+
+const EXTENSIONS: &[(&str, &str)] = &[
+    // Code
+    ("rs", "\u{e7a8}"),
+    ("py", "\u{e606}"),
+    ("js", "\u{e74e}"),
+    ("ts", "\u{e628}"),
+    ("go", "\u{e627}"),
+    ("c", "\u{e61e}"),
+    ("h", "\u{e61e}"),
+    ("cpp", "\u{e61d}"),
+    ("java", "\u{e738}"),
+    ("rb", "\u{e739}"),
+    ("lua", "\u{e620}"),
+    ("sh", "\u{f489}"),
+    ("bash", "\u{f489}"),
+    ("zsh", "\u{f489}"),
+    ("html", "\u{f13b}"),
+    ("css", "\u{e749}"),
+    // Text and data
+    ("md", "\u{f48a}"),
+    ("txt", "\u{f15c}"),
+    ("json", "\u{e60b}"),
+    ("toml", "\u{e615}"),
+    ("yaml", "\u{e615}"),
+    ("yml", "\u{e615}"),
+    ("ini", "\u{e615}"),
+    ("conf", "\u{e615}"),
+    ("lock", "\u{f023}"),
+    ("pdf", "\u{f1c1}"),
+    // Images
+    ("png", "\u{f1c5}"),
+    ("jpg", "\u{f1c5}"),
+    ("jpeg", "\u{f1c5}"),
+    ("gif", "\u{f1c5}"),
+    ("svg", "\u{f1c5}"),
+    ("webp", "\u{f1c5}"),
+    ("bmp", "\u{f1c5}"),
+    ("ico", "\u{f1c5}"),
+    // Audio and video
+    ("mp3", "\u{f1c7}"),
+    ("flac", "\u{f1c7}"),
+    ("wav", "\u{f1c7}"),
+    ("ogg", "\u{f1c7}"),
+    ("mp4", "\u{f1c8}"),
+    ("mkv", "\u{f1c8}"),
+    ("webm", "\u{f1c8}"),
+    ("avi", "\u{f1c8}"),
+    ("mov", "\u{f1c8}"),
+    // Archives
+    ("zip", "\u{f1c6}"),
+    ("tar", "\u{f1c6}"),
+    ("gz", "\u{f1c6}"),
+    ("xz", "\u{f1c6}"),
+    ("bz2", "\u{f1c6}"),
+    ("zst", "\u{f1c6}"),
+    ("7z", "\u{f1c6}"),
+    ("rar", "\u{f1c6}"),
+    ("deb", "\u{f1c6}"),
+    ("rpm", "\u{f1c6}"),
+];
+
+pub struct Icons {
+    extensions: HashMap<String, String>,
+}
+
+impl Icons {
+    /// The built-in icons, with the ones from the config file on top.
+    pub fn load() -> Result<Icons, String> {
+        let mut extensions: HashMap<String, String> = EXTENSIONS
+            .iter()
+            .map(|(extension, icon)| (extension.to_string(), icon.to_string()))
+            .collect();
+        let config = config::load()?;
+        // `icons = true` turns them on rather than remapping any
+        let remapped = match config.get("icons") {
+            Some(toml::Value::Boolean(_)) => toml::Table::new(),
+            _ => config::section(&config, "icons")?,
+        };
+        for (extension, icon) in remapped {
+            let icon = icon
+                .as_str()
+                .ok_or_else(|| format!("the icon for '{extension}' must be a string"))?;
+            extensions.insert(extension.to_lowercase(), icon.to_string());
+        }
+        Ok(Icons { extensions })
+    }
+
+    /// The icon of an entry called `name` (without any leading path).
+    pub fn icon(&self, kind: &Types, name: &str, executable: bool) -> &str {
+        if let Some((_, icon)) = NAMES.iter().find(|(known, _)| *known == name) {
+            return icon;
+        }
+        match kind {
+            Types::Dir => return DIR,
+            Types::Symlink => return SYMLINK,
+            Types::Fifo => return FIFO,
+            Types::Socket => return SOCKET,
+            Types::BlockDev | Types::CharDev => return DEVICE,
+            Types::File => {}
+        }
+        let extension = name
+            .rsplit_once('.')
+            .filter(|(stem, _)| !stem.is_empty())
+            .map(|(_, extension)| extension.to_lowercase());
+        match extension.and_then(|extension| self.extensions.get(&extension)) {
+            Some(icon) => icon,
+            None if executable => EXECUTABLE,
+            None => FILE,
+        }
+    }
+}
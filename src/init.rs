@@ -0,0 +1,139 @@
+//! `ds init`: a few questions about icons, colors and columns, answered into
+//! a commented config.toml to start from. Empty answers, or no terminal to
+//! ask on, keep the defaults.
+
+use crate::color::Palette;
+use crate::columns::Field;
+use crate::config;
+use clap::ValueEnum;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+/// The answer to `question`, trimmed; empty at the end of input.
+fn ask(question: &str) -> Result<String, String> {
+    print!("{question} ");
+    io::stdout().flush().map_err(|err| err.to_string())?;
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|err| err.to_string())?;
+    Ok(answer.trim().to_string())
+}
+
+fn names<T: ValueEnum>() -> Vec<String> {
+    T::value_variants()
+        .iter()
+        .filter_map(|value| value.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect()
+}
+
+/// `key = value`, commented out when it's the default.
+fn setting(key: &str, value: Option<String>, default: &str) -> String {
+    match value {
+        Some(value) => format!("{key} = {value}\n"),
+        None => format!("# {key} = {default}\n"),
+    }
+}
+
+/// Ask until the answer is empty or one `parse` accepts.
+fn choose<T>(
+    question: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Option<T>, String> {
+    loop {
+        let answer = ask(question)?;
+        if answer.is_empty() {
+            return Ok(None);
+        }
+        match parse(&answer) {
+            Ok(value) => return Ok(Some(value)),
+            Err(err) => println!("{err}"),
+        }
+    }
+}
+
+/// Ask the questions and write the answers to config.toml, which is only
+/// replaced with `force`. Returns the file written.
+pub fn run(force: bool) -> Result<PathBuf, String> {
+    let dir = config::dir().ok_or("no home directory to keep the settings in")?;
+    let file = dir.join("config.toml");
+    if file.exists() && !force {
+        return Err(format!(
+            "{} exists already (--force replaces it)",
+            file.display()
+        ));
+    }
+
+    let icons = choose(
+        "Show icons before names? They need a Nerd Font in the terminal. [y/N]",
+        |answer| match answer {
+            "y" | "Y" | "yes" => Ok(true),
+            "n" | "N" | "no" => Ok(false),
+            _ => Err("Answer y or n.".into()),
+        },
+    )?
+    .filter(|icons| *icons);
+    let palettes = names::<Palette>();
+    let palette = choose(
+        &format!("Color palette, {}? [default]", palettes.join(", ")),
+        |answer| match palettes.iter().any(|palette| palette == answer) {
+            true => Ok(format!("\"{answer}\"")),
+            false => Err(format!("Choose one of {}.", palettes.join(", "))),
+        },
+    )?;
+    let fields = names::<Field>();
+    let columns = choose(
+        &format!(
+            "Columns to show, in order, from {}? [name,type]",
+            fields.join(",")
+        ),
+        |answer| {
+            let columns: Vec<&str> = answer.split(',').map(str::trim).collect();
+            match columns
+                .iter()
+                .find(|column| !fields.iter().any(|field| field == *column))
+            {
+                Some(unknown) => Err(format!("Unknown column '{unknown}'.")),
+                None => Ok(format!(
+                    "[{}]",
+                    columns
+                        .iter()
+                        .map(|column| format!("\"{column}\""))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            }
+        },
+    )?;
+
+    let mut text = String::from(
+        "# Settings of ds. Top-level keys are defaults for the long options of the\n\
+         # same name, which the command line overrides; see `ds --help`.\n\n",
+    );
+    text += "# Nerd Font icons before each name\n";
+    text += &setting("icons", icons.map(|icons| icons.to_string()), "true");
+    text += &format!("\n# Color palette: {}\n", palettes.join(", "));
+    text += &setting("palette", palette, "\"default\"");
+    text += &format!("\n# Columns to show, in order: {}\n", fields.join(", "));
+    text += &setting("columns", columns, "[\"name\", \"type\", \"size\"]");
+    text += "\n# Hidden files, and the order of entries\n\
+             # all = true\n\
+             # sort = \"file-type\"\n\
+             \n\
+             # Colors of the columns, and of names by file type or suffix, written\n\
+             # like \"bold blue\", \"bright-red\", \"#e69f00\" or \"208\"\n\
+             # [theme]\n\
+             # size = \"bright-yellow\"\n\
+             # time = \"#e69f00\"\n\
+             #\n\
+             # [theme.names]\n\
+             # dir = \"bold blue\"\n\
+             # \"*.rs\" = \"#dea584\"\n";
+
+    fs::create_dir_all(&dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    fs::write(&file, text).map_err(|err| format!("{}: {err}", file.display()))?;
+    Ok(file)
+}
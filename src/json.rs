@@ -0,0 +1,288 @@
+//! JSON output: every collected field of each entry, with raw numbers next to
+//! the human-readable forms.
+
+use chrono::{DateTime, Utc};
+use ds::{
+    Basic, Binary, Entry, GroupOwner, Mac, Permission, Row, Size, SortField, Types, Units,
+    entry_type, escape_control, group_and_owner_mode, human_readable_size, is_broken_link,
+    permission_mode, shuffle,
+};
+use serde_json::{Map, Value, json};
+use std::fs::{self, Metadata};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// The keys of each entry, which `--fields` picks from.
+pub const FIELDS: &[&str] = &[
+    "name",
+    "path",
+    "type",
+    "broken",
+    "size",
+    "bytes",
+    "owner",
+    "group",
+    "uid",
+    "gid",
+    "permission",
+    "mode",
+    "mtime",
+    "mtime_iso",
+    "atime",
+    "atime_iso",
+    "ctime",
+    "ctime_iso",
+    "btime",
+    "btime_iso",
+];
+
+fn iso(seconds: i64, nanoseconds: i64) -> Value {
+    DateTime::<Utc>::from_timestamp(seconds, nanoseconds as u32)
+        .map(|date| Value::String(date.to_rfc3339()))
+        .unwrap_or(Value::Null)
+}
+
+/// `<key>` as epoch seconds and `<key>_iso` as an RFC 3339 string, both null
+/// when the time isn't known.
+fn timestamp(object: &mut Map<String, Value>, key: &str, time: Option<(i64, i64)>) {
+    let (epoch, text) = match time {
+        Some((seconds, nanoseconds)) => (json!(seconds), iso(seconds, nanoseconds)),
+        None => (Value::Null, Value::Null),
+    };
+    object.insert(key.into(), epoch);
+    object.insert(format!("{key}_iso"), text);
+}
+
+/// The modification, access, status change and birth times of an entry, in
+/// the order of [`FIELDS`], which CSV and TSV output take them in too.
+pub fn times(meta: &Metadata) -> Map<String, Value> {
+    let mut object = Map::new();
+    timestamp(
+        &mut object,
+        "mtime",
+        Some((meta.mtime(), meta.mtime_nsec())),
+    );
+    timestamp(
+        &mut object,
+        "atime",
+        Some((meta.atime(), meta.atime_nsec())),
+    );
+    timestamp(
+        &mut object,
+        "ctime",
+        Some((meta.ctime(), meta.ctime_nsec())),
+    );
+    // Birth time isn't recorded by every file system
+    let btime = meta
+        .created()
+        .ok()
+        .and_then(|created| created.duration_since(UNIX_EPOCH).ok())
+        .map(|since| (since.as_secs() as i64, since.subsec_nanos() as i64));
+    timestamp(&mut object, "btime", btime);
+    object
+}
+
+/// One object per entry; `path` is relative to the listed directory `root`.
+pub fn entries(root: &Path, entries: &[Entry], units: Units) -> Value {
+    entries
+        .iter()
+        .map(|entry| {
+            let (path, meta) = (&entry.path, &entry.meta);
+            let owner = group_and_owner_mode(meta);
+            let mut object = Map::new();
+            object.insert("name".into(), json!(entry.name().to_string_lossy()));
+            object.insert(
+                "path".into(),
+                json!(path.strip_prefix(root).unwrap_or(path).to_string_lossy()),
+            );
+            object.insert(
+                "type".into(),
+                json!(entry_type(meta).to_string().to_lowercase()),
+            );
+            object.insert("broken".into(), json!(is_broken_link(path, meta)));
+            object.insert("size".into(), json!(human_readable_size(meta.len(), units)));
+            object.insert("bytes".into(), json!(meta.len()));
+            object.insert("owner".into(), json!(owner.owner));
+            object.insert("group".into(), json!(owner.group));
+            object.insert("uid".into(), json!(meta.uid()));
+            object.insert("gid".into(), json!(meta.gid()));
+            object.insert("permission".into(), json!(permission_mode(meta).permission));
+            object.insert(
+                "mode".into(),
+                json!(format!("{:o}", meta.permissions().mode() & 0o7777)),
+            );
+            object.extend(times(meta));
+            Value::Object(object)
+        })
+        .collect()
+}
+
+/// Only the `fields` of each entry, for `--fields`.
+pub fn select(entries: Value, fields: &[String]) -> Value {
+    let Value::Array(entries) = entries else {
+        return entries;
+    };
+    entries
+        .into_iter()
+        .map(|entry| match entry {
+            Value::Object(mut object) => Value::Object(
+                fields
+                    .iter()
+                    .filter_map(|field| object.remove_entry(field))
+                    .collect(),
+            ),
+            entry => entry,
+        })
+        .collect()
+}
+
+/// Objects for rows without file system metadata, like a listing from git.
+pub fn rows(rows: &[Row]) -> Value {
+    rows.iter()
+        .map(|(basic, size, binary, _, _, permission)| {
+            json!({
+                "name": basic.name,
+                "path": basic.name,
+                "type": basic.types.to_string().to_lowercase(),
+                "size": size.size,
+                "bytes": binary.size.parse::<u64>().ok(),
+                "permission": permission.permission,
+            })
+        })
+        .collect()
+}
+
+fn kind(name: &str) -> Option<Types> {
+    Some(match name {
+        "file" => Types::File,
+        "dir" => Types::Dir,
+        "symlink" => Types::Symlink,
+        "fifo" => Types::Fifo,
+        "socket" => Types::Socket,
+        "blockdev" => Types::BlockDev,
+        "chardev" => Types::CharDev,
+        _ => return None,
+    })
+}
+
+/// Rows of a listing saved with `--output json`, on this machine or another,
+/// filtered and sorted again, at random with `seed`. Fields the export lacks,
+/// like those of a git revision or left out by `--fields`, are left empty, and
+/// entries without a type are taken for files.
+pub fn read(
+    file: &Path,
+    show_hidden: bool,
+    directories_only: bool,
+    sort: SortField,
+    reverse: bool,
+    seed: u64,
+    units: Units,
+) -> Result<Vec<Row>, String> {
+    let text = fs::read_to_string(file).map_err(|err| format!("{}: {err}", file.display()))?;
+    let value: Value =
+        serde_json::from_str(&text).map_err(|err| format!("{}: {err}", file.display()))?;
+    let invalid =
+        |index: usize, what: &str| format!("{}: entry {index} has no valid {what}", file.display());
+    let Value::Array(objects) = value else {
+        return Err(format!("{}: expected an array of entries", file.display()));
+    };
+
+    let mut objects = objects
+        .iter()
+        .enumerate()
+        .map(|(index, object)| {
+            let path = object
+                .get("path")
+                .or_else(|| object.get("name"))
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid(index, "path"))?;
+            let types = match object.get("type") {
+                Some(types) => types
+                    .as_str()
+                    .and_then(kind)
+                    .ok_or_else(|| invalid(index, "type"))?,
+                None => Types::File,
+            };
+            Ok((path, types, object))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    objects.retain(|(path, types, _)| {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        (show_hidden || !name.starts_with('.'))
+            && (!directories_only || matches!(types, Types::Dir))
+    });
+
+    let number = |object: &Value, key: &str| object.get(key).and_then(Value::as_i64);
+    let text = |object: &Value, key: &str| {
+        object
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    match sort {
+        SortField::Name | SortField::Path => objects.sort_by(|a, b| a.0.cmp(b.0)),
+        SortField::Depth => objects.sort_by_key(|(path, ..)| (path.matches('/').count(), *path)),
+        SortField::Size => objects.sort_by_key(|(.., object)| number(object, "bytes")),
+        SortField::Extension => objects.sort_by_key(|(path, ..)| {
+            Path::new(path)
+                .extension()
+                .map(|ext| ext.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        }),
+        SortField::Modified => objects.sort_by_key(|(.., object)| number(object, "mtime")),
+        SortField::Changed => objects.sort_by_key(|(.., object)| number(object, "ctime")),
+        SortField::Accessed => objects.sort_by_key(|(.., object)| number(object, "atime")),
+        SortField::Created => objects.sort_by_key(|(.., object)| number(object, "btime")),
+        SortField::FileType => {
+            objects.sort_by_key(|(path, types, _)| (!matches!(types, Types::Dir), *path))
+        }
+        SortField::Random => shuffle(&mut objects, seed),
+        // Inodes aren't exported; keep the saved order
+        SortField::Inode | SortField::None => {}
+    }
+    if reverse {
+        objects.reverse();
+    }
+
+    let date = |object: &Value, key: &str| {
+        number(object, key)
+            .and_then(|seconds| DateTime::<Utc>::from_timestamp(seconds, 0))
+            .map(|date| date.format("%a %b %e %Y").to_string())
+            .unwrap_or_default()
+    };
+    Ok(objects
+        .into_iter()
+        .map(|(path, types, object)| {
+            let bytes = object.get("bytes").and_then(Value::as_u64);
+            (
+                Basic {
+                    name: escape_control(path),
+                    types,
+                },
+                Size {
+                    size: match bytes {
+                        Some(bytes) => human_readable_size(bytes, units),
+                        None => text(object, "size"),
+                    },
+                },
+                Binary {
+                    size: bytes.map(|bytes| bytes.to_string()).unwrap_or_default(),
+                },
+                GroupOwner {
+                    owner: text(object, "owner"),
+                    group: text(object, "group"),
+                },
+                Mac {
+                    modified: date(object, "mtime"),
+                    accessed: date(object, "atime"),
+                    created: date(object, "btime"),
+                },
+                Permission {
+                    permission: text(object, "permission"),
+                },
+            )
+        })
+        .collect())
+}
@@ -0,0 +1,973 @@
+//! The listing engine behind `ds`: reading, filtering, sorting and walking
+//! directories, turning their entries into the rows of a listing, and laying
+//! columns of cells out as a table. Which columns there are, `--format`
+//! templates and colors stay in the `ds` binary, as they draw on its Git,
+//! package and plugin lookups.
+//!
+//! ```no_run
+//! use ds::{DirLister, SortField, Units, file_rows};
+//!
+//! let lister = DirLister {
+//!     sort: SortField::Size,
+//!     ..DirLister::default()
+//! };
+//! for (basic, size, ..) in file_rows(&lister.list(".".as_ref()), Units::Binary) {
+//!     println!("{}\t{}", basic.name, size.size);
+//! }
+//! ```
+
+use chrono::DateTime;
+use chrono::Utc;
+use glob::{MatchOptions, Pattern};
+use ignore::WalkBuilder;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::fs::Metadata;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use strum::{Display, EnumString, VariantNames};
+use tabled::{
+    Table,
+    builder::Builder,
+    settings::{Color, Style, object::Columns, object::Rows},
+};
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+use users::{Groups, Users, UsersCache};
+
+#[derive(Debug, Display, Clone)]
+pub enum Types {
+    File,
+    Dir,
+    Symlink,
+    /// Named pipe
+    Fifo,
+    Socket,
+    BlockDev,
+    CharDev,
+}
+
+/// What entries are sorted by. Parsed from and shown as kebab-case names
+/// (`file-type`), the way `--sort` takes them.
+#[derive(Debug, Clone, Copy, Display, EnumString, VariantNames)]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+pub enum SortField {
+    Name,
+    Size,
+    Extension,
+    Modified,
+    Changed,
+    Accessed,
+    Created,
+    Inode,
+    FileType,
+    None,
+    Random,
+    /// The whole path, so walks keep each directory's entries together
+    Path,
+    /// How deep below the listed directory, then the path
+    Depth,
+}
+
+/// How names compare when sorting by name.
+#[derive(Debug, Clone, Copy, Default, Display, EnumString, VariantNames)]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+pub enum Collate {
+    /// Byte order, which puts `é` after `z`
+    #[default]
+    Byte,
+    /// Base letters only, so `é` and `E` sort with `e` and `ü` with `u`
+    Base,
+}
+
+#[derive(Debug, Display, Clone, Copy, EnumString, VariantNames)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum Units {
+    Binary,
+    Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct Basic {
+    pub name: String,
+    pub types: Types,
+}
+
+#[derive(Debug, Clone)]
+pub struct Size {
+    pub size: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Mac {
+    pub modified: String,
+    pub accessed: String,
+    pub created: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Permission {
+    pub permission: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Binary {
+    pub size: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupOwner {
+    pub owner: String,
+    pub group: String,
+}
+
+pub type Row = (Basic, Size, Binary, GroupOwner, Mac, Permission);
+
+/// A listed path along with its metadata, which describes symlinks
+/// themselves unless `DirLister::dereference` is set.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub meta: Metadata,
+}
+
+impl Entry {
+    /// The last component of the path, or the whole path when it has none
+    /// (like `/` or `..`).
+    pub fn name(&self) -> &OsStr {
+        self.path.file_name().unwrap_or(self.path.as_os_str())
+    }
+
+    /// Whether the entry is a symlink, whatever its metadata says.
+    fn is_link(&self) -> bool {
+        self.meta.file_type().is_symlink()
+            || fs::symlink_metadata(&self.path).is_ok_and(|meta| meta.file_type().is_symlink())
+    }
+}
+
+/// The paths listings couldn't read, with why, shared by the clones of a
+/// `DirLister`.
+#[derive(Debug, Clone, Default)]
+pub struct ReadErrors(Arc<Mutex<Vec<(PathBuf, io::Error)>>>);
+
+impl ReadErrors {
+    fn record(&self, path: PathBuf, err: io::Error) {
+        if let Ok(mut errors) = self.0.lock() {
+            errors.push((path, err));
+        }
+    }
+
+    /// Every path that couldn't be read, with why.
+    pub fn all(&self) -> Vec<(PathBuf, String)> {
+        self.0
+            .lock()
+            .map(|errors| {
+                errors
+                    .iter()
+                    .map(|(path, err)| (path.clone(), err.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The paths refused for lack of permission, like directories a walk
+    /// couldn't enter.
+    pub fn denied(&self) -> Vec<PathBuf> {
+        self.0
+            .lock()
+            .map(|errors| {
+                errors
+                    .iter()
+                    .filter(|(_, err)| err.kind() == io::ErrorKind::PermissionDenied)
+                    .map(|(path, _)| path.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// A shell pattern names are matched against, from `--glob` or `--ext`.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    pub pattern: Pattern,
+    /// Letters match either case, as with `--iglob` and `--iext`
+    pub ignore_case: bool,
+}
+
+impl Glob {
+    /// The pattern matching names ending in `.extension`.
+    pub fn extension(extension: &str, ignore_case: bool) -> Glob {
+        let extension = Pattern::escape(extension.trim_start_matches('.'));
+        Glob {
+            pattern: Pattern::new(&format!("*.{extension}")).expect("an escaped pattern is valid"),
+            ignore_case,
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        let options = MatchOptions {
+            case_sensitive: !self.ignore_case,
+            ..MatchOptions::new()
+        };
+        self.pattern.matches_with(name, options)
+    }
+}
+
+/// Lists directories with one set of filtering and sorting options.
+#[derive(Debug, Clone)]
+pub struct DirLister {
+    /// Include hidden entries
+    pub all: bool,
+    /// Only list directories
+    pub dirs: bool,
+    /// Leave out what ignore files ignore, like `.gitignore` and `.ignore` in
+    /// the directory and its parents, `.git/info/exclude` and the global
+    /// `~/.config/git/ignore`. Walks read them again in every directory.
+    pub git_ignore: bool,
+    pub sort: SortField,
+    pub reverse: bool,
+    pub collate: Collate,
+    /// Seed of `SortField::Random`, so a seed always produces the same order
+    pub seed: u64,
+    /// Describe what symlinks point to rather than the links, like `ls -L`
+    pub dereference: bool,
+    /// Only list entries whose names match one of these, when there are any
+    pub globs: Vec<Glob>,
+    /// The listing is walked, so directories are kept whatever `globs` say
+    /// to find the matches below them
+    pub walk: bool,
+    /// Where the paths that couldn't be read are kept
+    pub errors: ReadErrors,
+}
+
+impl Default for DirLister {
+    fn default() -> Self {
+        DirLister {
+            all: false,
+            dirs: false,
+            git_ignore: false,
+            sort: SortField::Name,
+            reverse: false,
+            collate: Collate::Byte,
+            seed: 0,
+            dereference: false,
+            globs: Vec::new(),
+            walk: false,
+            errors: ReadErrors::default(),
+        }
+    }
+}
+
+impl DirLister {
+    /// The entries of `dir`, filtered and sorted.
+    pub fn list(&self, dir: &Path) -> Vec<Entry> {
+        let mut entries: Vec<Entry> = self.read_entries(dir).collect();
+        sort_entries(&mut entries, self.sort, self.reverse, self.collate);
+        if matches!(self.sort, SortField::Random) {
+            shuffle(&mut entries, self.seed);
+        }
+        entries
+    }
+
+    /// The entries of `paths` themselves, like files named on the command
+    /// line, sorted. Hidden ones are kept since they were asked for, and each
+    /// keeps the path it was given by.
+    pub fn named(&self, paths: &[PathBuf]) -> Vec<Entry> {
+        let mut entries: Vec<Entry> = paths
+            .iter()
+            .filter_map(|path| {
+                // Broken links keep describing themselves
+                let meta = match self.dereference {
+                    true => fs::metadata(path).or_else(|_| fs::symlink_metadata(path)),
+                    false => fs::symlink_metadata(path),
+                }
+                .map_err(|err| self.errors.record(path.clone(), err))
+                .ok()?;
+                Some(Entry {
+                    path: path.clone(),
+                    meta,
+                })
+            })
+            .filter(|entry| !self.dirs || !entry.meta.is_file())
+            .collect();
+        sort_entries(&mut entries, self.sort, self.reverse, self.collate);
+        if matches!(self.sort, SortField::Random) {
+            shuffle(&mut entries, self.seed);
+        }
+        entries
+    }
+
+    /// `count` entries of `dir` picked at random with the seed, then sorted.
+    pub fn sample(&self, dir: &Path, count: usize) -> Vec<Entry> {
+        let entries = self.read_entries(dir);
+        let mut entries = sample(entries, count, self.seed);
+        sort_entries(&mut entries, self.sort, self.reverse, self.collate);
+        if matches!(self.sort, SortField::Random) {
+            shuffle(&mut entries, self.seed);
+        }
+        entries
+    }
+
+    /// `entries` and everything below them down to `depth` levels, each paired
+    /// with the box-drawing prefix of its branch (`│   ├── `). Only the first
+    /// `limit` entries of each directory are kept, the rest are `Omitted`.
+    pub fn tree(
+        &self,
+        entries: Vec<Entry>,
+        depth: usize,
+        limit: usize,
+    ) -> (Vec<(String, Entry)>, Vec<Omitted>) {
+        let mut tree = Vec::new();
+        let mut omitted = Vec::new();
+        let walk = Walk {
+            limit,
+            children: &|dir| self.list(dir),
+        };
+        walk.tree(entries, "", depth, &mut tree, &mut omitted);
+        (tree, omitted)
+    }
+
+    /// `entries` and everything below them down to `depth` levels, each paired
+    /// with the path of its parent relative to the listed directory (`src/bin/`).
+    /// Sorting by path or depth orders the whole walk rather than each
+    /// directory.
+    pub fn recursive(&self, entries: Vec<Entry>, depth: usize) -> Vec<(String, Entry)> {
+        let mut listing = recursive_entries(entries, "", depth, &|dir| self.list(dir));
+        match self.sort {
+            SortField::Path => listing.sort_by(|a, b| a.1.path.cmp(&b.1.path)),
+            SortField::Depth => listing.sort_by(|a, b| {
+                let depth = |(parent, _): &(String, Entry)| parent.matches('/').count();
+                (depth(a), &a.1.path).cmp(&(depth(b), &b.1.path))
+            }),
+            _ => return listing,
+        }
+        if self.reverse {
+            listing.reverse();
+        }
+        listing
+    }
+}
+
+/// Fisher-Yates shuffle driven by a splitmix64 generator, so a given seed always
+/// produces the same order.
+pub fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    for i in (1..items.len()).rev() {
+        let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Uniformly pick `count` items in a single pass (reservoir sampling), so huge
+/// directories never have to be held or sorted in full.
+fn sample<T>(items: impl Iterator<Item = T>, count: usize, seed: u64) -> Vec<T> {
+    let mut state = seed;
+    let mut reservoir = Vec::with_capacity(count);
+    for (seen, item) in items.enumerate() {
+        if seen < count {
+            reservoir.push(item);
+        } else {
+            let slot = (splitmix64(&mut state) % (seen as u64 + 1)) as usize;
+            if slot < count {
+                reservoir[slot] = item;
+            }
+        }
+    }
+    reservoir
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Total size of the files under `path`, without following symlinks.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    if interrupted() {
+        return 0;
+    }
+
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| dir_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Set when the user asks to stop, like with Ctrl-C
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Stop reading directories: listings end early with what they've read so
+/// far. Safe to call from a signal handler.
+pub fn interrupt() {
+    INTERRUPTED.store(true, Ordering::Relaxed);
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// The entries of `dir` no ignore file leaves out, like ripgrep and fd: the
+/// `.gitignore` and `.ignore` files of the directory and its parents,
+/// `.git/info/exclude` and the global `~/.config/git/ignore`. Git's own files
+/// only count inside a repository.
+fn not_ignored(dir: &Path) -> HashSet<PathBuf> {
+    // Hidden entries are left to the listing's own filter
+    WalkBuilder::new(dir)
+        .max_depth(Some(1))
+        .hidden(false)
+        .build()
+        .flatten()
+        .filter(|entry| entry.depth() == 1)
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+impl DirLister {
+    /// The directory's entries in readdir order, after the hidden/dirs/glob/git
+    /// filters.
+    fn read_entries(&self, path: &Path) -> impl Iterator<Item = Entry> {
+        let dir = path.to_path_buf();
+        let kept = self.git_ignore.then(|| not_ignored(path));
+        let errors = self.errors.clone();
+        let read = fs::read_dir(path).map_err(|err| errors.record(path.to_path_buf(), err));
+        read.into_iter()
+            .flatten()
+            .take_while(|_| !interrupted())
+            .filter_map(move |entry| {
+                let entry = entry.map_err(|err| errors.record(dir.clone(), err)).ok()?;
+                // Broken links keep describing themselves
+                let meta = match self.dereference {
+                    true => fs::metadata(entry.path()).or_else(|_| entry.metadata()),
+                    false => entry.metadata(),
+                }
+                .map_err(|err| errors.record(entry.path(), err))
+                .ok()?;
+                Some(Entry {
+                    path: entry.path(),
+                    meta,
+                })
+            })
+            .filter(move |entry| {
+                let file_name = entry.name().to_string_lossy();
+
+                if self.dirs && entry.meta.is_file() {
+                    return false;
+                }
+                // Walks descend into every directory to find the matches below
+                let walked = self.walk && entry.meta.is_dir();
+                if !self.globs.is_empty()
+                    && !walked
+                    && !self.globs.iter().any(|glob| glob.matches(&file_name))
+                {
+                    return false;
+                }
+                // Git never lists its own directory either
+                if kept
+                    .as_ref()
+                    .is_some_and(|kept| file_name == ".git" || !kept.contains(&entry.path))
+                {
+                    return false;
+                }
+                self.all || !file_name.starts_with('.')
+            })
+    }
+}
+
+fn sort_entries(entries: &mut [Entry], sort: SortField, reverse: bool, collate: Collate) {
+    // Sort entries based on the specified field
+    match sort {
+        // Entries of one directory differ only by name; walks sort them again
+        SortField::Name | SortField::Path | SortField::Depth => match collate {
+            Collate::Byte => entries.sort_by(|a, b| a.name().cmp(b.name())),
+            Collate::Base => entries.sort_by_cached_key(|a| {
+                let name = a.name();
+                (base_letters(&name.to_string_lossy()), name.to_os_string())
+            }),
+        },
+        SortField::Size => {
+            entries.sort_by_key(|a| a.meta.len());
+        }
+        SortField::Extension => {
+            entries.sort_by(|a, b| {
+                let name_a = a.name();
+                let name_b = b.name();
+
+                let ext_a = Path::new(&name_a)
+                    .extension()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("");
+                let ext_b = Path::new(&name_b)
+                    .extension()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("");
+
+                ext_a.cmp(ext_b)
+            });
+        }
+        SortField::Modified => {
+            entries.sort_by(|a, b| {
+                a.meta
+                    .modified()
+                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+                    .cmp(
+                        &b.meta
+                            .modified()
+                            .unwrap_or(std::time::SystemTime::UNIX_EPOCH),
+                    )
+            });
+        }
+        SortField::Changed => {
+            // ctime (last status change) is only exposed through the Unix metadata
+            entries.sort_by_key(|a| (a.meta.ctime(), a.meta.ctime_nsec()));
+        }
+        SortField::Accessed => {
+            entries.sort_by(|a, b| {
+                a.meta
+                    .accessed()
+                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+                    .cmp(
+                        &b.meta
+                            .accessed()
+                            .unwrap_or(std::time::SystemTime::UNIX_EPOCH),
+                    )
+            });
+        }
+        SortField::Created => {
+            entries.sort_by(|a, b| {
+                a.meta
+                    .created()
+                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+                    .cmp(
+                        &b.meta
+                            .created()
+                            .unwrap_or(std::time::SystemTime::UNIX_EPOCH),
+                    )
+            });
+        }
+        SortField::Inode => {
+            entries.sort_by_key(|a| a.meta.ino());
+        }
+        SortField::FileType => {
+            entries.sort_by(|a, b| {
+                let a_type = a.meta.file_type();
+                let b_type = b.meta.file_type();
+
+                match (a_type.is_dir(), b_type.is_dir()) {
+                    (true, false) => std::cmp::Ordering::Less,
+                    (false, true) => std::cmp::Ordering::Greater,
+                    _ => a.name().cmp(b.name()),
+                }
+            });
+        }
+        // Raw readdir order; random is shuffled by the caller
+        SortField::None | SortField::Random => {}
+    }
+
+    if reverse {
+        entries.reverse();
+    }
+}
+
+/// The base letters of `name` in lower case, the primary strength of Unicode
+/// collation: accents and other combining marks are dropped, and letters with
+/// no decomposition spelled out, like `ß` as `ss`.
+fn base_letters(name: &str) -> String {
+    let mut base = String::with_capacity(name.len());
+    for c in name.nfd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            'ß' => base.push_str("ss"),
+            'æ' | 'Æ' => base.push_str("ae"),
+            'œ' | 'Œ' => base.push_str("oe"),
+            'ø' | 'Ø' => base.push('o'),
+            'đ' | 'Đ' => base.push('d'),
+            'ł' | 'Ł' => base.push('l'),
+            'ı' => base.push('i'),
+            _ => base.extend(c.to_lowercase()),
+        }
+    }
+    base
+}
+
+/// Entries of a directory left out of a tree: `count` of them, told on a
+/// line drawn with `branch` before the entry at `index` of the tree.
+#[derive(Debug, Clone)]
+pub struct Omitted {
+    pub index: usize,
+    pub branch: String,
+    pub count: usize,
+}
+
+/// How a tree is walked: at most `limit` entries of each directory, read
+/// with `children`.
+struct Walk<'a> {
+    limit: usize,
+    children: &'a dyn Fn(&Path) -> Vec<Entry>,
+}
+
+impl Walk<'_> {
+    fn tree(
+        &self,
+        entries: Vec<Entry>,
+        indent: &str,
+        depth: usize,
+        tree: &mut Vec<(String, Entry)>,
+        omitted: &mut Vec<Omitted>,
+    ) {
+        let count = entries.len();
+        let shown = count.min(self.limit);
+        for (i, entry) in entries.into_iter().take(shown).enumerate() {
+            // The line telling what's left out comes last
+            let last = i + 1 == count;
+            let branch = if last { "└── " } else { "├── " };
+            let below = (entry.meta.is_dir() && depth > 1 && !entry.is_link())
+                .then(|| (self.children)(&entry.path));
+            tree.push((format!("{indent}{branch}"), entry));
+            if let Some(below) = below {
+                let indent = format!("{indent}{}", if last { "    " } else { "│   " });
+                self.tree(below, &indent, depth - 1, tree, omitted);
+            }
+        }
+        if shown < count {
+            omitted.push(Omitted {
+                index: tree.len(),
+                branch: format!("{indent}└── "),
+                count: count - shown,
+            });
+        }
+    }
+}
+
+/// Like `DirLister::tree`, but each entry is paired with the path of its parent
+/// relative to the listed directory (`src/bin/`).
+fn recursive_entries(
+    entries: Vec<Entry>,
+    parent: &str,
+    depth: usize,
+    children: &dyn Fn(&Path) -> Vec<Entry>,
+) -> Vec<(String, Entry)> {
+    let mut listing = Vec::new();
+    for entry in entries {
+        let below = if entry.meta.is_dir() && depth > 1 && !entry.is_link() {
+            let parent = format!(
+                "{parent}{}/",
+                escape_control(&entry.name().to_string_lossy())
+            );
+            recursive_entries(children(&entry.path), &parent, depth - 1, children)
+        } else {
+            Vec::new()
+        };
+        listing.push((parent.to_string(), entry));
+        listing.extend(below);
+    }
+    listing
+}
+
+pub fn file_rows(entries: &[Entry], units: Units) -> Vec<Row> {
+    entries
+        .iter()
+        .map(|entry| {
+            let meta = &entry.meta;
+            (
+                basic_mode(entry),
+                size_mode(meta, units),
+                binary_mode(meta),
+                group_and_owner_mode(meta),
+                mac_mode(meta),
+                permission_mode(meta),
+            )
+        })
+        .collect()
+}
+
+/// One column of a listing table: a header and a cell for each row. A listing
+/// is assembled from columns, so they combine freely.
+pub struct Column {
+    header: String,
+    cells: Vec<String>,
+    color: Option<Color>,
+    /// Cells colored differently from the rest of the column
+    highlights: Vec<(usize, Color)>,
+}
+
+impl Column {
+    pub fn new(header: impl Into<String>, cells: Vec<String>, color: Color) -> Self {
+        Column {
+            header: header.into(),
+            cells,
+            color: Some(color),
+            highlights: Vec::new(),
+        }
+    }
+
+    /// A column in the terminal's default color.
+    pub fn plain(header: impl Into<String>, cells: Vec<String>) -> Self {
+        Column {
+            header: header.into(),
+            cells,
+            color: None,
+            highlights: Vec::new(),
+        }
+    }
+
+    /// Color the cells of the rows where `rows` is true with `color` instead.
+    pub fn highlight(mut self, rows: &[bool], color: Color) -> Self {
+        for (row, _) in rows.iter().enumerate().filter(|(_, marked)| **marked) {
+            self.highlights.push((row, color.clone()));
+        }
+        self
+    }
+
+    /// Put in a row at `row` with `cell`, colored `color`, moving the rows
+    /// below down.
+    pub fn insert(&mut self, row: usize, cell: String, color: Option<Color>) {
+        self.cells.insert(row.min(self.cells.len()), cell);
+        for (highlighted, _) in &mut self.highlights {
+            if *highlighted >= row {
+                *highlighted += 1;
+            }
+        }
+        if let Some(color) = color {
+            self.highlights.push((row, color));
+        }
+    }
+
+    pub fn into_cells(self) -> Vec<String> {
+        self.cells
+    }
+
+    /// Color each cell with its own color, where it has one.
+    pub fn paint(mut self, colors: &[Option<Color>]) -> Self {
+        for (row, color) in colors.iter().enumerate() {
+            if let Some(color) = color {
+                self.highlights.push((row, color.clone()));
+            }
+        }
+        self
+    }
+}
+
+/// A borderless table of `columns`, side by side, with headers in `header`.
+pub fn table(columns: &[Column], header: Color) -> Table {
+    let rows = columns
+        .iter()
+        .map(|column| column.cells.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut builder = Builder::new();
+    builder.push_record(columns.iter().map(|column| column.header.clone()));
+    for row in 0..rows {
+        builder.push_record(
+            columns
+                .iter()
+                .map(|column| column.cells.get(row).cloned().unwrap_or_default()),
+        );
+    }
+
+    let mut table = builder.build();
+    table.with(Style::empty());
+    for (index, column) in columns.iter().enumerate() {
+        if let Some(color) = &column.color {
+            table.modify(Columns::one(index), color.clone());
+        }
+        for (row, color) in &column.highlights {
+            table.modify((row + 1, index), color.clone());
+        }
+    }
+    table.modify(Rows::first(), header);
+    table
+}
+
+fn basic_mode(entry: &Entry) -> Basic {
+    Basic {
+        name: escape_control(&entry.name().to_string_lossy()),
+        types: entry_type(&entry.meta),
+    }
+}
+
+/// The `ls -F` suffix of an entry: `/` for directories, `@` for symlinks, `*`
+/// for executables, `|` for FIFOs and `=` for sockets.
+pub fn indicator(kind: &Types, executable: bool) -> &'static str {
+    match kind {
+        Types::Dir => "/",
+        Types::Symlink => "@",
+        Types::Fifo => "|",
+        Types::Socket => "=",
+        Types::File if executable => "*",
+        Types::File | Types::BlockDev | Types::CharDev => "",
+    }
+}
+
+/// Whether `path` is a symlink whose target doesn't exist, given metadata of
+/// the link itself.
+pub fn is_broken_link(path: &Path, meta: &Metadata) -> bool {
+    meta.file_type().is_symlink() && fs::metadata(path).is_err()
+}
+
+/// The type of an entry, from metadata that doesn't follow symlinks (as given
+/// by `DirEntry::metadata` and `fs::symlink_metadata`).
+pub fn entry_type(meta: &Metadata) -> Types {
+    let kind = meta.file_type();
+    if kind.is_symlink() {
+        Types::Symlink
+    } else if kind.is_dir() {
+        Types::Dir
+    } else if kind.is_fifo() {
+        Types::Fifo
+    } else if kind.is_socket() {
+        Types::Socket
+    } else if kind.is_block_device() {
+        Types::BlockDev
+    } else if kind.is_char_device() {
+        Types::CharDev
+    } else {
+        Types::File
+    }
+}
+
+/// Replace control characters with their Unicode control pictures (`\n` -> `␊`)
+/// so a single odd file name can't break the alignment of the whole table.
+pub fn escape_control(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '\0'..='\x1f' => char::from_u32(0x2400 + c as u32).unwrap_or(c),
+            '\x7f' => '␡',
+            _ => c,
+        })
+        .collect()
+}
+
+fn size_mode(meta: &Metadata, units: Units) -> Size {
+    Size {
+        size: human_readable_size(meta.len(), units),
+    }
+}
+
+fn mac_mode(meta: &Metadata) -> Mac {
+    Mac {
+        modified: if let Ok(modi) = meta.modified() {
+            let date: DateTime<Utc> = modi.into();
+            format!("{}", date.format("%a %b %e %Y"))
+        } else {
+            String::default()
+        },
+
+        accessed: if let Ok(access) = meta.accessed() {
+            let date: DateTime<Utc> = access.into();
+            format!("{}", date.format("%a %b %e %Y"))
+        } else {
+            String::default()
+        },
+        created: if let Ok(created) = meta.created() {
+            let date: DateTime<Utc> = created.into();
+            format!("{}", date.format("%a %b %e %Y"))
+        } else {
+            String::default()
+        },
+    }
+}
+
+pub fn permission_mode(meta: &Metadata) -> Permission {
+    Permission {
+        permission: permission_string(
+            match entry_type(meta) {
+                Types::File => '-',
+                Types::Dir => 'd',
+                Types::Symlink => 'l',
+                Types::Fifo => 'p',
+                Types::Socket => 's',
+                Types::BlockDev => 'b',
+                Types::CharDev => 'c',
+            },
+            meta.permissions().mode(),
+        ),
+    }
+}
+
+pub fn permission_string(kind: char, mode: u32) -> String {
+    let mut perm_string = String::with_capacity(10);
+
+    // File type
+    perm_string.push(kind);
+
+    // User permissions
+    perm_string.push(if mode & 0o400 != 0 { 'r' } else { '-' });
+    perm_string.push(if mode & 0o200 != 0 { 'w' } else { '-' });
+    perm_string.push(if mode & 0o100 != 0 { 'x' } else { '-' });
+
+    // Group permissions
+    perm_string.push(if mode & 0o40 != 0 { 'r' } else { '-' });
+    perm_string.push(if mode & 0o20 != 0 { 'w' } else { '-' });
+    perm_string.push(if mode & 0o10 != 0 { 'x' } else { '-' });
+
+    // Other permissions
+    perm_string.push(if mode & 0o4 != 0 { 'r' } else { '-' });
+    perm_string.push(if mode & 0o2 != 0 { 'w' } else { '-' });
+    perm_string.push(if mode & 0o1 != 0 { 'x' } else { '-' });
+
+    perm_string
+}
+
+fn binary_mode(meta: &Metadata) -> Binary {
+    Binary {
+        size: meta.len().to_string(),
+    }
+}
+
+pub fn group_and_owner_mode(meta: &Metadata) -> GroupOwner {
+    let cache = UsersCache::new();
+    let uid = meta.uid();
+    let gid = meta.gid();
+
+    GroupOwner {
+        owner: cache
+            .get_user_by_uid(uid)
+            .map(|u| u.name().to_string_lossy().into_owned())
+            .unwrap_or_else(|| uid.to_string()),
+
+        group: cache
+            .get_group_by_gid(gid)
+            .map(|g| g.name().to_string_lossy().into_owned())
+            .unwrap_or_else(|| gid.to_string()),
+    }
+}
+
+pub fn human_readable_size(bytes: u64, units: Units) -> String {
+    let (base, prefixes) = match units {
+        Units::Binary => (1024.0, ["B", "K", "M", "G", "T", "P"]),
+        Units::Decimal => (1000.0, ["B", "k", "M", "G", "T", "P"]),
+    };
+    let mut size = bytes as f64;
+    let mut unit_index = 0;
+
+    while size >= base && unit_index < prefixes.len() - 1 {
+        size /= base;
+        unit_index += 1;
+    }
+
+    // Show 1 decimal place only if needed
+    if size >= 10.0 || unit_index == 0 {
+        format!("{:.0}{}", size, prefixes[unit_index])
+    } else {
+        format!("{:.1}{}", size, prefixes[unit_index])
+    }
+}
@@ -0,0 +1,124 @@
+//! Shared library dependencies of ELF files, resolved roughly the way the
+//! dynamic loader does (RPATH/RUNPATH, LD_LIBRARY_PATH, ld.so.conf and the
+//! default directories). Only direct dependencies are checked.
+
+use goblin::elf::Elf;
+use goblin::elf::header::ET_EXEC;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_DIRS: [&str; 4] = ["/lib", "/usr/lib", "/lib64", "/usr/lib64"];
+
+/// Directories listed in `/etc/ld.so.conf`, following its `include` lines.
+fn ld_so_conf(path: &Path, dirs: &mut Vec<PathBuf>) {
+    let Ok(text) = fs::read_to_string(path) else {
+        return;
+    };
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some(pattern) = line.strip_prefix("include") else {
+            dirs.push(PathBuf::from(line));
+            continue;
+        };
+
+        // Only `dir/*.suffix` patterns are used in practice
+        let pattern = Path::new(pattern.trim());
+        let suffix = pattern
+            .file_name()
+            .map(|name| name.to_string_lossy().trim_start_matches('*').to_string())
+            .unwrap_or_default();
+        let mut included: Vec<PathBuf> = pattern
+            .parent()
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.to_string_lossy().ends_with(&suffix))
+            .collect();
+        included.sort();
+        for file in included {
+            ld_so_conf(&file, dirs);
+        }
+    }
+}
+
+/// The directories searched for every file: LD_LIBRARY_PATH, then the
+/// configured and the default ones.
+fn system_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::env::var("LD_LIBRARY_PATH")
+        .map(|paths| std::env::split_paths(&paths).collect())
+        .unwrap_or_default();
+    ld_so_conf(Path::new("/etc/ld.so.conf"), &mut dirs);
+    dirs.extend(DEFAULT_DIRS.map(PathBuf::from));
+    dirs
+}
+
+fn is_elf(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|()| magic == *b"\x7fELF")
+}
+
+/// Annotation for one file, and whether it has missing dependencies.
+fn annotation(path: &Path, system: &[PathBuf]) -> (String, bool) {
+    if !path.is_file() || !is_elf(path) {
+        return (String::new(), false);
+    }
+    let Ok(bytes) = fs::read(path) else {
+        return (String::new(), false);
+    };
+    let Ok(elf) = Elf::parse(&bytes) else {
+        return ("invalid ELF".into(), true);
+    };
+    if elf.libraries.is_empty() {
+        let is_static = elf.interpreter.is_none() && elf.header.e_type == ET_EXEC;
+        let kind = if is_static { "static" } else { "no libs" };
+        return (kind.into(), false);
+    }
+
+    // Only whether a library is found matters, not where, so the search order
+    // is left aside. RPATH is ignored when there is a RUNPATH.
+    let origin = path.parent().unwrap_or(Path::new(".")).to_string_lossy();
+    let own = if elf.runpaths.is_empty() {
+        &elf.rpaths
+    } else {
+        &elf.runpaths
+    };
+    let mut dirs: Vec<PathBuf> = own
+        .iter()
+        .flat_map(|list| list.split(':'))
+        .map(|dir| {
+            let dir = dir
+                .replace("${ORIGIN}", &origin)
+                .replace("$ORIGIN", &origin);
+            PathBuf::from(dir)
+        })
+        .collect();
+    dirs.extend_from_slice(system);
+
+    let missing: Vec<&str> = elf
+        .libraries
+        .iter()
+        .copied()
+        .filter(|library| match library.contains('/') {
+            true => !Path::new(library).exists(),
+            false => !dirs.iter().any(|dir| dir.join(library).exists()),
+        })
+        .collect();
+    match missing.is_empty() {
+        true => (format!("{} ok", elf.libraries.len()), false),
+        false => (format!("missing {}", missing.join(", ")), true),
+    }
+}
+
+/// One annotation per path: the missing libraries of ELF files, or how many
+/// were found; nothing for other files.
+pub fn annotations(paths: &[PathBuf]) -> Vec<(String, bool)> {
+    let system = system_dirs();
+    paths.iter().map(|path| annotation(path, &system)).collect()
+}
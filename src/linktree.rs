@@ -0,0 +1,77 @@
+//! `ds link-tree SRC DST`: mirror the directories of a tree and link its files
+//! from the mirror, like `cp -as`, for staging directories.
+
+use ds::{DirLister, Entry};
+use std::fs;
+use std::io::ErrorKind;
+use std::os::unix::fs::symlink;
+use std::path::{Component, Path, PathBuf};
+
+/// The path leading from directory `from` to `to`, both absolute.
+fn relative(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut path: PathBuf = from[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .collect();
+    path.extend(&to[common..]);
+    path
+}
+
+/// Mirror `src` into `dst`, which is created if needed. Existing entries are
+/// never replaced. Returns how many directories and links were created.
+pub fn create(src: &Path, dst: &Path, relative_links: bool) -> Result<usize, String> {
+    let absolute = |path: &Path| {
+        path.canonicalize()
+            .map_err(|err| format!("{}: {err}", path.display()))
+    };
+    let src = absolute(src)?;
+    if !src.is_dir() {
+        return Err(format!("{} isn't a directory", src.display()));
+    }
+    fs::create_dir_all(dst).map_err(|err| format!("{}: {err}", dst.display()))?;
+    let dst = absolute(dst)?;
+
+    // Read before creating anything, so a mirror inside the source isn't walked
+    let lister = DirLister {
+        all: true,
+        ..DirLister::default()
+    };
+    let entries = lister.recursive(lister.list(&src), usize::MAX);
+
+    let mut created = 0;
+    for (_, Entry { path, meta }) in entries {
+        if path == dst || path.starts_with(&dst) {
+            continue;
+        }
+        let Ok(inside) = path.strip_prefix(&src) else {
+            continue;
+        };
+        let mirror = dst.join(inside);
+        // Symlinked directories are linked, not walked
+        let made = match meta.is_dir() {
+            true => fs::create_dir(&mirror),
+            false => {
+                let target = match relative_links {
+                    true => relative(mirror.parent().unwrap_or(&dst), &path),
+                    false => path.clone(),
+                };
+                symlink(&target, &mirror)
+            }
+        };
+        match made {
+            Ok(()) => created += 1,
+            // Left as it is, from an earlier run or otherwise, but what's
+            // below a directory has to go somewhere
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                if meta.is_dir() && !mirror.is_dir() {
+                    return Err(format!("{} isn't a directory", mirror.display()));
+                }
+            }
+            Err(err) => return Err(format!("{}: {err}", mirror.display())),
+        }
+    }
+    Ok(created)
+}
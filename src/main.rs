@@ -1,97 +1,107 @@
-use chrono::DateTime;
-use chrono::Utc;
-use clap::Parser;
+use clap::ArgGroup;
+use clap::Subcommand;
 use clap::ValueEnum;
-use owo_colors::OwoColorize;
-use std::fs::DirEntry;
-use std::fs::Metadata;
-use std::os::unix::fs::MetadataExt;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use serde_json::{Value, json};
+use std::cell::{OnceCell, RefCell};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::Write;
+use std::ops::Range;
 use std::os::unix::fs::PermissionsExt;
+use std::str::FromStr;
 use std::{
     fs,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
-use strum::Display;
+use strum::VariantNames;
+use tabled::grid::records::vec_records::Cell;
 use tabled::settings::object::Columns;
 use tabled::{
     Table, Tabled,
     settings::{Color, Style, object::Rows},
 };
-use users::{Groups, Users, UsersCache};
 
-#[derive(Debug, Display, Clone)]
-enum Types {
-    File,
-    Dir,
-}
-
-#[derive(Debug, Clone, ValueEnum)]
-enum SortField {
-    Name,
-    Size,
-    Extension,
-    Modified,
-    Changed,
-    Accessed,
-    Created,
-    Inode,
-    FileType,
-    None,
-}
-
-#[derive(Debug, Tabled, Clone)]
-struct Basic {
-    #[tabled(rename = "Name")]
-    name: String,
-    #[tabled(rename = "Type")]
-    types: Types,
-}
-
-#[derive(Debug, Tabled, Clone)]
-struct Size {
-    #[tabled(rename = "Size")]
-    size: String,
-}
-
-#[derive(Debug, Tabled, Clone)]
-struct MAC {
-    #[tabled(rename = "Date Modified")]
-    modified: String,
-    #[tabled(rename = "Date Accessed")]
-    accessed: String,
-    #[tabled(rename = "Date Created")]
-    created: String,
-}
-
-#[derive(Debug, Tabled, Clone)]
-struct Permission {
-    #[tabled(rename = "Permission")]
-    permission: String,
-}
+use crate::color::{ColorMode, ColorScheme, LsColors, Palette};
+use crate::columns::Field;
+use ds::{
+    Collate, Column, DirLister, Entry, Glob, Omitted, ReadErrors, SortField, Units, escape_control,
+    file_rows, human_readable_size, indicator, is_broken_link,
+};
 
-#[derive(Debug, Tabled, Clone)]
-struct Binary {
-    #[tabled(rename = "Binary")]
-    size: String,
-}
+mod activity;
+mod baseline;
+mod brief;
+mod budget;
+mod cache;
+mod chown;
+mod clipboard;
+mod color;
+mod columns;
+mod config;
+mod daemon;
+mod dedupe;
+mod detail;
+mod doctor;
+mod du;
+mod effective;
+mod format;
+mod git;
+mod grid;
+mod hooks;
+mod icons;
+mod init;
+mod json;
+mod libs;
+mod linktree;
+mod man;
+mod matches;
+mod mktree;
+mod mounts;
+mod perms;
+mod pkg;
+mod plugin;
+mod project;
+mod readonly;
+mod reflink;
+mod report;
+mod retention;
+mod serve;
+mod spark;
+mod stats;
+mod systemd;
+mod theme;
+mod usage;
+mod views;
 
 #[derive(Debug, Tabled, Clone)]
-struct GroupOwner {
-    #[tabled(rename = "Owner")]
-    owner: String,
-    #[tabled(rename = "Group")]
-    group: String,
+struct LinkTarget {
+    #[tabled(rename = "Target Directory")]
+    directory: String,
+    #[tabled(rename = "Links")]
+    links: usize,
 }
 
-#[derive(Debug, Parser)]
+#[derive(Debug, Clone, Parser)]
 #[command(
     version,
+    args_conflicts_with_subcommands = true,
+    group = ArgGroup::new("walk").args(["tree", "recursive"]),
     about,
     long_about = "List directory sizes with various display options.\n\n\
     A modern replacement for 'ls' with colorful output and additional features."
 )]
 struct Cli {
-    path: Option<PathBuf>,
+    #[arg(
+        value_name = "PATH",
+        help = "Directories or files to list [default: .]"
+    )]
+    path: Vec<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
 
     // Filtering options
     #[arg(short, long, help = "Show hidden files (starting with '.')", help_heading = Some("FILTERING OPTIONS"))]
@@ -103,7 +113,7 @@ struct Cli {
     #[arg(
         short = 'S',
         long,
-        value_enum,
+        value_parser = choice::<SortField>(),
         default_value = "name",
         help = "Sort by specific field",
         long_help = "Sort criteria:\n\
@@ -116,12 +126,69 @@ struct Cli {
         - created: Creation time\n\
         - inode: Inode number\n\
         - file-type: Directory first then files\n\
-        - none: No sorting",
+        - none: No sorting, entries keep the order the directory returns them in (readdir)\n\
+        - random: Shuffled order, reproducible with --seed\n\
+        - path: Full path, grouping a -R listing by directory\n\
+        - depth: How deep entries are in a -R listing, then their path",
         help_heading = Some("FILTERING OPTIONS")
     )]
     sort: SortField,
-    #[arg(short = 'i', long = "git-ignore", help = "ignore files mentioned in \'.gitignore\'", help_heading = Some("FILTERING OPTIONS"))]
+    #[arg(long, value_parser = choice::<Collate>(), default_value = "byte", help = "How names compare when sorting by name", help_heading = Some("FILTERING OPTIONS"))]
+    collate: Collate,
+    #[arg(long, help = "Seed for '--sort random' and '--sample'", help_heading = Some("FILTERING OPTIONS"))]
+    seed: Option<u64>,
+    #[arg(long, value_name = "N", conflicts_with = "at", help = "List a random sample of N entries, for huge directories", help_heading = Some("FILTERING OPTIONS"))]
+    sample: Option<usize>,
+    #[arg(short = 'F', long, help = "Append '/' to directories, '@' to symlinks, '*' to executables, '|' to FIFOs and '=' to sockets", help_heading = Some("DISPLAY OPTIONS"))]
+    classify: bool,
+    #[arg(long, help = "Show a Nerd Font icon before each name, by file type and extension", help_heading = Some("DISPLAY OPTIONS"))]
+    icons: bool,
+    #[arg(short = 'L', long, help = "Show the size, mode and times of what symlinks point to", help_heading = Some("FILTERING OPTIONS"))]
+    dereference: bool,
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        help = "Only list entries whose names match a shell pattern like '*.rs' (repeatable); -R and --tree still descend into every directory",
+        help_heading = Some("FILTERING OPTIONS")
+    )]
+    glob: Vec<glob::Pattern>,
+    #[arg(long, value_name = "PATTERN", help = "Like --glob, ignoring case (repeatable)", help_heading = Some("FILTERING OPTIONS"))]
+    iglob: Vec<glob::Pattern>,
+    #[arg(
+        long,
+        value_name = "EXT",
+        value_delimiter = ',',
+        help = "Only list entries with one of these extensions, like 'rs' or 'jpg,png'; -R and --tree still descend into every directory",
+        help_heading = Some("FILTERING OPTIONS")
+    )]
+    ext: Vec<String>,
+    #[arg(long, value_name = "EXT", value_delimiter = ',', help = "Like --ext, ignoring case, so 'jpg' matches '.JPG' too", help_heading = Some("FILTERING OPTIONS"))]
+    iext: Vec<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["at", "from_json", "tree", "sample"],
+        help = "List entries found at several paths, or hard links to one file, only once",
+        help_heading = Some("FILTERING OPTIONS")
+    )]
+    dedupe: bool,
+    #[arg(
+        long = "save-view",
+        help = "Remember the sort, --reverse and --columns given for this directory and use them whenever it's listed without any",
+        help_heading = Some("FILTERING OPTIONS")
+    )]
+    save_view: bool,
+    #[arg(long = "forget-view", conflicts_with = "save_view", help = "Forget the view saved for this directory", help_heading = Some("FILTERING OPTIONS"))]
+    forget_view: bool,
+    #[arg(
+        short = 'i',
+        long = "git-ignore",
+        overrides_with = "no_ignore",
+        help = "Leave out files ignored by '.gitignore', '.ignore', '.git/info/exclude' and '~/.config/git/ignore'",
+        help_heading = Some("FILTERING OPTIONS")
+    )]
     git_ignore: bool,
+    #[arg(long = "no-ignore", overrides_with = "git_ignore", help = "List ignored files too, undoing --git-ignore from the settings", help_heading = Some("FILTERING OPTIONS"))]
+    no_ignore: bool,
 
     // Display options
     #[arg(short, long, help = "Show file permissions in Unix format", help_heading = Some("DISPLAY OPTIONS"))]
@@ -130,751 +197,2213 @@ struct Cli {
     size: bool,
     #[arg(short, long, help = "list file sizes with binary prefixes", help_heading = Some("DISPLAY OPTIONS"))]
     binary: bool,
+    #[arg(
+        long = "total-size",
+        conflicts_with_all = ["at", "from_json"],
+        help = "Size directories by all the files below them, like du (implies --size)",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    total_size: bool,
+    #[arg(
+        long,
+        overrides_with = "no_cache",
+        help = "Keep directory totals, hashes and match counts between runs, by inode and modification time",
+        long_help = "Keep directory totals, hashes and match counts between runs, by inode and modification time.\n\
+        A directory's modification time doesn't change when files further down grow, so its total can be out of date; `ds cache clear` forgets them",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    cache: bool,
+    #[arg(long = "no-cache", overrides_with = "cache", help = "Work everything out afresh, undoing --cache from the settings", help_heading = Some("DISPLAY OPTIONS"))]
+    no_cache: bool,
+    #[arg(
+        long,
+        value_parser = choice::<Units>(),
+        default_value = "binary",
+        help = "Unit system for human readable sizes",
+        long_help = "Unit system for human readable sizes:\n\
+        - binary: powers of 1024 (K, M, G)\n\
+        - decimal: powers of 1000 (k, M, G)\n\
+        The Binary column always shows the exact byte count.",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    units: Units,
     #[arg(short = 'g', long = "group_and_owner", help = "list each file's group and owner", help_heading = Some("DISPLAY OPTIONS"))]
     group_and_owner: bool,
+    #[arg(short, long, help = "Long listing, same as '-p -s -g -t'", help_heading = Some("DISPLAY OPTIONS"))]
+    long: bool,
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with_all = ["permission", "size", "binary", "group_and_owner", "mac", "long"],
+        help = "Columns to show, in order (e.g. name,size,owner,modified,perm)",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    columns: Option<Vec<Field>>,
     #[arg(short = 't', long = "mac", help = "Show last MAC (modification/accessed/created) timestamp time", help_heading = Some("DISPLAY OPTIONS"))]
     mac: bool,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Add a column provided by the 'ds-column-NAME' executable (repeatable)",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    plugin: Vec<String>,
+    #[arg(long = "link-target", help = "Show where symlinks point, as 'name -> target'", help_heading = Some("DISPLAY OPTIONS"))]
+    link_target: bool,
+    #[arg(long = "link-targets", help = "Summarize symlinks by the directory they point into", help_heading = Some("DISPLAY OPTIONS"))]
+    link_targets: bool,
+    #[arg(long, help = "Annotate Rust crate directories with their name and version", help_heading = Some("DISPLAY OPTIONS"))]
+    cargo: bool,
+    #[arg(long, help = "Annotate Node/Python/Rust project directories and mark dependency folders", help_heading = Some("DISPLAY OPTIONS"))]
+    project: bool,
+    #[arg(long, conflicts_with = "at", help = "Show subdirectory usage against the limits in '.ds-budget.toml'", help_heading = Some("DISPLAY OPTIONS"))]
+    budget: bool,
+    #[arg(
+        long = "match-count",
+        value_name = "REGEX",
+        conflicts_with = "at",
+        help = "Show how many lines of each file match a regex",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    match_count: Option<regex::bytes::Regex>,
+    #[arg(long = "age-spark", conflicts_with = "at", help = "Show a sparkline of how old the files below each directory are", help_heading = Some("DISPLAY OPTIONS"))]
+    age_spark: bool,
+    #[arg(long = "reflink-info", help = "Show files sharing extents (reflinks/CoW clones)", help_heading = Some("DISPLAY OPTIONS"))]
+    reflink_info: bool,
+    #[arg(long, help = "Show the mode and group new files and directories would get in each directory", help_heading = Some("DISPLAY OPTIONS"))]
+    effective: bool,
+    #[arg(long, help = "Show the system package (dpkg, rpm or pacman) owning each file", help_heading = Some("DISPLAY OPTIONS"))]
+    pkg: bool,
+    #[arg(long, help = "Show what is mounted on entries that are mount points, like bind mounts and volumes", help_heading = Some("DISPLAY OPTIONS"))]
+    mounts: bool,
+    #[arg(long = "unit-hints", help = "Show whether systemd unit files are enabled and active", help_heading = Some("DISPLAY OPTIONS"))]
+    unit_hints: bool,
+    #[arg(long, help = "Check the shared libraries ELF files need, flagging missing ones", help_heading = Some("DISPLAY OPTIONS"))]
+    libs: bool,
+    #[arg(long, help = "Mark unreadable (🔒), symlinked (🔗), executable (⭐) and empty (🕳) entries", help_heading = Some("DISPLAY OPTIONS"))]
+    badges: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "default",
+        global = true,
+        help = "Color palette, including ones safe for color blindness",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    palette: Palette,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHEN",
+        default_value = "auto",
+        global = true,
+        help = "When to color the output; 'auto' colors terminals and honors NO_COLOR",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    color: ColorMode,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["at", "sample", "git_log", "churn"],
+        help = "Show subdirectories recursively as a tree",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    tree: bool,
+    #[arg(
+        short = 'R',
+        long,
+        conflicts_with_all = ["at", "sample", "git_log", "churn", "tree"],
+        help = "List subdirectories recursively, with paths relative to PATH",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    recursive: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "walk",
+        help = "Descend at most N levels with --tree or --recursive (1 lists PATH only)",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    depth: Option<usize>,
+    #[arg(
+        long = "tree-limit",
+        value_name = "N",
+        requires = "tree",
+        help = "Show the first N entries of each directory in --tree, and how many more there are",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    tree_limit: Option<usize>,
+    #[arg(
+        long = "max-width",
+        value_name = "N",
+        help = "Cut names, with their tree branches, to N columns",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    max_width: Option<usize>,
+
+    #[arg(
+        short = '1',
+        long,
+        conflicts_with_all = ["tree", "a11y", "long", "columns", "output", "format", "grid"],
+        help = "Print only the names, one per line, for pipes and shell loops",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    oneline: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["tree", "a11y", "long", "columns", "output", "format"],
+        help = "Pack only the names into as many columns as fit the terminal",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    grid: bool,
+    #[arg(
+        long,
+        conflicts_with = "tree",
+        help = "Describe each entry in a plain sentence, for screen readers",
+        help_heading = Some("DISPLAY OPTIONS")
+    )]
+    a11y: bool,
+
+    // Git options
+    #[arg(
+        long,
+        value_name = "REV",
+        conflicts_with_all = ["plugin", "link_targets", "reflink_info", "cargo", "project", "effective", "badges", "libs", "pkg", "unit_hints", "mounts", "link_target"],
+        help = "List the directory as recorded in a git revision",
+        help_heading = Some("GIT OPTIONS")
+    )]
+    at: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "at",
+        help = "Show the git status of each entry: staged then unstaged changes, like 'eza --git'; Git LFS pointers are listed at the real size",
+        help_heading = Some("GIT OPTIONS")
+    )]
+    git: bool,
+    #[arg(
+        long = "git-header",
+        conflicts_with_all = ["at", "from_json"],
+        help = "Show the branch, commits ahead/behind its upstream and uncommitted changes above the table",
+        help_heading = Some("GIT OPTIONS")
+    )]
+    git_header: bool,
+    #[arg(
+        long = "git-log",
+        conflicts_with = "at",
+        help = "Show the last commit subject, author and date of each entry",
+        help_heading = Some("GIT OPTIONS")
+    )]
+    git_log: bool,
+    #[arg(
+        long,
+        value_name = "MONTHS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "6",
+        conflicts_with = "at",
+        help = "Show how many commits touched each entry in the last MONTHS months [default: 6]",
+        help_heading = Some("GIT OPTIONS")
+    )]
+    churn: Option<u32>,
+
+    // Output options
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with = "output",
+        help = "Print one line per entry from a template like '{name}\\t{size}\\t{perm}'",
+        long_help = "Print one line per entry from a template instead of a table.\n\
+        Placeholders are the columns of --columns, like {name}, {size}, {perm}, {changed},\n\
+        {inode}, {links}, {uid}, {gid}, {mode} (octal) or {target} (of symlinks).\n\
+        '\\t', '\\n' and '\\\\' are unescaped, '{{' and '}}' print literal braces.",
+        help_heading = Some("OUTPUT OPTIONS")
+    )]
+    format: Option<String>,
+    #[arg(long, value_enum, default_value = "table", help = "Output format", help_heading = Some("OUTPUT OPTIONS"))]
+    output: Output,
+    #[arg(
+        long,
+        value_name = "KEYS",
+        value_delimiter = ',',
+        value_parser = PossibleValuesParser::new(json::FIELDS),
+        help = "Only these keys of each entry in '--output json' (e.g. name,size,mtime)",
+        help_heading = Some("OUTPUT OPTIONS")
+    )]
+    fields: Option<Vec<String>>,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "listing",
+        help = "Copy the plain listing, or with '--copy=paths' the listed paths, to the system clipboard",
+        long_help = "Copy the plain listing, or with '--copy=paths' the listed paths, to the system clipboard.\n\
+            Where it's out of reach, like over SSH, set DS_COPY_COMMAND to a shell command that \
+            gets the text on stdin instead, like 'tmux load-buffer -'.",
+        help_heading = Some("OUTPUT OPTIONS")
+    )]
+    copy: Option<CopyMode>,
+    #[arg(
+        long = "from-json",
+        value_name = "FILE",
+        conflicts_with_all = ["path", "at", "git", "git_header", "match_count", "age_spark", "tree", "recursive", "sample", "budget", "git_log", "churn", "plugin", "link_targets", "reflink_info", "cargo", "project", "effective", "badges", "libs", "pkg", "unit_hints", "mounts", "link_target"],
+        help = "Re-render a listing saved with --output json instead of reading the directory",
+        help_heading = Some("OUTPUT OPTIONS")
+    )]
+    from_json: Option<PathBuf>,
+    #[arg(long, help = "Print the entries and size of every listed path added up", help_heading = Some("OUTPUT OPTIONS"))]
+    summary: bool,
+    #[arg(long = "sudo-hint", help = "Print the sudo command that lists what couldn't be read for lack of permission", help_heading = Some("OUTPUT OPTIONS"))]
+    sudo_hint: bool,
+    #[arg(long = "stats-json", help = "Print counts, bytes, timings and read errors of the listing as JSON on stderr", help_heading = Some("OUTPUT OPTIONS"))]
+    stats_json: bool,
+    #[arg(long = "generate-man", hide = true, help = "Print a man page in roff")]
+    generate_man: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Output {
+    Table,
+    /// Every collected field as a JSON array, for jq and scripts
+    Json,
+    /// The selected columns as comma-separated values, then every time as
+    /// epoch seconds and ISO 8601
+    Csv,
+    /// The selected columns as tab-separated values, then every time as
+    /// epoch seconds and ISO 8601
+    Tsv,
+    /// A short summary with the largest, latest and broken entries, for chat
+    /// and alerts
+    Brief,
+}
+
+/// What `--copy` puts on the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum CopyMode {
+    /// Whatever is printed, without colors
+    Listing,
+    /// The path of each listed entry, one per line
+    Paths,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum Commands {
+    /// Write a self-contained HTML report of a directory
+    Report {
+        path: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            default_value = "ds-report.html",
+            help = "File to write the report to"
+        )]
+        output: PathBuf,
+        #[arg(short, long, help = "Include hidden files (starting with '.')")]
+        all: bool,
+        #[arg(
+            long,
+            value_parser = choice::<Units>(),
+            default_value = "binary",
+            help = "Unit system for sizes and totals"
+        )]
+        units: Units,
+    },
+    /// Show which entries `chown <SPEC>` would change, without changing them
+    #[command(name = "chown-dry")]
+    ChownDry {
+        #[arg(help = "Owner and/or group as given to chown (OWNER, OWNER:GROUP, :GROUP)")]
+        spec: String,
+        path: Option<PathBuf>,
+        #[arg(short, long, help = "Include hidden files (starting with '.')")]
+        all: bool,
+    },
+    /// Audit file and directory modes
+    Perms {
+        #[command(subcommand)]
+        action: PermsAction,
+    },
+    /// Record modes, owners and content hashes of a tree, and check it later
+    Baseline {
+        #[command(subcommand)]
+        action: BaselineAction,
+    },
+    /// List entries of a tree anyone besides their owner can write to, through
+    /// group or other write bits or ACLs; exits with 1 if any can
+    #[command(name = "verify-readonly")]
+    VerifyReadonly {
+        path: Option<PathBuf>,
+        #[arg(short, long, help = "Include hidden files (starting with '.')")]
+        all: bool,
+    },
+    /// Create a directory tree (files, sizes, modes, mtimes, symlinks) from a
+    /// YAML spec
+    Mktree {
+        spec: PathBuf,
+        #[arg(help = "Directory to create the tree in [default: .]")]
+        dir: Option<PathBuf>,
+    },
+    /// Rank owners, groups or extensions by the bytes of their files in a tree
+    Usage {
+        path: Option<PathBuf>,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "owner",
+            help = "What to add up sizes by"
+        )]
+        by: usage::By,
+        #[arg(short, long, help = "Include hidden files (starting with '.')")]
+        all: bool,
+        #[arg(
+            long,
+            value_parser = choice::<Units>(),
+            default_value = "binary",
+            help = "Unit system for sizes"
+        )]
+        units: Units,
+    },
+    /// List the files of a tree modified recently, grouped by day
+    Activity {
+        path: Option<PathBuf>,
+        #[arg(
+            long,
+            default_value = "7d",
+            help = "Start of the window: a span back from now (30m, 12h, 7d, 2w) or a date (2024-05-01)"
+        )]
+        since: String,
+        #[arg(short, long, help = "Include hidden files (starting with '.')")]
+        all: bool,
+        #[arg(
+            long,
+            value_parser = choice::<Units>(),
+            default_value = "binary",
+            help = "Unit system for sizes"
+        )]
+        units: Units,
+    },
+    /// Preview which files a retention policy would delete, and delete them
+    /// with --apply
+    Retention {
+        path: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Keep files modified within a span back from now (30m, 12h, 30d, 2w) or since a date (2024-05-01)"
+        )]
+        keep: String,
+        #[arg(
+            long = "keep-latest",
+            value_name = "K",
+            default_value_t = 0,
+            help = "Keep the K newest files of each pattern however old they are"
+        )]
+        keep_latest: usize,
+        #[arg(
+            long,
+            value_name = "PATTERN",
+            help = "Only consider files whose names match, like '*.log' (repeatable)"
+        )]
+        pattern: Vec<glob::Pattern>,
+        #[arg(short, long, help = "Include hidden files (starting with '.')")]
+        all: bool,
+        #[arg(long, help = "Delete the files after asking for confirmation")]
+        apply: bool,
+        #[arg(
+            long,
+            value_parser = choice::<Units>(),
+            default_value = "binary",
+            help = "Unit system for sizes"
+        )]
+        units: Units,
+    },
+    /// Mirror the directories of SRC in DST and link its files from there, like
+    /// `cp -as`
+    #[command(name = "link-tree")]
+    LinkTree {
+        src: PathBuf,
+        dst: PathBuf,
+        #[arg(long, help = "Make links relative to where they are, not absolute")]
+        relative: bool,
+    },
+    /// Print a completion script for a shell, to be installed by packagers or
+    /// sourced from the shell's startup file
+    Completions { shell: clap_complete::Shell },
+    /// Check the terminal, locale and helper programs ds relies on
+    Doctor,
+    /// Manage what --cache keeps between runs
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Ask about icons, colors and columns, and write a commented config.toml
+    Init {
+        #[arg(long, help = "Replace an existing config.toml")]
+        force: bool,
+    },
+    /// Try out the colors of the palette and the [theme] in config.toml
+    Theme {
+        #[command(subcommand)]
+        action: ThemeAction,
+    },
+    /// Serve a read-only HTML/JSON view of directories over HTTP
+    Serve {
+        #[arg(
+            help = "Directories to serve, each under its name when there are several [default: .]"
+        )]
+        paths: Vec<PathBuf>,
+        #[arg(long, default_value = "127.0.0.1:8080", help = "Address to listen on")]
+        listen: String,
+        #[arg(short, long, help = "Include hidden files (starting with '.')")]
+        all: bool,
+    },
+    /// Keep an index of directories, looking again every few seconds at those
+    /// that changed, for `ds query` to list and search instantly
+    Daemon {
+        #[arg(help = "Directories to index [default: the 'dirs' of [daemon] in config.toml]")]
+        paths: Vec<PathBuf>,
+        #[arg(long, default_value = "127.0.0.1:7878", help = "Address to listen on")]
+        listen: String,
+        #[arg(
+            long,
+            default_value_t = 10,
+            help = "Seconds between looks at the directories"
+        )]
+        interval: u64,
+        #[arg(short, long, help = "Include hidden files (starting with '.')")]
+        all: bool,
+    },
+    /// List a directory, or search names, in the index of `ds daemon`
+    Query {
+        #[arg(help = "Directory to list [default: .]")]
+        path: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "GLOB",
+            conflicts_with = "path",
+            help = "Print the indexed paths whose names match GLOB instead"
+        )]
+        search: Option<String>,
+        #[arg(
+            long,
+            default_value = "127.0.0.1:7878",
+            help = "Address the daemon listens on"
+        )]
+        connect: String,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum BaselineAction {
+    /// Record every entry under a directory
+    Create {
+        path: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            default_value = "ds-baseline.json",
+            help = "File to write the baseline to"
+        )]
+        file: PathBuf,
+        #[arg(short, long, help = "Include hidden files (starting with '.')")]
+        all: bool,
+        #[arg(
+            long,
+            help = "Reuse the hashes of files whose inode and modification time haven't changed since an earlier --cache run"
+        )]
+        cache: bool,
+    },
+    /// List entries that changed since the baseline; exits with 1 if any did
+    Verify {
+        path: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            default_value = "ds-baseline.json",
+            help = "Baseline to check against"
+        )]
+        file: PathBuf,
+        #[arg(
+            long = "verify-mtime",
+            help = "Also list files whose content changed but not their modification time, or the other way round"
+        )]
+        verify_mtime: bool,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum PermsAction {
+    /// List entries in a tree whose mode differs from the expected one
+    Check {
+        path: Option<PathBuf>,
+        #[arg(
+            long,
+            default_value = "644/755",
+            help = "Expected octal modes for files and directories (FILE/DIR)"
+        )]
+        expect: String,
+        #[arg(short, long, help = "Include hidden files (starting with '.')")]
+        all: bool,
+        #[arg(
+            long = "git-ignore",
+            help = "Leave out files ignored by '.gitignore', '.ignore', '.git/info/exclude' and '~/.config/git/ignore'"
+        )]
+        git_ignore: bool,
+        #[arg(long, help = "Set the expected modes after asking for confirmation")]
+        fix: bool,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum CacheAction {
+    /// Forget every cached directory total, hash and match count
+    Clear,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum ThemeAction {
+    /// Show a listing with every kind of file in the current colors
+    Preview,
+}
+
+/// Parses a value of a library enum, listing its values in help and errors
+/// like a `ValueEnum` would.
+fn choice<T>() -> impl TypedValueParser<Value = T>
+where
+    T: FromStr + VariantNames + Clone + Send + Sync + 'static,
+{
+    PossibleValuesParser::new(T::VARIANTS).map(|name| {
+        name.parse()
+            .unwrap_or_else(|_| unreachable!("{name} is one of the variants"))
+    })
 }
 
 fn main() {
-    let cli: Cli = Cli::parse();
-    let path: PathBuf = cli.path.unwrap_or(PathBuf::from("."));
-
-    println!("Path: {}", path.display());
-    if let Ok(is_exist) = fs::exists(&path) {
-        if is_exist {
-            let files = get_files(
-                &path,
-                cli.all,
-                cli.reverse,
-                cli.dirs,
-                cli.sort,
-                cli.git_ignore,
+    if clipboard::serve() {
+        return;
+    }
+    run();
+    if ds::interrupted() {
+        // After the partial listing, and out of the way of piped output
+        eprintln!("(interrupted)");
+        std::process::exit(130);
+    }
+}
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+    if ds::interrupted() {
+        // A second Ctrl-C doesn't wait for the listing
+        // SAFETY: _exit is async-signal-safe.
+        unsafe { libc::_exit(130) };
+    }
+    ds::interrupt();
+}
+
+/// While kept, Ctrl-C ends the scan early instead of the process, so what was
+/// read so far is still shown, and subcommands don't act on a scan cut short.
+/// Anywhere else, like at a prompt or while serving, Ctrl-C exits at once.
+struct CatchInterrupts;
+
+fn catch_interrupts() -> CatchInterrupts {
+    let handler: extern "C" fn(libc::c_int) = on_interrupt;
+    // SAFETY: the handler only touches an atomic flag or exits.
+    unsafe { libc::signal(libc::SIGINT, handler as libc::sighandler_t) };
+    CatchInterrupts
+}
+
+impl Drop for CatchInterrupts {
+    fn drop(&mut self) {
+        // SAFETY: restores the default action.
+        unsafe { libc::signal(libc::SIGINT, libc::SIG_DFL) };
+    }
+}
+
+fn run() {
+    // Defaults from config.toml, then DS_OPTS, go before the command line,
+    // which overrides them, or after a subcommand, which takes no options
+    // before it
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let command_line = Cli::command().get_matches_from(&args);
+    let settings = config::load();
+    let defaults = settings.clone().and_then(|settings| {
+        let command = Cli::command();
+        let layers = vec![
+            config::from_config(&settings, &command)?,
+            config::from_env(&command)?,
+        ];
+        Ok(config::default_args(layers, &command, &command_line))
+    });
+    let matches = match &defaults {
+        Ok(defaults) if !defaults.is_empty() => {
+            let mut all = args.clone();
+            let at = match command_line.subcommand() {
+                Some(_) => all.len(),
+                None => 1,
+            };
+            all.splice(at..at, defaults.iter().cloned());
+            Cli::command().get_matches_from(all)
+        }
+        _ => command_line.clone(),
+    };
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    if cli.generate_man {
+        if let Err(err) = man::render(Cli::command()) {
+            eprintln!("error:\nCan't write the man page ({err}).");
+        }
+        return;
+    }
+    let given = |id: &str| command_line.value_source(id) == Some(ValueSource::CommandLine);
+    if cli.long {
+        cli.permission = true;
+        cli.size = true;
+        cli.group_and_owner = true;
+        cli.mac = true;
+    }
+    if cli.total_size {
+        cli.size = true;
+    }
+    let colored = color::enabled(cli.color);
+    let (mut colors, mut ls_colors) = match colored {
+        true => (
+            // Asked of the terminal only for the palette ds picks itself, as
+            // terminals over slow links take a while to answer
+            ColorScheme::new(
+                cli.palette,
+                color::background(
+                    matches.value_source("palette") == Some(ValueSource::DefaultValue),
+                ),
+            ),
+            LsColors::from_env(),
+        ),
+        false => (ColorScheme::none(), None),
+    };
+    if let Err(err) = &defaults {
+        println!(
+            "{}",
+            colors
+                .alert
+                .colorize(format!("error:\nCan't read the settings ({err})."))
+        );
+    }
+    if colored && let Err(err) = theme::apply(&mut colors, &mut ls_colors) {
+        println!(
+            "{}",
+            colors
+                .alert
+                .colorize(format!("error:\nCan't load the theme ({err})."))
+        );
+    }
+    if colored {
+        // Palettes and themes may use colors the terminal can't show
+        let depth = color::depth();
+        colors.downgrade(depth);
+        if let Some(ls_colors) = &mut ls_colors {
+            ls_colors.downgrade(depth);
+        }
+    }
+
+    match cli.command {
+        Some(Commands::Report {
+            path,
+            output,
+            all,
+            units,
+        }) => {
+            let _interrupts = catch_interrupts();
+            run_report(
+                &path.unwrap_or(PathBuf::from(".")),
+                &output,
+                all,
+                units,
+                &colors,
             );
+            return;
+        }
+        Some(Commands::Serve {
+            mut paths,
+            listen,
+            all,
+        }) => {
+            if paths.is_empty() {
+                paths.push(PathBuf::from("."));
+            }
+            if let Err(err) = serve::serve(&paths, &listen, all) {
+                println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't serve directory ({err})."))
+                );
+            }
+            return;
+        }
+        Some(Commands::Daemon {
+            paths,
+            listen,
+            interval,
+            all,
+        }) => {
+            let run = daemon::dirs(paths, &settings.unwrap_or_default())
+                .and_then(|dirs| daemon::run(&dirs, &listen, interval, all));
+            if let Err(err) = run {
+                println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't index directories ({err})."))
+                );
+            }
+            return;
+        }
+        Some(Commands::Query {
+            path,
+            search,
+            connect,
+        }) => {
+            let path = path.unwrap_or(PathBuf::from("."));
+            if let Err(err) = daemon::query(&connect, &path, search.as_deref()) {
+                println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't query the index ({err})."))
+                );
+            }
+            return;
+        }
+        Some(Commands::Baseline {
+            action:
+                BaselineAction::Create {
+                    path,
+                    file,
+                    all,
+                    cache,
+                },
+        }) => {
+            let _interrupts = catch_interrupts();
+            let cache = cache.then(cache::Cache::load);
+            match baseline::create(
+                &path.unwrap_or(PathBuf::from(".")),
+                &file,
+                all,
+                cache.as_ref(),
+            ) {
+                Ok(count) => {
+                    println!("Recorded {count} entries in {}.", file.display());
+                    if let Some(cache) = cache {
+                        cache.save();
+                    }
+                }
+                Err(err) => println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't create baseline ({err})."))
+                ),
+            }
+            return;
+        }
+        Some(Commands::Baseline {
+            action:
+                BaselineAction::Verify {
+                    path,
+                    file,
+                    verify_mtime,
+                },
+        }) => {
+            let _interrupts = catch_interrupts();
+            match baseline::verify(
+                &path.unwrap_or(PathBuf::from(".")),
+                &file,
+                verify_mtime,
+                &colors,
+            ) {
+                Ok(None) => println!("Nothing changed since the baseline."),
+                Ok(Some(table)) => {
+                    println!("{table}");
+                    // Lets cron jobs and scripts notice changes
+                    std::process::exit(1);
+                }
+                Err(err) => println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't verify baseline ({err})."))
+                ),
+            }
+            return;
+        }
+        Some(Commands::VerifyReadonly { path, all }) => {
+            let path = path.unwrap_or(PathBuf::from("."));
+            if !path.is_dir() {
+                println!(
+                    "{}",
+                    colors.alert.colorize(format!(
+                        "error:\nCan't verify '{}' (not a directory).",
+                        path.display()
+                    ))
+                );
+                return;
+            }
+            let _interrupts = catch_interrupts();
+            match readonly::verify(&path, all, &colors) {
+                None => println!("Nothing is writable by anyone but its owner."),
+                Some(table) => {
+                    println!("{table}");
+                    // Lets backup jobs and scripts notice
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Commands::Mktree { spec, dir }) => {
+            match mktree::create(&spec, &dir.unwrap_or(PathBuf::from("."))) {
+                Ok(count) => println!("Created {count} entries."),
+                Err(err) => println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't create tree ({err})."))
+                ),
+            }
+            return;
+        }
+        Some(Commands::Usage {
+            path,
+            by,
+            all,
+            units,
+        }) => {
+            let path = path.unwrap_or(PathBuf::from("."));
+            if !fs::exists(&path).unwrap_or(false) {
+                println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize("error:\nPath doesn't exist. (try other location)")
+                );
+                return;
+            }
+            let _interrupts = catch_interrupts();
+            println!("{}", usage::render(&path, by, all, units, &colors));
+            return;
+        }
+        Some(Commands::Activity {
+            path,
+            since,
+            all,
+            units,
+        }) => {
+            let path = path.unwrap_or(PathBuf::from("."));
+            let since = match activity::parse_since(&since) {
+                Ok(since) => since,
+                Err(err) => {
+                    println!(
+                        "{}",
+                        colors
+                            .alert
+                            .colorize(format!("error:\nCan't list activity ({err})."))
+                    );
+                    return;
+                }
+            };
+            let _interrupts = catch_interrupts();
+            match activity::render(&path, since, all, units, &colors) {
+                Some(table) => println!("{table}"),
+                None => println!("Nothing modified since {}.", since.format("%Y-%m-%d %H:%M")),
+            }
+            return;
+        }
+        Some(Commands::Retention {
+            path,
+            keep,
+            keep_latest,
+            pattern,
+            all,
+            apply,
+            units,
+        }) => {
+            let result = activity::parse_since(&keep).and_then(|cutoff| {
+                let policy = retention::Policy {
+                    cutoff,
+                    keep_latest,
+                    patterns: pattern,
+                };
+                retention::run(
+                    &path.unwrap_or(PathBuf::from(".")),
+                    &policy,
+                    all,
+                    apply,
+                    units,
+                    &colors,
+                )
+            });
+            if let Err(err) = result {
+                println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't apply the retention policy ({err})."))
+                );
+            }
+            return;
+        }
+        Some(Commands::LinkTree { src, dst, relative }) => {
+            match linktree::create(&src, &dst, relative) {
+                Ok(count) => println!("Created {count} entries."),
+                Err(err) => println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't link tree ({err})."))
+                ),
+            }
+            return;
+        }
+        Some(Commands::Completions { shell }) => {
+            clap_complete::generate(shell, &mut Cli::command(), "ds", &mut std::io::stdout());
+            return;
+        }
+        Some(Commands::Doctor) => {
+            println!("{}", doctor::render(cli.color, &colors));
+            println!("\n{}", doctor::GLYPHS);
+            return;
+        }
+        Some(Commands::Theme {
+            action: ThemeAction::Preview,
+        }) => {
+            println!("{}", theme::preview(&colors, &ls_colors));
+            return;
+        }
+        Some(Commands::Cache {
+            action: CacheAction::Clear,
+        }) => {
+            match cache::clear() {
+                Ok(Some(file)) => println!("Removed {}.", file.display()),
+                Ok(None) => println!("Nothing is cached."),
+                Err(err) => println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't clear the cache ({err})."))
+                ),
+            }
+            return;
+        }
+        Some(Commands::Init { force }) => {
+            match init::run(force) {
+                Ok(file) => println!("Settings written to {}.", file.display()),
+                Err(err) => println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't write the settings ({err})."))
+                ),
+            }
+            return;
+        }
+        Some(Commands::ChownDry { spec, path, all }) => {
+            let _interrupts = catch_interrupts();
+            match chown::render(&path.unwrap_or(PathBuf::from(".")), &spec, all, &colors) {
+                Ok(Some(table)) => println!("{table}"),
+                Ok(None) => println!("Nothing would change."),
+                Err(err) => println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't check chown ({err})."))
+                ),
+            }
+            return;
+        }
+        Some(Commands::Perms {
+            action:
+                PermsAction::Check {
+                    path,
+                    expect,
+                    all,
+                    git_ignore,
+                    fix,
+                },
+        }) => {
+            if let Err(err) = perms::check(
+                &path.unwrap_or(PathBuf::from(".")),
+                &expect,
+                all,
+                git_ignore,
+                fix,
+                &colors,
+            ) {
+                println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't check modes ({err})."))
+                );
+            }
+            return;
+        }
+        None => {}
+    }
 
-            if cli.permission && cli.size && cli.mac && cli.binary && cli.group_and_owner {
-                // Show all fields
-                let combined: Vec<(Basic, Size, Binary, GroupOwner, MAC, Permission)> = files;
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(4), Color::FG_BLUE);
-                table.modify(Columns::one(5), Color::FG_BLUE);
-                table.modify(Columns::one(6), Color::FG_YELLOW);
-                table.modify(Columns::one(7), Color::FG_YELLOW);
-                table.modify(Columns::one(8), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.permission && cli.size && cli.mac && cli.binary {
-                // Show all fields
-                let combined: Vec<(Basic, Size, Binary, MAC, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, size, binary, _, modified, permission)| {
-                        (basic, size, binary, modified, permission)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(4), Color::FG_YELLOW);
-                table.modify(Columns::one(5), Color::FG_YELLOW);
-                table.modify(Columns::one(6), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.binary && cli.group_and_owner && cli.mac {
-                // Show all fields
-                let combined: Vec<(Basic, Size, Binary, GroupOwner, MAC)> = files
-                    .into_iter()
-                    .map(|(basic, size, binary, group_and_owner, modified, _)| {
-                        (basic, size, binary, group_and_owner, modified)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(4), Color::FG_BLUE);
-                table.modify(Columns::one(5), Color::FG_BLUE);
-                table.modify(Columns::one(6), Color::FG_YELLOW);
-                table.modify(Columns::one(7), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_YELLOW);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.binary && cli.group_and_owner && cli.permission {
-                // Show all fields
-                let combined: Vec<(Basic, Size, Binary, GroupOwner, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, size, binary, group_and_owner, _, permission)| {
-                        (basic, size, binary, group_and_owner, permission)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(4), Color::FG_BLUE);
-                table.modify(Columns::one(5), Color::FG_BLUE);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.group_and_owner && cli.mac && cli.permission {
-                // Show all fields
-                let combined: Vec<(Basic, Size, GroupOwner, MAC, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, size, _, group_and_owner, modified, permission)| {
-                        (basic, size, group_and_owner, modified, permission)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BLUE);
-                table.modify(Columns::one(4), Color::FG_BLUE);
-                table.modify(Columns::one(5), Color::FG_YELLOW);
-                table.modify(Columns::one(6), Color::FG_YELLOW);
-                table.modify(Columns::one(7), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.binary && cli.group_and_owner && cli.mac && cli.permission {
-                // Show all fields
-                let combined: Vec<(Basic, Binary, GroupOwner, MAC, Permission)> = files
-                    .into_iter()
-                    .map(
-                        |(basic, _, binary, group_and_owner, modified, permission)| {
-                            (basic, binary, group_and_owner, modified, permission)
-                        },
-                    )
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BLUE);
-                table.modify(Columns::one(4), Color::FG_BLUE);
-                table.modify(Columns::one(5), Color::FG_YELLOW);
-                table.modify(Columns::one(6), Color::FG_YELLOW);
-                table.modify(Columns::one(7), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.binary && cli.mac {
-                // Show size, binary and modifier
-                let combined: Vec<(Basic, Size, Binary, MAC)> = files
-                    .into_iter()
-                    .map(|(basic, size, binary, _, modified, _)| (basic, size, binary, modified))
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(4), Color::FG_YELLOW);
-                table.modify(Columns::one(5), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_YELLOW);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.binary && cli.group_and_owner {
-                // Show size, binary and modifier
-                let combined: Vec<(Basic, Size, Binary, GroupOwner)> = files
-                    .into_iter()
-                    .map(|(basic, size, binary, group_and_owner, _, _)| {
-                        (basic, size, binary, group_and_owner)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(4), Color::FG_BLUE);
-                table.modify(Columns::last(), Color::FG_BLUE);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.group_and_owner && cli.mac {
-                // Show size, binary and modifier
-                let combined: Vec<(Basic, Size, GroupOwner, MAC)> = files
-                    .into_iter()
-                    .map(|(basic, size, _, group_and_owner, modified, _)| {
-                        (basic, size, group_and_owner, modified)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BLUE);
-                table.modify(Columns::one(4), Color::FG_BLUE);
-                table.modify(Columns::one(5), Color::FG_YELLOW);
-                table.modify(Columns::one(6), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_YELLOW);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.group_and_owner && cli.permission {
-                // Show size, binary and modifier
-                let combined: Vec<(Basic, Size, GroupOwner, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, size, _, group_and_owner, _, permission)| {
-                        (basic, size, group_and_owner, permission)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BLUE);
-                table.modify(Columns::one(4), Color::FG_BLUE);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.binary && cli.group_and_owner && cli.mac {
-                // Show size, binary and modifier
-                let combined: Vec<(Basic, Binary, GroupOwner, MAC)> = files
-                    .into_iter()
-                    .map(|(basic, _, binary, group_and_owner, modified, _)| {
-                        (basic, binary, group_and_owner, modified)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BLUE);
-                table.modify(Columns::one(4), Color::FG_BLUE);
-                table.modify(Columns::one(5), Color::FG_YELLOW);
-                table.modify(Columns::one(6), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_YELLOW);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.binary && cli.group_and_owner && cli.permission {
-                // Show size, binary and modifier
-                let combined: Vec<(Basic, Binary, GroupOwner, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, _, binary, group_and_owner, _, permission)| {
-                        (basic, binary, group_and_owner, permission)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BLUE);
-                table.modify(Columns::one(4), Color::FG_BLUE);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.binary && cli.permission {
-                // Show size, binary and modifier
-                let combined: Vec<(Basic, Size, Binary, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, size, binary, _, _, permission)| {
-                        (basic, size, binary, permission)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.mac && cli.permission {
-                // Show size, binary and modifier
-                let combined: Vec<(Basic, Size, MAC, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, size, _, _, modified, permission)| {
-                        (basic, size, modified, permission)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_YELLOW);
-                table.modify(Columns::one(4), Color::FG_YELLOW);
-                table.modify(Columns::one(5), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.binary && cli.mac && cli.permission {
-                // Show size, binary and modifier
-                let combined: Vec<(Basic, Binary, MAC, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, _, binary, _, modified, permission)| {
-                        (basic, binary, modified, permission)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_YELLOW);
-                table.modify(Columns::one(4), Color::FG_YELLOW);
-                table.modify(Columns::one(5), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.binary {
-                // Show size and binary
-                let combined: Vec<(Basic, Size, Binary)> = files
-                    .into_iter()
-                    .map(|(basic, size, binary, _, _, _)| (basic, size, binary))
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::last(), Color::FG_BRIGHT_YELLOW);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.binary && cli.mac {
-                // Show size and binary
-                let combined: Vec<(Basic, Binary, MAC)> = files
-                    .into_iter()
-                    .map(|(basic, _, binary, _, modified, _)| (basic, binary, modified))
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_YELLOW);
-                table.modify(Columns::one(4), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_YELLOW);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.binary && cli.permission {
-                // Show binary and permission
-                let combined: Vec<(Basic, Binary, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, _, binary, _, _, permission)| (basic, binary, permission))
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.permission && cli.size {
-                // Show permission and size
-                let combined: Vec<(Basic, Size, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, size, _, _, _, permission)| (basic, size, permission))
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.permission && cli.mac {
-                // Show permission and modified time
-                let combined: Vec<(Basic, MAC, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, _, _, _, modified, permission)| (basic, modified, permission))
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_YELLOW);
-                table.modify(Columns::one(3), Color::FG_YELLOW);
-                table.modify(Columns::one(4), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.mac {
-                // Show size and modified time
-                let combined: Vec<(Basic, Size, MAC)> = files
-                    .into_iter()
-                    .map(|(basic, size, _, _, modified, _)| (basic, size, modified))
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_YELLOW);
-                table.modify(Columns::one(4), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_YELLOW);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size && cli.group_and_owner {
-                // show size and grop/owner
-                let combined: Vec<(Basic, Size, GroupOwner)> = files
-                    .into_iter()
-                    .map(|(basic, size, _, group_and_owner, _, _)| (basic, size, group_and_owner))
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BLUE);
-                table.modify(Columns::last(), Color::FG_BLUE);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.binary && cli.group_and_owner {
-                // show size and grop/owner
-                let combined: Vec<(Basic, Binary, GroupOwner)> = files
-                    .into_iter()
-                    .map(|(basic, _, binary, group_and_owner, _, _)| {
-                        (basic, binary, group_and_owner)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BRIGHT_YELLOW);
-                table.modify(Columns::one(3), Color::FG_BLUE);
-                table.modify(Columns::last(), Color::FG_BLUE);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.permission && cli.group_and_owner {
-                // show size and grop/owner
-                let combined: Vec<(Basic, GroupOwner, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, _, _, group_and_owner, _, permission)| {
-                        (basic, group_and_owner, permission)
-                    })
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BLUE);
-                table.modify(Columns::one(3), Color::FG_BLUE);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.permission {
-                // Show only permission
-                let combined: Vec<(Basic, Permission)> = files
-                    .into_iter()
-                    .map(|(basic, _, _, _, _, permission)| (basic, permission))
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::last(), Color::FG_BRIGHT_GREEN);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.size {
-                // Show only size
-                let combined: Vec<(Basic, Size)> = files
-                    .into_iter()
-                    .map(|(basic, size, _, _, _, _)| (basic, size))
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::last(), Color::FG_BRIGHT_YELLOW);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.mac {
-                // Show only modified time
-                let combined: Vec<(Basic, MAC)> = files
-                    .into_iter()
-                    .map(|(basic, _, _, _, modified, _)| (basic, modified))
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_YELLOW);
-                table.modify(Columns::one(3), Color::FG_YELLOW);
-                table.modify(Columns::last(), Color::FG_YELLOW);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.binary {
-                let combined: Vec<(Basic, Binary)> = files
-                    .into_iter()
-                    .map(|(basic, _, binary, _, _, _)| (basic, binary)) // Changed to access binary field
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::last(), Color::FG_BRIGHT_YELLOW);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else if cli.group_and_owner {
-                let combined: Vec<(Basic, GroupOwner)> = files
-                    .into_iter()
-                    .map(|(basic, _, _, group_and_owner, _, _)| (basic, group_and_owner)) // Changed to access binary field
-                    .collect();
-                let mut table = Table::new(combined);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Columns::one(2), Color::FG_BLUE);
-                table.modify(Columns::last(), Color::FG_BLUE);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
-            } else {
-                // Show basic info only
-                let basic_info: Vec<Basic> = files
-                    .into_iter()
-                    .map(|(basic, _, _, _, _, _)| basic)
-                    .collect();
-                let mut table = Table::new(basic_info);
-                table.with(Style::empty());
-                table.modify(Columns::one(1), Color::FG_MAGENTA);
-                table.modify(Rows::first(), Color::FG_BRIGHT_BLACK);
-                println!("{}", table);
+    let paths = match cli.path.is_empty() {
+        true => vec![PathBuf::from(".")],
+        false => cli.path.clone(),
+    };
+    if cli.save_view || cli.forget_view {
+        for path in &paths {
+            let saved = match cli.save_view {
+                true => views::save(
+                    path,
+                    &views::View {
+                        sort: cli.sort,
+                        reverse: cli.reverse,
+                        columns: cli.columns.clone(),
+                    },
+                )
+                .map(|_| format!("Saved the view of {}.", path.display())),
+                false => views::forget(path).map(|forgotten| match forgotten {
+                    true => format!("Forgot the view of {}.", path.display()),
+                    false => format!("No view saved for {}.", path.display()),
+                }),
+            };
+            match saved {
+                Ok(message) => println!("{message}"),
+                Err(err) => println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't save the view ({err})."))
+                ),
             }
-        } else {
+        }
+        return;
+    }
+    // A saved view only fills in what the command line leaves open, and
+    // overrides config.toml
+    let displayed = [
+        "columns",
+        "permission",
+        "size",
+        "binary",
+        "group_and_owner",
+        "mac",
+        "long",
+    ]
+    .into_iter()
+    .any(given);
+    let errors = ReadErrors::default();
+    let mut stats = stats::Stats::start(cli.stats_json, &errors);
+    let _interrupts = catch_interrupts();
+    let cache = cli.cache.then(cache::Cache::load);
+    let hooks = settings.as_ref().map_or_else(
+        // Already told
+        |_| Ok(hooks::Hooks::default()),
+        hooks::Hooks::load,
+    );
+    let hooks = hooks.unwrap_or_else(|err| {
+        println!(
+            "{}",
+            colors
+                .alert
+                .colorize(format!("error:\nCan't load the hooks ({err})."))
+        );
+        hooks::Hooks::default()
+    });
+
+    let template = match cli.format.as_deref().map(format::Template::parse) {
+        Some(Err(err)) => {
             println!(
                 "{}",
-                "error:\nPath doesn't exist. (try other location)".red()
+                colors
+                    .alert
+                    .colorize(format!("error:\nInvalid format ({err})."))
             );
+            return;
         }
-    } else {
-        println!("{}", "error:\nCan't read directory.".red());
+        Some(Ok(template)) => Some(template),
+        None => None,
+    };
+    let seed = cli.seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+    if banner(&cli) && (matches!(cli.sort, SortField::Random) || cli.sample.is_some()) {
+        println!("Seed: {seed}");
     }
-}
 
-fn get_files(
-    path: &Path,
-    show_hidden: bool,
-    reverse: bool,
-    directories_only: bool,
-    sort: SortField,
-    git_ignore: bool,
-) -> Vec<(Basic, Size, Binary, GroupOwner, MAC, Permission)> {
-    let mut entries: Vec<_> = fs::read_dir(path)
-        .ok()
-        .map(|dir| {
-            dir.filter_map(|entry| {
-                let entry = entry.ok()?;
-                let meta = entry.metadata().ok()?;
-                Some((entry, meta))
-            })
-            .filter(|(entry, meta)| {
-                let file_name = entry.file_name().into_string().unwrap_or_default();
-
-                if directories_only && meta.is_file() {
-                    return false;
-                }
-                if show_hidden && file_name.starts_with('.') {
-                    if git_ignore && file_name.eq(".gitignore") {
-                        return false;
-                    }
-                    return true;
-                } else if !show_hidden && file_name.starts_with('.') {
-                    return false;
-                }
-                return true;
-            })
-            .collect()
-        })
-        .unwrap_or_default();
-
-    // Sort entries based on the specified field
-    match sort {
-        SortField::Name => {
-            entries.sort_by(|a, b| a.0.file_name().cmp(&b.0.file_name()));
-        }
-        SortField::Size => {
-            entries.sort_by(|a, b| a.1.len().cmp(&b.1.len()));
-        }
-        SortField::Extension => {
-            entries.sort_by(|a, b| {
-                let name_a = a.0.file_name();
-                let name_b = b.0.file_name();
-
-                let ext_a = Path::new(&name_a)
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("");
-                let ext_b = Path::new(&name_b)
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("");
-
-                ext_a.cmp(ext_b)
-            });
-        }
-        SortField::Modified => {
-            entries.sort_by(|a, b| {
-                a.1.modified()
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-                    .cmp(&b.1.modified().unwrap_or(std::time::SystemTime::UNIX_EPOCH))
-            });
+    // Files named on the command line are listed together before the
+    // directories, like `ls` does
+    let (dirs, named): (Vec<PathBuf>, Vec<PathBuf>) =
+        match cli.at.is_none() && cli.from_json.is_none() {
+            true => paths.iter().cloned().partition(|path| {
+                // Broken links are named entries too
+                let meta = fs::metadata(path).or_else(|_| fs::symlink_metadata(path));
+                !meta.is_ok_and(|meta| !meta.is_dir())
+            }),
+            false => (paths.clone(), Vec::new()),
+        };
+    // A single file gets every detail rather than a table of one row
+    if let [file] = &named[..]
+        && paths.len() == 1
+        && banner(&cli)
+    {
+        let meta = match cli.dereference {
+            true => fs::metadata(file),
+            false => fs::symlink_metadata(file),
+        };
+        match meta {
+            Ok(meta) => {
+                let detail = detail::render(file, &meta, cli.units, &colors);
+                println!("{detail}");
+                let copied = match cli.copy {
+                    Some(CopyMode::Paths) => format!("{}\n", file.display()),
+                    _ => strip_ansi(&format!("{detail}\n")),
+                };
+                copy(cli.copy.map(|_| copied), &colors);
+            }
+            Err(err) => println!(
+                "{}",
+                colors
+                    .alert
+                    .colorize(format!("error:\nCan't read {} ({err}).", file.display()))
+            ),
         }
-        SortField::Changed => {
-            entries.sort_by(|a, b| {
-                a.1.accessed()
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-                    .cmp(&b.1.accessed().unwrap_or(std::time::SystemTime::UNIX_EPOCH))
+        return;
+    }
+    let labeled = paths.len() > 1;
+    // Several paths in a machine-readable format make one document
+    let combined = (labeled && machine(&cli)).then(|| RefCell::new(Combined::default()));
+    let copied = cli.copy.map(|_| RefCell::new(String::new()));
+    let seen = cli.dedupe.then(|| {
+        let lister = lister(&cli, seed, &errors);
+        let mut seen = dedupe::Seen::default();
+        let listings = std::iter::once(lister.named(&named))
+            .chain(dirs.iter().map(|dir| lister.list(dir)))
+            .map(|entries| match cli.recursive {
+                true => lister
+                    .recursive(entries, cli.depth.unwrap_or(usize::MAX))
+                    .into_iter()
+                    .map(|(_, entry)| entry)
+                    .collect(),
+                false => entries,
             });
+        for entries in listings {
+            seen.add(&entries);
         }
-        SortField::Accessed => {
-            entries.sort_by(|a, b| {
-                a.1.created()
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-                    .cmp(&b.1.created().unwrap_or(std::time::SystemTime::UNIX_EPOCH))
-            });
+        seen
+    });
+    if !named.is_empty() {
+        Listing {
+            cli: &cli,
+            template: template.as_ref(),
+            seed,
+            labeled,
+            colors: &colors,
+            ls_colors: ls_colors.as_ref(),
+            seen: seen.as_ref(),
+            errors: &errors,
+            combined: combined.as_ref(),
+            copied: copied.as_ref(),
+            cache: cache.as_ref(),
+            hooks: &hooks,
         }
-        SortField::Created => {
-            entries.sort_by(|a, b| {
-                a.1.created()
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-                    .cmp(&b.1.created().unwrap_or(std::time::SystemTime::UNIX_EPOCH))
-            });
+        .show(Path::new("."), Some(&named), &mut stats);
+    }
+    for (index, path) in dirs.iter().enumerate() {
+        if ds::interrupted() {
+            break;
         }
-        SortField::Inode => {
-            entries.sort_by(|a, b| a.1.ino().cmp(&b.1.ino()));
+        if (index > 0 || !named.is_empty()) && combined.is_none() {
+            println!();
+            if let Some(copied) = &copied
+                && cli.copy == Some(CopyMode::Listing)
+            {
+                copied.borrow_mut().push('\n');
+            }
         }
-        SortField::FileType => {
-            entries.sort_by(|a, b| {
-                let a_type = a.1.file_type();
-                let b_type = b.1.file_type();
-
-                match (a_type.is_dir(), b_type.is_dir()) {
-                    (true, false) => std::cmp::Ordering::Less,
-                    (false, true) => std::cmp::Ordering::Greater,
-                    _ => a.0.file_name().cmp(&b.0.file_name()),
+        let mut cli = cli.clone();
+        match views::load(path) {
+            Ok(Some(view)) => {
+                if !given("sort") && !given("reverse") {
+                    cli.sort = view.sort;
+                    cli.reverse = view.reverse;
                 }
-            });
+                // Columns saved for one path would break a header shared by several
+                if !displayed
+                    && combined.is_none()
+                    && let Some(columns) = view.columns
+                {
+                    cli.columns = Some(columns);
+                }
+            }
+            Ok(None) => {}
+            Err(err) => println!(
+                "{}",
+                colors
+                    .alert
+                    .colorize(format!("error:\nCan't read the saved view ({err})."))
+            ),
         }
-        SortField::None => {}
+        Listing {
+            cli: &cli,
+            template: template.as_ref(),
+            seed,
+            labeled,
+            colors: &colors,
+            ls_colors: ls_colors.as_ref(),
+            seen: seen.as_ref(),
+            errors: &errors,
+            combined: combined.as_ref(),
+            copied: copied.as_ref(),
+            cache: cache.as_ref(),
+            hooks: &hooks,
+        }
+        .show(path, None, &mut stats);
     }
-
-    if reverse {
-        entries.reverse();
+    if let Some(combined) = combined
+        && cli.output == Output::Json
+    {
+        let value = Value::Array(combined.into_inner().json);
+        let json = serde_json::to_string_pretty(&value).unwrap_or_default();
+        println!("{json}");
+        if let Some(copied) = &copied
+            && cli.copy == Some(CopyMode::Listing)
+        {
+            copied.borrow_mut().push_str(&format!("{json}\n"));
+        }
+    }
+    copy(copied.map(RefCell::into_inner), &colors);
+    if let Some(cache) = cache {
+        cache.save();
+    }
+    if cli.summary && !ds::interrupted() {
+        println!("\n{}", stats.summary(paths.len(), cli.units));
+    }
+    if cli.sudo_hint && !errors.denied().is_empty() {
+        eprintln!(
+            "Some paths couldn't be read for lack of permission. To list everything, run:\n  {}",
+            sudo_command(&args)
+        );
     }
+}
 
-    entries
+/// The items whose entry in `kept` is true.
+fn keep<T>(items: Vec<T>, kept: &[bool]) -> Vec<T> {
+    items
         .into_iter()
-        .map(|(file, meta)| {
-            (
-                basic_mode(&file, &meta),
-                size_mode(&meta),
-                binary_mode(&meta),
-                group_and_owner_mode(&meta),
-                mac_mode(&meta),
-                permission_mode(&meta),
-            )
-        })
+        .zip(kept)
+        .filter(|(_, kept)| **kept)
+        .map(|(item, _)| item)
         .collect()
 }
 
-fn basic_mode(file: &DirEntry, meta: &Metadata) -> Basic {
-    Basic {
-        name: file
-            .file_name()
-            .into_string()
-            .unwrap_or("UNKNOWN NAME".into()),
-        types: if meta.is_dir() {
-            Types::Dir
-        } else {
-            Types::File
-        },
+/// The command line that ran `ds`, under sudo and ready to paste into a shell.
+fn sudo_command(args: &[OsString]) -> String {
+    let program = std::env::current_exe()
+        .map(OsString::from)
+        .unwrap_or_else(|_| args.first().cloned().unwrap_or("ds".into()));
+    let quote = |arg: &OsString| {
+        let arg = arg.to_string_lossy();
+        let plain = !arg.is_empty()
+            && arg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "@%+=:,./_-".contains(c));
+        match plain {
+            true => arg.into_owned(),
+            false => format!("'{}'", arg.replace('\'', r"'\''")),
+        }
+    };
+    std::iter::once(&program)
+        .chain(args.iter().skip(1))
+        .fold("sudo".to_string(), |command, arg| {
+            format!("{command} {}", quote(arg))
+        })
+}
+
+fn lister(cli: &Cli, seed: u64, errors: &ReadErrors) -> DirLister {
+    DirLister {
+        all: cli.all,
+        dirs: cli.dirs,
+        git_ignore: cli.git_ignore,
+        sort: cli.sort,
+        reverse: cli.reverse,
+        collate: cli.collate,
+        seed,
+        dereference: cli.dereference,
+        globs: globs(cli),
+        walk: cli.recursive || cli.tree,
+        errors: errors.clone(),
     }
 }
 
-fn size_mode(meta: &Metadata) -> Size {
-    Size {
-        size: human_readable_size(meta.len()),
+/// The name filters of `--glob`, `--iglob`, `--ext` and `--iext`.
+fn globs(cli: &Cli) -> Vec<Glob> {
+    let mut globs = Vec::new();
+    for (patterns, ignore_case) in [(&cli.glob, false), (&cli.iglob, true)] {
+        globs.extend(patterns.iter().map(|pattern| Glob {
+            pattern: pattern.clone(),
+            ignore_case,
+        }));
+    }
+    for (extensions, ignore_case) in [(&cli.ext, false), (&cli.iext, true)] {
+        globs.extend(
+            extensions
+                .iter()
+                .map(|extension| Glob::extension(extension, ignore_case)),
+        );
     }
+    globs
 }
 
-fn mac_mode(meta: &Metadata) -> MAC {
-    MAC {
-        modified: if let Ok(modi) = meta.modified() {
-            let date: DateTime<Utc> = modi.into();
-            format!("{}", date.format("%a %b %e %Y"))
-        } else {
-            String::default()
-        },
+/// Only tables get a banner, other formats are meant for programs.
+fn banner(cli: &Cli) -> bool {
+    cli.output == Output::Table && cli.format.is_none() && !cli.grid && !cli.oneline
+}
 
-        accessed: if let Ok(access) = meta.accessed() {
-            let date: DateTime<Utc> = access.into();
-            format!("{}", date.format("%a %b %e %Y"))
-        } else {
-            String::default()
-        },
-        created: if let Ok(created) = meta.created() {
-            let date: DateTime<Utc> = created.into();
-            format!("{}", date.format("%a %b %e %Y"))
+/// Output meant for programs, which several paths share one document of.
+fn machine(cli: &Cli) -> bool {
+    matches!(cli.output, Output::Json | Output::Csv | Output::Tsv)
+        && cli.format.is_none()
+        && !cli.grid
+        && !cli.oneline
+}
+
+/// The listings of several paths in a machine-readable format: JSON is
+/// printed as one array at the end, CSV and TSV share one header.
+#[derive(Default)]
+struct Combined {
+    json: Vec<Value>,
+    /// Whether the header row is printed already
+    header: bool,
+}
+
+/// What listing each path shares: the options, output template and colors.
+struct Listing<'a> {
+    cli: &'a Cli,
+    template: Option<&'a format::Template>,
+    seed: u64,
+    /// Several paths are listed, so each is labeled
+    labeled: bool,
+    colors: &'a ColorScheme,
+    ls_colors: Option<&'a LsColors>,
+    /// Entries of every path, with --dedupe
+    seen: Option<&'a dedupe::Seen>,
+    /// What the listings couldn't read
+    errors: &'a ReadErrors,
+    /// Where the listings go when several paths make one document
+    combined: Option<&'a RefCell<Combined>>,
+    /// What `--copy` collects of every path
+    copied: Option<&'a RefCell<String>>,
+    /// Directory totals and match counts from earlier runs, with --cache
+    cache: Option<&'a cache::Cache>,
+    /// The filter and color hooks of config.toml
+    hooks: &'a hooks::Hooks,
+}
+
+impl Listing<'_> {
+    /// Print part of the listing, keeping it for `--copy`.
+    fn print(&self, text: &str) {
+        // Stop quietly when the reader goes away, as with `| head`
+        let _ = write!(std::io::stdout(), "{text}");
+        if let Some(copied) = self.copied
+            && self.cli.copy == Some(CopyMode::Listing)
+        {
+            copied.borrow_mut().push_str(&strip_ansi(text));
+        }
+    }
+
+    /// List the directory `path`, or the entries `named` on the command line.
+    fn show(&self, path: &Path, named: Option<&[PathBuf]>, stats: &mut stats::Stats) {
+        let Listing {
+            cli,
+            template,
+            seed,
+            labeled,
+            colors,
+            ls_colors,
+            seen,
+            errors,
+            combined,
+            copied,
+            cache,
+            hooks,
+        } = *self;
+        if named.is_none() {
+            match (&cli.at, &cli.from_json) {
+                _ if !banner(cli) => {
+                    if labeled && !machine(cli) {
+                        self.print(&format!("{}:\n", path.display()));
+                    }
+                }
+                (Some(rev), _) => self.print(&format!("Path: {} @ {}\n", path.display(), rev)),
+                (_, Some(file)) => self.print(&format!("Path: {} (saved)\n", file.display())),
+                _ => self.print(&format!("Path: {}\n", path.display())),
+            }
+            if cli.git_header
+                && banner(cli)
+                && let Some(header) = git::header(path)
+            {
+                self.print(&format!("Branch: {header}\n"));
+            }
+        }
+        if cli.budget {
+            match budget::render(path, cli.units, colors) {
+                Ok(table) => self.print(&format!("{table}\n")),
+                Err(err) => println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize(format!("error:\nCan't read {} ({err}).", budget::FILE_NAME))
+                ),
+            }
+            return;
+        }
+        // A revision may have directories deleted since
+        if let Ok(is_exist) = fs::exists(path).map(|exists| exists || cli.at.is_some()) {
+            if is_exist {
+                // Git LFS pointers, which are listed at the real size
+                let mut lfs: Vec<bool> = Vec::new();
+                // What --tree-limit left out, told between the rows
+                let mut omitted: Vec<Omitted> = Vec::new();
+                // Tree branches are drawn last so icons go between them and the name
+                let (entries, files, branches) = match (&cli.at, &cli.from_json) {
+                    (None, Some(file)) => {
+                        match json::read(
+                            file,
+                            cli.all,
+                            cli.dirs,
+                            cli.sort,
+                            cli.reverse,
+                            seed,
+                            cli.units,
+                        ) {
+                            Ok(rows) => (Vec::new(), rows, Vec::new()),
+                            Err(err) => {
+                                println!(
+                                    "{}",
+                                    colors.alert.colorize(format!(
+                                        "error:\nCan't read the saved listing ({err})."
+                                    ))
+                                );
+                                return;
+                            }
+                        }
+                    }
+                    (Some(rev), _) => {
+                        match git::tree_rows(
+                            path,
+                            rev,
+                            cli.all,
+                            cli.dirs,
+                            cli.sort,
+                            cli.reverse,
+                            cli.units,
+                        ) {
+                            Ok(rows) => {
+                                let (rows, pointers) = rows.into_iter().unzip();
+                                lfs = pointers;
+                                (Vec::new(), rows, Vec::new())
+                            }
+                            Err(err) => {
+                                println!(
+                                    "{}",
+                                    colors.alert.colorize(format!(
+                                        "error:\nCan't list revision '{rev}' ({err})."
+                                    ))
+                                );
+                                return;
+                            }
+                        }
+                    }
+                    (None, None) => {
+                        let lister = lister(cli, seed, errors);
+                        let entries = match (named, cli.sample) {
+                            (Some(named), _) => lister.named(named),
+                            (None, Some(count)) => lister.sample(path, count),
+                            (None, None) => lister.list(path),
+                        };
+                        if cli.tree || cli.recursive {
+                            let depth = cli.depth.unwrap_or(usize::MAX);
+                            let walked = match cli.tree {
+                                true => {
+                                    let limit = cli.tree_limit.unwrap_or(usize::MAX);
+                                    let (tree, left_out) = lister.tree(entries, depth, limit);
+                                    omitted = left_out;
+                                    tree
+                                }
+                                false => lister.recursive(entries, depth),
+                            };
+                            let (prefixes, entries): (Vec<String>, Vec<_>) =
+                                walked.into_iter().unzip();
+                            let mut files = file_rows(&entries, cli.units);
+                            if cli.tree {
+                                (entries, files, prefixes)
+                            } else {
+                                for ((basic, ..), prefix) in files.iter_mut().zip(prefixes) {
+                                    basic.name = format!("{prefix}{}", basic.name);
+                                }
+                                (entries, files, Vec::new())
+                            }
+                        } else {
+                            let files = file_rows(&entries, cli.units);
+                            (entries, files, Vec::new())
+                        }
+                    }
+                };
+                // What the filter hook turns down is left out, rows left out by
+                // --tree-limit are told where the kept ones are
+                let (entries, files, branches) = match hooks.filter(&entries, cli.units) {
+                    Ok(Some(kept)) => {
+                        for omission in &mut omitted {
+                            omission.index = kept
+                                .iter()
+                                .take(omission.index)
+                                .filter(|kept| **kept)
+                                .count();
+                        }
+                        (
+                            keep(entries, &kept),
+                            keep(files, &kept),
+                            keep(branches, &kept),
+                        )
+                    }
+                    Ok(None) => (entries, files, branches),
+                    Err(err) => {
+                        println!(
+                            "{}",
+                            colors
+                                .alert
+                                .colorize(format!("error:\nCan't run the filter hook ({err})."))
+                        );
+                        (entries, files, branches)
+                    }
+                };
+                // Entries listed before, under another path or this one, are left out
+                let (entries, mut files) = match seen {
+                    Some(seen) => entries
+                        .into_iter()
+                        .zip(files)
+                        .filter(|(entry, _)| seen.claim(&entry.meta))
+                        .unzip(),
+                    None => (entries, files),
+                };
+                let (entries, mut files) = match cli.total_size {
+                    true => {
+                        let totals = du::totals(&entries, cache);
+                        for ((_, size, binary, ..), total) in files.iter_mut().zip(&totals) {
+                            if let Some(total) = total {
+                                size.size = human_readable_size(*total, cli.units);
+                                binary.size = total.to_string();
+                            }
+                        }
+                        let mut rows: Vec<_> = entries.into_iter().zip(files).zip(totals).collect();
+                        // Sorting by size goes by the totals, unless it would break up a walk
+                        if matches!(cli.sort, SortField::Size) && !cli.tree && !cli.recursive {
+                            rows.sort_by_key(|((entry, _), total)| {
+                                total.unwrap_or(entry.meta.len())
+                            });
+                            if cli.reverse {
+                                rows.reverse();
+                            }
+                        }
+                        rows.into_iter().map(|(row, _)| row).unzip()
+                    }
+                    false => (entries, files),
+                };
+                if cli.git {
+                    let sizes = git::pointer_sizes(&entries);
+                    for ((_, size, binary, ..), real) in files.iter_mut().zip(&sizes) {
+                        if let Some(real) = real {
+                            size.size = human_readable_size(*real, cli.units);
+                            binary.size = real.to_string();
+                        }
+                    }
+                    lfs = sizes.iter().map(Option::is_some).collect();
+                }
+                // Named entries show the path they were given by
+                if named.is_some() {
+                    for ((basic, ..), entry) in files.iter_mut().zip(&entries) {
+                        let path = entry.path.strip_prefix(".").unwrap_or(&entry.path);
+                        basic.name = escape_control(&path.to_string_lossy());
+                    }
+                }
+                // Piped names are kept as they are, like ls does, so `ds -1 | xargs` gets
+                // names that exist
+                if cli.oneline && !color::stdout_is_terminal() {
+                    for ((basic, ..), entry) in files.iter_mut().zip(&entries) {
+                        let name = match named {
+                            Some(_) => entry.path.strip_prefix(".").unwrap_or(&entry.path),
+                            None => entry
+                                .path
+                                .strip_prefix(path)
+                                .unwrap_or(Path::new(entry.name())),
+                        };
+                        basic.name = name.to_string_lossy().into_owned();
+                    }
+                }
+                stats.scanned(&files);
+                // Taken before names are decorated with icons, suffixes and branches
+                if let Some(copied) = copied
+                    && cli.copy == Some(CopyMode::Paths)
+                {
+                    let mut copied = copied.borrow_mut();
+                    for (index, (basic, ..)) in files.iter().enumerate() {
+                        let path = match entries.get(index) {
+                            Some(entry) => entry.path.clone(),
+                            None => path.join(&basic.name),
+                        };
+                        copied.push_str(&format!("{}\n", path.display()));
+                    }
+                }
+                // Revisions and saved listings have no metadata, executables aren't marked there
+                let executable = |index: usize| {
+                    entries
+                        .get(index)
+                        .is_some_and(|entry| entry.meta.permissions().mode() & 0o111 != 0)
+                };
+                if cli.icons {
+                    let icons = match icons::Icons::load() {
+                        Ok(icons) => icons,
+                        Err(err) => {
+                            println!(
+                                "{}",
+                                colors
+                                    .alert
+                                    .colorize(format!("error:\nCan't load icons ({err})."))
+                            );
+                            return;
+                        }
+                    };
+                    for (index, (basic, ..)) in files.iter_mut().enumerate() {
+                        let name = match entries.get(index) {
+                            Some(entry) => entry.name().to_string_lossy().into_owned(),
+                            None => basic
+                                .name
+                                .rsplit('/')
+                                .next()
+                                .unwrap_or_default()
+                                .to_string(),
+                        };
+                        let icon = icons.icon(&basic.types, &name, executable(index));
+                        basic.name = format!("{icon} {}", basic.name);
+                    }
+                }
+                if cli.classify {
+                    for (index, (basic, ..)) in files.iter_mut().enumerate() {
+                        basic
+                            .name
+                            .push_str(indicator(&basic.types, executable(index)));
+                    }
+                }
+                // Directories a walk couldn't enter, so what's below them is missing
+                let denied = errors.denied();
+                let denied: Vec<bool> = entries
+                    .iter()
+                    .map(|entry| entry.meta.is_dir() && denied.contains(&entry.path))
+                    .collect();
+                for ((basic, ..), _) in files.iter_mut().zip(&denied).filter(|(_, denied)| **denied)
+                {
+                    basic.name.push_str(" [no access]");
+                }
+                for ((basic, ..), branch) in files.iter_mut().zip(branches) {
+                    basic.name = format!("{branch}{}", basic.name);
+                }
+                if let Some(width) = cli.max_width {
+                    for (basic, ..) in files.iter_mut() {
+                        basic.name = brief::shorten(&basic.name, width);
+                    }
+                }
+                // The line of what --tree-limit left out of a directory
+                let more = |omitted: &Omitted| {
+                    let line = format!("{}… {} more", omitted.branch, omitted.count);
+                    match cli.max_width {
+                        Some(width) => brief::shorten(&line, width),
+                        None => line,
+                    }
+                };
+                // Rows rendered by `render` with the lines of `omitted` between them
+                let interleave = |render: &dyn Fn(Range<usize>) -> String| {
+                    let mut text = String::new();
+                    let mut start = 0;
+                    for omitted in &omitted {
+                        text.push_str(&render(start..omitted.index));
+                        text.push_str(&format!("{}\n", colors.dim.colorize(more(omitted))));
+                        start = omitted.index;
+                    }
+                    text.push_str(&render(start..files.len()));
+                    text
+                };
+
+                let context = columns::Context {
+                    path,
+                    entries: &entries,
+                    rows: &files,
+                    units: cli.units,
+                    all: cli.all,
+                    colors,
+                    lfs: &lfs,
+                    seen,
+                    cache,
+                    pattern: cli.match_count.as_ref(),
+                    months: cli.churn,
+                    commits: OnceCell::new(),
+                };
+                if let Some(template) = &template {
+                    match template.render(&context) {
+                        Ok(lines) => self.print(&interleave(&|rows| lines[rows].concat())),
+                        Err(err) => {
+                            println!("{}", colors.alert.colorize(format!("error:\n{err}.")))
+                        }
+                    }
+                    return;
+                }
+                if cli.oneline {
+                    self.print(&interleave(&|rows| {
+                        files[rows]
+                            .iter()
+                            .map(|(basic, ..)| format!("{}\n", basic.name))
+                            .collect()
+                    }));
+                    return;
+                }
+                if cli.grid {
+                    self.print(&interleave(&|rows| {
+                        grid::render(&files[rows], grid::terminal_width(), colors)
+                    }));
+                    return;
+                }
+                if cli.output == Output::Brief {
+                    let title = match (&cli.at, &cli.from_json) {
+                        (Some(rev), _) => format!("{} @ {rev}", path.display()),
+                        (_, Some(file)) => file.display().to_string(),
+                        _ => path.display().to_string(),
+                    };
+                    self.print(&brief::render(&title, &files, &entries, cli.units));
+                    return;
+                }
+                if cli.output == Output::Json {
+                    let value = match cli.at.is_some() || cli.from_json.is_some() {
+                        true => json::rows(&files),
+                        // Each path is kept whole when several are listed
+                        false if combined.is_some() => {
+                            json::entries(Path::new(""), &entries, cli.units)
+                        }
+                        false => json::entries(path, &entries, cli.units),
+                    };
+                    let value = match &cli.fields {
+                        Some(fields) => json::select(value, fields),
+                        None => value,
+                    };
+                    if let Some(combined) = combined {
+                        let Value::Array(objects) = value else {
+                            return;
+                        };
+                        for mut object in objects {
+                            if entries.is_empty()
+                                && let Some(name) = object["path"].as_str()
+                            {
+                                object["path"] = json!(path.join(name).to_string_lossy());
+                            }
+                            combined.borrow_mut().json.push(object);
+                        }
+                        return;
+                    }
+                    let json = serde_json::to_string_pretty(&value).unwrap_or_default();
+                    self.print(&format!("{json}\n"));
+                    return;
+                }
+
+                let mut fields = cli.columns.clone().unwrap_or_else(|| {
+                    let mut fields = vec![Field::Name, Field::Type];
+                    if cli.size {
+                        fields.push(Field::Size);
+                    }
+                    if cli.binary {
+                        fields.push(Field::Binary);
+                    }
+                    if cli.group_and_owner {
+                        fields.extend([Field::Owner, Field::Group]);
+                    }
+                    if cli.mac {
+                        fields.extend([Field::Modified, Field::Accessed, Field::Created]);
+                    }
+                    if cli.permission {
+                        fields.push(Field::Permission);
+                    }
+                    fields
+                });
+                // Columns the options add go after those asked for
+                let added = [
+                    (lfs.contains(&true), Field::Lfs),
+                    (cli.cargo, Field::Crate),
+                    (cli.project, Field::Project),
+                    (cli.link_target, Field::LinkTarget),
+                    (cli.match_count.is_some(), Field::Matches),
+                    (cli.age_spark, Field::Ages),
+                    (
+                        seen.is_some_and(|seen| {
+                            entries.iter().any(|entry| !seen.others(entry).is_empty())
+                        }),
+                        Field::AlsoAt,
+                    ),
+                    (cli.reflink_info, Field::SharedExtents),
+                    (cli.badges, Field::Badges),
+                    (cli.libs, Field::Libraries),
+                    (cli.pkg, Field::Package),
+                    (cli.mounts, Field::Mount),
+                    (cli.unit_hints, Field::UnitState),
+                    (cli.effective, Field::Effective),
+                    (cli.git, Field::Git),
+                    (cli.git_log, Field::LastCommit),
+                    (cli.git_log, Field::Author),
+                    (cli.git_log, Field::CommitDate),
+                    (cli.churn.is_some(), Field::Churn),
+                ];
+                for (_, field) in added.into_iter().filter(|(added, _)| *added) {
+                    if !fields.contains(&field) {
+                        fields.push(field);
+                    }
+                }
+                // Dead links stand out in red for cleanups
+                let broken: Vec<bool> = entries
+                    .iter()
+                    .map(|entry| is_broken_link(&entry.path, &entry.meta))
+                    .collect();
+                let hooked = match color::enabled(cli.color) {
+                    true => hooks.colors(&entries, cli.units).unwrap_or_else(|err| {
+                        println!(
+                            "{}",
+                            colors
+                                .alert
+                                .colorize(format!("error:\nCan't run the color hook ({err})."))
+                        );
+                        None
+                    }),
+                    false => None,
+                };
+                let hooked = hooked.unwrap_or_default();
+                let name_colors: Vec<Option<Color>> = match &ls_colors {
+                    Some(ls_colors) => files
+                        .iter()
+                        .enumerate()
+                        .map(|(index, (basic, ..))| {
+                            let (name, mode) = match entries.get(index) {
+                                Some(entry) => (
+                                    entry.name().to_string_lossy().into_owned(),
+                                    entry.meta.permissions().mode(),
+                                ),
+                                None => (basic.name.clone(), 0),
+                            };
+                            let broken = broken.get(index).copied().unwrap_or(false);
+                            ls_colors.color(&name, &basic.types, mode, broken)
+                        })
+                        .collect(),
+                    None => Vec::new(),
+                };
+                // What --tree-limit left out is told in the name column, or the first
+                let mut name = 0;
+                let mut columns: Vec<Column> = Vec::new();
+                for field in fields {
+                    let column = match columns::field(field, &context) {
+                        Ok(column) => column,
+                        Err(err) => {
+                            println!("{}", colors.alert.colorize(format!("error:\n{err}.")));
+                            continue;
+                        }
+                    };
+                    let column = match (field, &ls_colors) {
+                        (Field::Name, Some(_)) => column
+                            .paint(&name_colors)
+                            .paint(&hooked)
+                            .highlight(&denied, colors.alert.clone()),
+                        (Field::Name, None) => column
+                            .highlight(&broken, colors.alert.clone())
+                            .paint(&hooked)
+                            .highlight(&denied, colors.alert.clone()),
+                        _ => column,
+                    };
+                    if field == Field::Name {
+                        name = columns.len();
+                    }
+                    columns.push(column);
+                }
+                for plugin in &cli.plugin {
+                    match columns::plugin(plugin, &context) {
+                        Ok(column) => columns.push(column),
+                        Err(err) => {
+                            println!("{}", colors.alert.colorize(format!("error:\n{err}.")))
+                        }
+                    }
+                }
+
+                // Every time to the nanosecond, as epoch seconds and ISO 8601 like
+                // JSON has them, rather than only the dates of the table
+                if matches!(cli.output, Output::Csv | Output::Tsv) && !entries.is_empty() {
+                    let times: Vec<_> = entries
+                        .iter()
+                        .map(|entry| json::times(&entry.meta))
+                        .collect();
+                    for key in json::FIELDS
+                        .iter()
+                        .filter(|key| times[0].contains_key(**key))
+                    {
+                        let cells = times
+                            .iter()
+                            .map(|times| match &times[*key] {
+                                Value::Null => String::new(),
+                                Value::String(text) => text.clone(),
+                                value => value.to_string(),
+                            })
+                            .collect();
+                        columns.push(Column::plain(*key, cells));
+                    }
+                }
+                for omitted in omitted.iter().rev() {
+                    for (index, column) in columns.iter_mut().enumerate() {
+                        match index == name {
+                            true => column.insert(
+                                omitted.index,
+                                more(omitted),
+                                Some(colors.dim.clone()),
+                            ),
+                            false => column.insert(omitted.index, String::new(), None),
+                        }
+                    }
+                }
+                let table = ds::table(&columns, colors.dim.clone());
+                match cli.output {
+                    Output::Csv | Output::Tsv => {
+                        // Several paths share one header, with the path of each entry in front
+                        let full: Option<Vec<String>> =
+                            combined.map(|_| match entries.is_empty() {
+                                true => files
+                                    .iter()
+                                    .map(|(basic, ..)| path.join(&basic.name).display().to_string())
+                                    .collect(),
+                                false => entries
+                                    .iter()
+                                    .map(|entry| entry.path.display().to_string())
+                                    .collect(),
+                            });
+                        let header = combined.is_none_or(|combined| {
+                            !std::mem::replace(&mut combined.borrow_mut().header, true)
+                        });
+                        self.print(&delimited(&table, cli.output, full.as_deref(), header));
+                        return;
+                    }
+                    _ if cli.a11y => self.print(&sentences(&table)),
+                    _ => self.print(&format!("{table}\n")),
+                }
+                if cli.link_targets {
+                    let targets = link_targets_table(&entries, colors);
+                    match cli.a11y {
+                        true => self.print(&format!("\n{}", sentences(&targets))),
+                        false => self.print(&format!("\n{targets}\n")),
+                    }
+                }
+            } else {
+                println!(
+                    "{}",
+                    colors
+                        .alert
+                        .colorize("error:\nPath doesn't exist. (try other location)")
+                );
+            }
         } else {
-            String::default()
-        },
+            println!("{}", colors.alert.colorize("error:\nCan't read directory."));
+        }
     }
 }
 
-fn permission_mode(meta: &Metadata) -> Permission {
-    let permissions = meta.permissions();
-    let mode = permissions.mode();
+fn run_report(path: &Path, output: &Path, show_hidden: bool, units: Units, colors: &ColorScheme) {
+    if !fs::exists(path).unwrap_or(false) {
+        println!(
+            "{}",
+            colors
+                .alert
+                .colorize("error:\nPath doesn't exist. (try other location)")
+        );
+        return;
+    }
+
+    let lister = DirLister {
+        all: show_hidden,
+        ..DirLister::default()
+    };
+    let entries = lister.list(path);
+    let files = file_rows(&entries, units);
+    match fs::write(output, report::render(path, &files, units)) {
+        Ok(()) => println!("Report written to {}", output.display()),
+        Err(err) => println!(
+            "{}",
+            colors
+                .alert
+                .colorize(format!("error:\nCan't write report ({err})."))
+        ),
+    }
+}
 
-    let mut perm_string = String::with_capacity(10);
+fn link_targets_table(entries: &[Entry], colors: &ColorScheme) -> Table {
+    let mut counts: BTreeMap<PathBuf, usize> = BTreeMap::new();
 
-    // File type
-    perm_string.push(if meta.is_dir() { 'd' } else { '-' });
+    for Entry { path, meta } in entries {
+        if !meta.file_type().is_symlink() {
+            continue;
+        }
+        let Ok(target) = fs::read_link(path) else {
+            continue;
+        };
 
-    // User permissions
-    perm_string.push(if mode & 0o400 != 0 { 'r' } else { '-' });
-    perm_string.push(if mode & 0o200 != 0 { 'w' } else { '-' });
-    perm_string.push(if mode & 0o100 != 0 { 'x' } else { '-' });
+        // Resolve lexically so dangling links are still counted, from the
+        // absolute directory so `..` can't climb out of a relative one
+        let parent = path.parent().unwrap_or(Path::new("."));
+        let Ok(parent) = std::path::absolute(parent) else {
+            continue;
+        };
+        let target = normalize(&parent.join(target));
+        let directory = target.parent().unwrap_or(Path::new("/")).to_path_buf();
+        *counts.entry(directory).or_default() += 1;
+    }
 
-    // Group permissions
-    perm_string.push(if mode & 0o40 != 0 { 'r' } else { '-' });
-    perm_string.push(if mode & 0o20 != 0 { 'w' } else { '-' });
-    perm_string.push(if mode & 0o10 != 0 { 'x' } else { '-' });
+    let mut targets: Vec<LinkTarget> = counts
+        .into_iter()
+        .map(|(directory, links)| LinkTarget {
+            directory: directory.display().to_string(),
+            links,
+        })
+        .collect();
+    targets.sort_by_key(|target| std::cmp::Reverse(target.links));
 
-    // Other permissions
-    perm_string.push(if mode & 0o4 != 0 { 'r' } else { '-' });
-    perm_string.push(if mode & 0o2 != 0 { 'w' } else { '-' });
-    perm_string.push(if mode & 0o1 != 0 { 'x' } else { '-' });
+    let mut table = Table::new(targets);
+    table.with(Style::empty());
+    table.modify(Columns::first(), colors.note.clone());
+    table.modify(Columns::last(), colors.size.clone());
+    table.modify(Rows::first(), colors.dim.clone());
+    table
+}
 
-    Permission {
-        permission: perm_string,
+/// Collapse `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
     }
+    normalized
 }
 
-fn binary_mode(meta: &Metadata) -> Binary {
-    Binary {
-        size: meta.len().to_string(),
+/// Put what `--copy` collected on the clipboard. Failing exits with an error,
+/// so scripts notice nothing was copied.
+fn copy(copied: Option<String>, colors: &ColorScheme) {
+    let Some(copied) = copied else {
+        return;
+    };
+    match clipboard::copy(&copied) {
+        Ok(()) => eprintln!("{}", colors.dim.colorize("Copied to clipboard.")),
+        Err(err) => {
+            eprintln!(
+                "{}",
+                colors
+                    .alert
+                    .colorize(format!("error:\nCan't copy to clipboard ({err})."))
+            );
+            std::process::exit(1);
+        }
     }
 }
 
-fn group_and_owner_mode(meta: &Metadata) -> GroupOwner {
-    let cache = UsersCache::new();
-    let uid = meta.uid();
-    let gid = meta.gid();
-
-    GroupOwner {
-        owner: cache
-            .get_user_by_uid(uid)
-            .map(|u| u.name().to_string_lossy().into_owned())
-            .unwrap_or_else(|| uid.to_string()),
+/// One "header: value, ..." line per row, leaving out empty cells, so the
+/// listing reads well without relying on alignment or colors.
+fn sentences(table: &Table) -> String {
+    let records = table.get_records();
+    let Some((header, rows)) = records.split_first() else {
+        return String::new();
+    };
+    let header: Vec<String> = header
+        .iter()
+        .map(|cell| strip_ansi(cell.text()).to_lowercase())
+        .collect();
 
-        group: cache
-            .get_group_by_gid(gid)
-            .map(|g| g.name().to_string_lossy().into_owned())
-            .unwrap_or_else(|| gid.to_string()),
+    let mut text = String::new();
+    for row in rows {
+        let fields: Vec<String> = header
+            .iter()
+            .zip(row)
+            .map(|(name, cell)| (name, strip_ansi(cell.text())))
+            .filter(|(_, value)| !value.trim().is_empty())
+            .map(|(name, value)| format!("{name}: {}", value.trim()))
+            .collect();
+        text.push_str(&fields.join(", "));
+        text.push('\n');
     }
+    text
 }
 
-fn human_readable_size(bytes: u64) -> String {
-    const UNITS: [&str; 6] = ["B", "K", "M", "G", "T", "P"];
-    let mut size = bytes as f64;
-    let mut unit_index = 0;
+/// The table's cells as CSV (quoted per RFC 4180) or TSV (tabs, newlines and
+/// backslashes escaped with a backslash). `paths` go in a first column, and
+/// the header row is left out unless `header`.
+fn delimited(table: &Table, output: Output, paths: Option<&[String]>, header: bool) -> String {
+    let field = |cell: &str| -> String {
+        let cell = strip_ansi(cell);
+        match output {
+            Output::Tsv => cell
+                .replace('\\', "\\\\")
+                .replace('\t', "\\t")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r"),
+            _ if cell.contains([',', '"', '\n', '\r']) => {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            }
+            _ => cell,
+        }
+    };
+    let separator = if output == Output::Tsv { "\t" } else { "," };
 
-    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_index += 1;
+    let mut text = String::new();
+    for (index, row) in table.get_records().iter().enumerate() {
+        if index == 0 && !header {
+            continue;
+        }
+        let mut fields: Vec<String> = Vec::new();
+        if let Some(paths) = paths {
+            fields.push(match index {
+                0 => "Path".to_string(),
+                index => field(paths.get(index - 1).map_or("", String::as_str)),
+            });
+        }
+        fields.extend(row.iter().map(|cell| field(cell.text())));
+        text.push_str(&fields.join(separator));
+        text.push('\n');
     }
+    text
+}
+
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
 
-    // Show 1 decimal place only if needed
-    if size >= 10.0 || unit_index == 0 {
-        format!("{:.0}{}", size, UNITS[unit_index])
-    } else {
-        format!("{:.1}{}", size, UNITS[unit_index])
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip a CSI sequence: ESC '[' params... final byte
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            plain.push(c);
+        }
     }
+
+    plain
 }
@@ -0,0 +1,67 @@
+//! `ds --generate-man`: a roff man page made from the command line definition,
+//! for distribution packages to ship.
+
+use clap::Command;
+use clap_mangen::Man;
+use clap_mangen::roff::{Roff, bold, roman};
+use std::io::{self, Write};
+
+/// Environment variables ds reads, and what for
+const ENVIRONMENT: &[(&str, &str)] = &[
+    (
+        "DS_OPTS",
+        "Options read before the command line, like \"--all --sort modified\".",
+    ),
+    ("NO_COLOR", "Turns colors off with --color auto."),
+    (
+        "CLICOLOR, CLICOLOR_FORCE",
+        "CLICOLOR=0 turns colors off, CLICOLOR_FORCE turns them on even when piped.",
+    ),
+    (
+        "LS_COLORS",
+        "Colors of names by file type and suffix, as set by dircolors(1).",
+    ),
+    (
+        "COLORFGBG",
+        "The terminal background, to pick colors readable on it.",
+    ),
+    (
+        "XDG_CONFIG_HOME",
+        "Where the ds directory of settings is, ~/.config when unset.",
+    ),
+];
+
+/// Files ds reads, and what for
+const FILES: &[(&str, &str)] = &[
+    (
+        "~/.config/ds/config.toml",
+        "Default options as top-level keys, like sort = \"size\", and the [theme] and [icons] tables.",
+    ),
+    (
+        "~/.config/ds/views.toml",
+        "Sorts and columns saved for directories with --save-view.",
+    ),
+    (
+        ".ds-budget.toml",
+        "Size budgets of subdirectories, for --budget.",
+    ),
+];
+
+fn section(roff: &mut Roff, title: &str, items: &[(&str, &str)]) {
+    roff.control("SH", [title]);
+    for (name, text) in items {
+        roff.control("TP", []);
+        roff.text([bold(*name)]);
+        roff.text([roman(*text)]);
+    }
+}
+
+pub fn render(command: Command) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    Man::new(command).render(&mut stdout)?;
+    let mut roff = Roff::new();
+    section(&mut roff, "ENVIRONMENT", ENVIRONMENT);
+    section(&mut roff, "FILES", FILES);
+    roff.to_writer(&mut stdout)?;
+    stdout.flush()
+}
@@ -0,0 +1,58 @@
+//! `--match-count REGEX`: how many lines of each listed file match, counted on
+//! all cores, to see which files mention something and how often.
+
+use crate::cache::Cache;
+use ds::Entry;
+use regex::bytes::Regex;
+use std::fs;
+use std::thread;
+
+/// Larger files are skipped rather than read whole
+const MAX_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Matching lines of a file; `-` when it is too large, binary or unreadable.
+fn count(regex: &Regex, Entry { path, meta }: &Entry) -> String {
+    if !meta.is_file() {
+        return String::new();
+    }
+    if meta.len() > MAX_SIZE {
+        return "-".into();
+    }
+    let Ok(content) = fs::read(path) else {
+        return "-".into();
+    };
+    // A NUL byte near the start means binary, like grep decides
+    if content.iter().take(8192).any(|byte| *byte == 0) {
+        return "-".into();
+    }
+    content
+        .split(|byte| *byte == b'\n')
+        .filter(|line| regex.is_match(line))
+        .count()
+        .to_string()
+}
+
+/// The matching lines of each entry, from `cache` when a file hasn't changed
+/// since they were last counted.
+pub fn annotations(regex: &Regex, entries: &[Entry], cache: Option<&Cache>) -> Vec<String> {
+    let kind = format!("matches of {}", regex.as_str());
+    let count = |entry: &Entry| match cache {
+        Some(cache) if entry.meta.is_file() => cache
+            .get(&kind, &entry.meta, || Some(count(regex, entry).into()))
+            .and_then(|count| count.as_str().map(str::to_string))
+            .unwrap_or_default(),
+        _ => count(regex, entry),
+    };
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk = entries.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let workers: Vec<_> = entries
+            .chunks(chunk)
+            .map(|entries| scope.spawn(|| entries.iter().map(count).collect::<Vec<_>>()))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_default())
+            .collect()
+    })
+}
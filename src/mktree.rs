@@ -0,0 +1,148 @@
+//! `ds mktree`: create a directory tree from a YAML spec, for test fixtures
+//! and for reproducing bug reports.
+//!
+//! Keys ending in `/` are directories holding the entries of their mapping.
+//! Other keys are files: a string is their content, an empty value an empty
+//! file, and a mapping sets any of `content`, `size` (in bytes, padding or
+//! cutting the content), `mode` (octal like `755`), `mtime` (RFC 3339 or Unix
+//! seconds) or `symlink` (the target, making a link instead of a file).
+//!
+//! ```yaml
+//! src/:
+//!   main.rs: "fn main() {}\n"
+//!   big.bin: { size: 1048576, mtime: 2024-01-01T00:00:00Z }
+//! run.sh: { content: "#!/bin/sh\n", mode: 755 }
+//! latest: { symlink: src/main.rs }
+//! empty/:
+//! ```
+
+use chrono::DateTime;
+use serde_yaml::{Mapping, Value};
+use std::fs::{self, FileTimes, OpenOptions, Permissions};
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn mode(value: &Value) -> Result<u32, String> {
+    // `mode: 755` is read as the number seven hundred fifty-five
+    let text = match value {
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => text.clone(),
+        _ => String::new(),
+    };
+    u32::from_str_radix(&text, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o7777)
+        .ok_or_else(|| format!("invalid mode {value:?} (expected octal, like 644)"))
+}
+
+fn time(value: &Value) -> Result<SystemTime, String> {
+    let invalid = || format!("invalid mtime {value:?} (expected RFC 3339 or Unix seconds)");
+    match value {
+        Value::Number(number) => {
+            let seconds = number.as_u64().ok_or_else(invalid)?;
+            Ok(UNIX_EPOCH + Duration::from_secs(seconds))
+        }
+        Value::String(text) => DateTime::parse_from_rfc3339(text)
+            .map(SystemTime::from)
+            .map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+fn text(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Number(number) => Ok(number.to_string()),
+        Value::Bool(flag) => Ok(flag.to_string()),
+        _ => Err(format!("expected text, found {value:?}")),
+    }
+}
+
+fn create_file(path: &Path, spec: &Value) -> Result<(), String> {
+    let attributes = match spec {
+        Value::Null => Mapping::new(),
+        Value::Mapping(attributes) => attributes.clone(),
+        content => Mapping::from_iter([("content".into(), Value::String(text(content)?))]),
+    };
+    for key in attributes.keys() {
+        let known = ["content", "size", "mode", "mtime", "symlink"];
+        if !key.as_str().is_some_and(|key| known.contains(&key)) {
+            return Err(format!(
+                "unknown attribute {key:?} (expected one of {})",
+                known.join(", ")
+            ));
+        }
+    }
+
+    if let Some(target) = attributes.get("symlink") {
+        return std::os::unix::fs::symlink(text(target)?, path).map_err(|err| err.to_string());
+    }
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|err| err.to_string())?;
+    if let Some(content) = attributes.get("content") {
+        file.write_all(text(content)?.as_bytes())
+            .map_err(|err| err.to_string())?;
+    }
+    if let Some(size) = attributes.get("size") {
+        let size = size
+            .as_u64()
+            .ok_or_else(|| format!("invalid size {size:?} (expected bytes)"))?;
+        file.set_len(size).map_err(|err| err.to_string())?;
+    }
+    if let Some(mtime) = attributes.get("mtime") {
+        file.set_times(FileTimes::new().set_modified(time(mtime)?))
+            .map_err(|err| err.to_string())?;
+    }
+    // Last, as the mode may take away the write permission
+    if let Some(value) = attributes.get("mode") {
+        file.set_permissions(Permissions::from_mode(mode(value)?))
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+fn create_dir(dir: &Path, spec: &Mapping) -> Result<usize, String> {
+    let mut created = 0;
+    for (name, spec) in spec {
+        let name = text(name)?;
+        let (path, is_dir) = match name.strip_suffix('/') {
+            Some(dir_name) => (dir.join(dir_name), true),
+            None => (dir.join(&name), false),
+        };
+        let fail = |err: String| format!("{}: {err}", path.display());
+
+        if is_dir {
+            fs::create_dir(&path).map_err(|err| fail(err.to_string()))?;
+            created += 1;
+            match spec {
+                Value::Null => {}
+                Value::Mapping(children) => created += create_dir(&path, children)?,
+                _ => return Err(fail("a directory holds a mapping of entries".into())),
+            }
+        } else {
+            create_file(&path, spec).map_err(fail)?;
+            created += 1;
+        }
+    }
+    Ok(created)
+}
+
+/// Create the tree `spec` describes inside `dir`, which is created if needed.
+/// Existing entries are never overwritten. Returns how many were created.
+pub fn create(spec: &Path, dir: &Path) -> Result<usize, String> {
+    let text =
+        fs::read_to_string(spec).map_err(|err| format!("can't read {}: {err}", spec.display()))?;
+    let spec: Value = serde_yaml::from_str(&text)
+        .map_err(|err| format!("{} isn't valid YAML: {err}", spec.display()))?;
+    fs::create_dir_all(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    match spec {
+        Value::Null => Ok(0),
+        Value::Mapping(entries) => create_dir(dir, &entries),
+        _ => Err("the spec must be a mapping of entries".into()),
+    }
+}
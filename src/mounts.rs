@@ -0,0 +1,101 @@
+//! Mount points among the entries, read from `/proc/self/mountinfo`, to tell
+//! bind mounts and volumes apart from the files of a container image.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+struct Mount {
+    /// The directory of the mounted filesystem that appears at the mount point
+    root: String,
+    fs_type: String,
+    source: String,
+    read_only: bool,
+}
+
+/// Mount points and paths escape spaces, tabs, newlines and backslashes as
+/// octal, like `\040`.
+fn unescape(field: &str) -> String {
+    let mut bytes = Vec::with_capacity(field.len());
+    let mut rest = field.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let code = tail
+            .get(..3)
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok());
+        match (byte, code) {
+            (b'\\', Some(code)) => {
+                bytes.push(code);
+                rest = &tail[3..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// The mounts of this process by mount point. Later lines mount over earlier
+/// ones, so they win.
+fn mounts() -> Result<HashMap<PathBuf, Mount>, String> {
+    let text = fs::read_to_string("/proc/self/mountinfo")
+        .map_err(|err| format!("can't read /proc/self/mountinfo: {err}"))?;
+
+    // id parent major:minor root mount-point options [optional...] - type source super-options
+    let mut mounts = HashMap::new();
+    for line in text.lines() {
+        let Some((before, after)) = line.split_once(" - ") else {
+            continue;
+        };
+        let before: Vec<&str> = before.split(' ').collect();
+        let after: Vec<&str> = after.split(' ').collect();
+        let (Some(root), Some(point), Some(options)) =
+            (before.get(3), before.get(4), before.get(5))
+        else {
+            continue;
+        };
+        mounts.insert(
+            PathBuf::from(unescape(point)),
+            Mount {
+                root: unescape(root),
+                fs_type: after.first().unwrap_or(&"").to_string(),
+                source: after
+                    .get(1)
+                    .map(|source| unescape(source))
+                    .unwrap_or_default(),
+                read_only: options.split(',').any(|option| option == "ro"),
+            },
+        );
+    }
+    Ok(mounts)
+}
+
+/// One annotation per path: what is mounted there, if it is a mount point.
+pub fn annotations(paths: &[PathBuf]) -> Result<Vec<String>, String> {
+    let mounts = mounts()?;
+
+    Ok(paths
+        .iter()
+        .map(|path| {
+            // Symlinks are never mount points themselves
+            if path.is_symlink() {
+                return String::new();
+            }
+            let Some(mount) = path.canonicalize().ok().and_then(|path| mounts.get(&path)) else {
+                return String::new();
+            };
+
+            // A root other than "/" means only part of the filesystem is
+            // mounted here: a bind mount or a volume
+            let mut annotation = match mount.root.as_str() {
+                "/" => format!("mount {} {}", mount.fs_type, mount.source),
+                root => format!("bind {root} ({} {})", mount.fs_type, mount.source),
+            };
+            if mount.read_only {
+                annotation.push_str(", ro");
+            }
+            annotation
+        })
+        .collect())
+}
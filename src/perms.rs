@@ -0,0 +1,147 @@
+//! Audit of file and directory modes in a tree against expected values.
+
+use crate::color::ColorScheme;
+use ds::{DirLister, Entry, permission_string};
+use std::fs::{self, Permissions};
+use std::io::{self, BufRead, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tabled::{
+    Table, Tabled,
+    settings::{Style, object::Columns, object::Rows},
+};
+
+#[derive(Debug, Tabled, Clone)]
+struct Deviation {
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Mode")]
+    mode: String,
+    #[tabled(rename = "Expected")]
+    expected: String,
+}
+
+/// Parse `FILE/DIR` octal modes like `644/755`.
+fn parse_expect(text: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("invalid modes '{text}' (expected FILE/DIR, like 644/755)");
+    let (file, dir) = text.split_once('/').ok_or_else(invalid)?;
+    let octal = |mode: &str| {
+        u32::from_str_radix(mode, 8)
+            .ok()
+            .filter(|mode| *mode <= 0o7777)
+    };
+    Ok((
+        octal(file).ok_or_else(invalid)?,
+        octal(dir).ok_or_else(invalid)?,
+    ))
+}
+
+fn describe(kind: char, mode: u32) -> String {
+    format!("{} ({:03o})", permission_string(kind, mode), mode)
+}
+
+/// Files and directories `lister` finds under `dir` whose mode isn't the
+/// expected one, with the mode they should have. Symlinks are skipped, their
+/// own mode is meaningless.
+fn deviations(lister: &DirLister, dir: &Path, modes: (u32, u32)) -> Vec<(PathBuf, u32, u32)> {
+    let mut found = Vec::new();
+    for (_, Entry { path, meta }) in lister.recursive(lister.list(dir), usize::MAX) {
+        if meta.file_type().is_symlink() {
+            continue;
+        }
+        let mode = meta.permissions().mode() & 0o7777;
+        let expected = if meta.is_dir() { modes.1 } else { modes.0 };
+        if mode != expected {
+            found.push((path, mode, expected));
+        }
+    }
+    found
+}
+
+pub fn check(
+    path: &Path,
+    expect: &str,
+    show_hidden: bool,
+    git_ignore: bool,
+    fix: bool,
+    colors: &ColorScheme,
+) -> Result<(), String> {
+    if !path.is_dir() {
+        return Err(format!("'{}' is not a directory", path.display()));
+    }
+    let modes = parse_expect(expect)?;
+
+    let lister = DirLister {
+        all: show_hidden,
+        git_ignore,
+        ..DirLister::default()
+    };
+    let interrupts = crate::catch_interrupts();
+    let mut found = deviations(&lister, path, modes);
+    drop(interrupts);
+    found.sort();
+    for (unread, err) in lister.errors.all() {
+        println!(
+            "{}",
+            colors
+                .alert
+                .colorize(format!("{}: {err}", unread.display()))
+        );
+    }
+    if found.is_empty() {
+        println!("All modes match {expect}.");
+        return Ok(());
+    }
+
+    let rows = found.iter().map(|(entry, mode, expected)| {
+        let kind = if entry.is_dir() { 'd' } else { '-' };
+        Deviation {
+            path: entry
+                .strip_prefix(path)
+                .unwrap_or(entry)
+                .display()
+                .to_string(),
+            mode: describe(kind, *mode),
+            expected: describe(kind, *expected),
+        }
+    });
+    let mut table = Table::new(rows);
+    table.with(Style::empty());
+    table.modify(Columns::one(1), colors.alert.clone());
+    table.modify(Columns::one(2), colors.permission.clone());
+    table.modify(Rows::first(), colors.dim.clone());
+    println!("{table}");
+
+    // Nothing is fixed after a scan cut short
+    if !fix || ds::interrupted() {
+        return Ok(());
+    }
+    print!("Change the mode of {} entries? [y/N] ", found.len());
+    io::stdout().flush().map_err(|err| err.to_string())?;
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|err| err.to_string())?;
+    if !matches!(answer.trim(), "y" | "Y" | "yes") {
+        println!("Nothing changed.");
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for (entry, _, expected) in &found {
+        if let Err(err) = fs::set_permissions(entry, Permissions::from_mode(*expected)) {
+            println!(
+                "{}",
+                colors.alert.colorize(format!("{}: {err}", entry.display()))
+            );
+            failed += 1;
+        }
+    }
+    println!(
+        "Changed {} of {} entries.",
+        found.len() - failed,
+        found.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,127 @@
+//! Which system package owns each file, asked of dpkg, rpm or pacman, whichever
+//! is installed.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub fn installed(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+/// The absolute path of `path` without resolving a final symlink, as package
+/// databases record links themselves.
+fn absolute(path: &Path) -> PathBuf {
+    match (
+        path.parent().and_then(|dir| dir.canonicalize().ok()),
+        path.file_name(),
+    ) {
+        (Some(dir), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// On merged-/usr systems packages may record `/bin/ls` for `/usr/bin/ls`.
+fn unmerged(path: &Path) -> Option<PathBuf> {
+    let rest = path.strip_prefix("/usr").ok()?;
+    let top = rest.components().next()?.as_os_str().to_string_lossy();
+    (top == "bin" || top == "sbin" || top.starts_with("lib")).then(|| Path::new("/").join(rest))
+}
+
+/// Owners of the queried paths, parsed from the output of the package manager.
+fn query(paths: &[PathBuf]) -> Result<HashMap<PathBuf, Vec<String>>, String> {
+    let mut owners: HashMap<PathBuf, Vec<String>> = HashMap::new();
+
+    if installed("dpkg-query") {
+        // "pkg-a, pkg-b: /path" per owned path, errors for the others
+        let output = Command::new("dpkg-query")
+            .arg("-S")
+            .args(paths)
+            .output()
+            .map_err(|err| format!("can't run dpkg-query: {err}"))?;
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            if line.starts_with("diversion ") {
+                continue;
+            }
+            if let Some((packages, path)) = line.split_once(": ") {
+                let packages = packages.split(", ").map(String::from);
+                owners
+                    .entry(PathBuf::from(path))
+                    .or_default()
+                    .extend(packages);
+            }
+        }
+    } else if installed("rpm") {
+        // One line per path, in order
+        let output = Command::new("rpm")
+            .args(["-qf", "--queryformat", "%{NAME}\\n"])
+            .args(paths)
+            .output()
+            .map_err(|err| format!("can't run rpm: {err}"))?;
+        for (path, line) in paths
+            .iter()
+            .zip(String::from_utf8_lossy(&output.stdout).lines())
+        {
+            if !line.contains(' ') {
+                owners
+                    .entry(path.clone())
+                    .or_default()
+                    .push(line.to_string());
+            }
+        }
+    } else if installed("pacman") {
+        // "/path is owned by pkg 1.0-1" per owned path
+        let output = Command::new("pacman")
+            .arg("-Qo")
+            .args(paths)
+            .output()
+            .map_err(|err| format!("can't run pacman: {err}"))?;
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            if let Some((path, owner)) = line.split_once(" is owned by ") {
+                let package = owner.split_whitespace().next().unwrap_or_default();
+                owners
+                    .entry(PathBuf::from(path))
+                    .or_default()
+                    .push(package.to_string());
+            }
+        }
+    } else {
+        return Err("no supported package manager (dpkg, rpm, pacman) found".into());
+    }
+
+    Ok(owners)
+}
+
+/// One annotation per path: the owning package, or how many packages share a
+/// directory.
+pub fn annotations(paths: &[PathBuf]) -> Result<Vec<String>, String> {
+    let candidates: Vec<(PathBuf, Option<PathBuf>)> = paths
+        .iter()
+        .map(|path| {
+            let path = absolute(path);
+            let alternative = unmerged(&path);
+            (path, alternative)
+        })
+        .collect();
+    let queried: Vec<PathBuf> = candidates
+        .iter()
+        .flat_map(|(path, alternative)| std::iter::once(path).chain(alternative))
+        .cloned()
+        .collect();
+    let owners = query(&queried)?;
+
+    Ok(candidates
+        .iter()
+        .map(|(path, alternative)| {
+            let packages = owners
+                .get(path)
+                .or_else(|| owners.get(alternative.as_ref()?));
+            match packages.map(Vec::as_slice) {
+                None | Some([]) => String::new(),
+                Some([package]) => package.clone(),
+                Some(packages) => format!("{} packages", packages.len()),
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,56 @@
+//! External column providers.
+//!
+//! A plugin is any executable on `PATH` named `ds-column-<name>`. It receives the
+//! listed paths on stdin as raw bytes, each ended by a NUL byte since names
+//! may hold newlines or invalid UTF-8, and answers on stdout with a JSON array
+//! holding one value per path in the same order. Strings are shown as-is, other
+//! JSON values are shown in their JSON form and `null` leaves the cell empty.
+
+use serde_json::Value;
+use std::io::Write;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+
+pub fn column(name: &str, paths: &[PathBuf]) -> Result<Vec<String>, String> {
+    let mut child = Command::new(format!("ds-column-{name}"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| err.to_string())?;
+
+    // Written from another thread so a full stdout pipe can't stall both ends
+    let mut stdin = child.stdin.take().ok_or("stdin unavailable")?;
+    let input: Vec<u8> = paths
+        .iter()
+        .flat_map(|path| path.as_os_str().as_bytes().iter().copied().chain([0]))
+        .collect();
+    let writer = thread::spawn(move || stdin.write_all(&input));
+
+    let output = child.wait_with_output().map_err(|err| err.to_string())?;
+    // A plugin may answer without reading everything, which breaks the pipe
+    let _ = writer.join();
+    if !output.status.success() {
+        return Err(format!("exited with {}", output.status));
+    }
+
+    let values: Vec<Value> =
+        serde_json::from_slice(&output.stdout).map_err(|err| err.to_string())?;
+    if values.len() != paths.len() {
+        return Err(format!(
+            "expected {} values, got {}",
+            paths.len(),
+            values.len()
+        ));
+    }
+
+    Ok(values
+        .into_iter()
+        .map(|value| match value {
+            Value::Null => String::new(),
+            Value::String(text) => text,
+            other => other.to_string(),
+        })
+        .collect())
+}
@@ -0,0 +1,114 @@
+//! Annotations for project directories, read from their manifests.
+
+use std::fs;
+use std::path::Path;
+use toml::Table;
+
+fn read_toml(path: &Path) -> Option<Table> {
+    fs::read_to_string(path).ok()?.parse().ok()
+}
+
+/// `name vX.Y.Z` of the crate in `dir`, "workspace" for a virtual manifest and
+/// "N crates" for a plain directory holding crates (like `crates/`).
+pub fn cargo_annotation(dir: &Path, workspace: Option<&Table>) -> String {
+    let Some(manifest) = read_toml(&dir.join("Cargo.toml")) else {
+        let crates = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.path().join("Cargo.toml").is_file())
+                    .count()
+            })
+            .unwrap_or(0);
+        return match crates {
+            0 => String::new(),
+            1 => "1 crate".into(),
+            n => format!("{n} crates"),
+        };
+    };
+
+    let Some(package) = manifest
+        .get("package")
+        .and_then(|package| package.as_table())
+    else {
+        return if manifest.contains_key("workspace") {
+            "workspace".into()
+        } else {
+            String::new()
+        };
+    };
+
+    let name = package
+        .get("name")
+        .and_then(|name| name.as_str())
+        .unwrap_or("?");
+    // `version.workspace = true` inherits from the root's [workspace.package]
+    let version = match package.get("version") {
+        Some(toml::Value::String(version)) => Some(version.as_str()),
+        Some(toml::Value::Table(_)) => workspace.and_then(|root| {
+            root.get("workspace")?
+                .get("package")?
+                .get("version")?
+                .as_str()
+        }),
+        _ => None,
+    };
+
+    match version {
+        Some(version) => format!("{name} v{version}"),
+        None => name.to_string(),
+    }
+}
+
+/// The manifest of the workspace `dir` belongs to, looked up in `dir` and its
+/// parents.
+pub fn workspace_manifest(dir: &Path) -> Option<Table> {
+    let dir = dir.canonicalize().ok()?;
+    dir.ancestors()
+        .filter_map(|ancestor| read_toml(&ancestor.join("Cargo.toml")))
+        .find(|manifest| manifest.contains_key("workspace"))
+}
+
+/// A project annotation for `dir`, and whether it is a dependency or virtualenv
+/// directory rather than a project of its own.
+pub fn project_annotation(dir: &Path) -> (String, bool) {
+    let name = dir.file_name().unwrap_or_default();
+    if name == "node_modules" {
+        return ("npm dependencies".into(), true);
+    }
+    if dir.join("pyvenv.cfg").is_file() {
+        return ("python venv".into(), true);
+    }
+
+    if let Some(package) = fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+    {
+        let name = package["name"].as_str().unwrap_or("?");
+        return match package["version"].as_str() {
+            Some(version) => (format!("node: {name} v{version}"), false),
+            None => (format!("node: {name}"), false),
+        };
+    }
+
+    if let Some(pyproject) = read_toml(&dir.join("pyproject.toml")) {
+        // PEP 621 metadata, falling back to Poetry's own table
+        let project = pyproject
+            .get("project")
+            .or_else(|| pyproject.get("tool").and_then(|tool| tool.get("poetry")));
+        let field = |key: &str| project.and_then(|project| project.get(key)?.as_str());
+        let name = field("name").unwrap_or("?");
+        return match field("version") {
+            Some(version) => (format!("python: {name} v{version}"), false),
+            None => (format!("python: {name}"), false),
+        };
+    }
+
+    if dir.join("Cargo.toml").is_file() {
+        let workspace = workspace_manifest(dir);
+        let annotation = cargo_annotation(dir, workspace.as_ref());
+        return (format!("rust: {annotation}"), false);
+    }
+
+    (String::new(), false)
+}
@@ -0,0 +1,104 @@
+//! `ds verify-readonly`: entries of a tree that anyone besides their owner may
+//! write to, through group or other write bits or an ACL, to check backup
+//! archives are write-protected.
+
+use crate::color::ColorScheme;
+use crate::effective::{ACL_GROUP, ACL_GROUP_OBJ, ACL_MASK, ACL_USER, acl};
+use ds::{DirLister, Entry, escape_control, permission_string};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tabled::{
+    Table, Tabled,
+    settings::{Style, object::Columns, object::Rows},
+};
+use users::{get_group_by_gid, get_user_by_uid};
+
+#[derive(Debug, Tabled)]
+struct Violation {
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Mode")]
+    mode: String,
+    #[tabled(rename = "Writable By")]
+    writer: String,
+}
+
+/// Who besides the owner may write to `path` with `mode`. With an access ACL
+/// the group bits of the mode are its mask, so the owning group and the users
+/// and groups it names are looked up there, each limited by the mask.
+fn writers(path: &Path, mode: u32) -> Vec<String> {
+    let mut writers = Vec::new();
+    let entries = acl(path, c"system.posix_acl_access").unwrap_or_default();
+    let mask = entries
+        .iter()
+        .find(|(tag, ..)| *tag == ACL_MASK)
+        .map_or(0o7, |(_, perm, _)| *perm);
+    let group = match entries.iter().find(|(tag, ..)| *tag == ACL_GROUP_OBJ) {
+        Some((_, perm, _)) => perm & mask,
+        None => mode >> 3,
+    };
+    if group & 0o2 != 0 {
+        writers.push("group".to_string());
+    }
+    if mode & 0o002 != 0 {
+        writers.push("others".to_string());
+    }
+    for (tag, perm, id) in &entries {
+        if perm & mask & 0o2 == 0 {
+            continue;
+        }
+        match *tag {
+            ACL_USER => writers.push(format!(
+                "user {} (ACL)",
+                get_user_by_uid(*id)
+                    .map(|user| user.name().to_string_lossy().into_owned())
+                    .unwrap_or_else(|| id.to_string())
+            )),
+            ACL_GROUP => writers.push(format!(
+                "group {} (ACL)",
+                get_group_by_gid(*id)
+                    .map(|group| group.name().to_string_lossy().into_owned())
+                    .unwrap_or_else(|| id.to_string())
+            )),
+            _ => {}
+        }
+    }
+    writers
+}
+
+/// A table of the entries under `path` someone besides their owner can write
+/// to, None when there are none. Symlinks are skipped, their own mode is
+/// meaningless.
+pub fn verify(path: &Path, show_hidden: bool, colors: &ColorScheme) -> Option<Table> {
+    let lister = DirLister {
+        all: show_hidden,
+        ..DirLister::default()
+    };
+    let mut violations = Vec::new();
+    for (_, Entry { path: file, meta }) in lister.recursive(lister.list(path), usize::MAX) {
+        if meta.file_type().is_symlink() {
+            continue;
+        }
+        let mode = meta.permissions().mode() & 0o7777;
+        let writers = writers(&file, mode);
+        if writers.is_empty() {
+            continue;
+        }
+        let kind = if meta.is_dir() { 'd' } else { '-' };
+        violations.push(Violation {
+            path: escape_control(&file.strip_prefix(path).unwrap_or(&file).to_string_lossy()),
+            mode: format!("{} ({:03o})", permission_string(kind, mode), mode),
+            writer: writers.join(", "),
+        });
+    }
+
+    if violations.is_empty() {
+        return None;
+    }
+    let mut table = Table::new(violations);
+    table.with(Style::empty());
+    table.modify(Columns::one(1), colors.permission.clone());
+    table.modify(Columns::one(2), colors.alert.clone());
+    table.modify(Rows::first(), colors.dim.clone());
+    Some(table)
+}
@@ -0,0 +1,139 @@
+//! Detection of extents shared between files (reflinks / copy-on-write clones).
+//!
+//! Extents are read with the `FS_IOC_FIEMAP` ioctl. Only extents the file system
+//! flags as shared are considered, then the listed files are matched against each
+//! other by overlapping physical ranges.
+
+use std::fs::File;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+
+const FS_IOC_FIEMAP: u64 = 0xC020_660B;
+const FIEMAP_FLAG_SYNC: u32 = 0x1;
+const FIEMAP_EXTENT_LAST: u32 = 0x1;
+const FIEMAP_EXTENT_SHARED: u32 = 0x2000;
+
+// Extents requested per ioctl call
+const BATCH: usize = 128;
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct FiemapExtent {
+    logical: u64,
+    physical: u64,
+    length: u64,
+    reserved64: [u64; 2],
+    flags: u32,
+    reserved: [u32; 3],
+}
+
+#[repr(C)]
+struct Fiemap {
+    start: u64,
+    length: u64,
+    flags: u32,
+    mapped_extents: u32,
+    extent_count: u32,
+    reserved: u32,
+    extents: [FiemapExtent; BATCH],
+}
+
+/// Physical `(start, length)` ranges of the file's shared extents.
+fn shared_extents(path: &Path) -> Vec<(u64, u64)> {
+    let Ok(file) = File::open(path) else {
+        return Vec::new();
+    };
+
+    let mut shared = Vec::new();
+    let mut map = Box::new(Fiemap {
+        start: 0,
+        length: u64::MAX,
+        flags: FIEMAP_FLAG_SYNC,
+        mapped_extents: 0,
+        extent_count: BATCH as u32,
+        reserved: 0,
+        extents: [FiemapExtent::default(); BATCH],
+    });
+
+    loop {
+        // SAFETY: `map` is a properly laid out `struct fiemap` with room for
+        // `extent_count` extents, as the ioctl expects.
+        let result = unsafe { libc::ioctl(file.as_raw_fd(), FS_IOC_FIEMAP as _, &mut *map) };
+        if result != 0 || map.mapped_extents == 0 {
+            break;
+        }
+
+        let extents = &map.extents[..map.mapped_extents as usize];
+        shared.extend(
+            extents
+                .iter()
+                .filter(|extent| extent.flags & FIEMAP_EXTENT_SHARED != 0)
+                .map(|extent| (extent.physical, extent.length)),
+        );
+
+        let last = extents[extents.len() - 1];
+        if last.flags & FIEMAP_EXTENT_LAST != 0 {
+            break;
+        }
+        map.start = last.logical + last.length;
+        map.mapped_extents = 0;
+    }
+
+    shared
+}
+
+/// One annotation per path: the names of the other listed files sharing extents
+/// with it, "shared" when its extents are only shared outside the listing, or
+/// nothing.
+pub fn annotations(paths: &[PathBuf]) -> Vec<String> {
+    let mut ranges: Vec<(u64, u64, usize)> = paths
+        .iter()
+        .enumerate()
+        .filter(|(_, path)| path.is_file())
+        .flat_map(|(index, path)| {
+            shared_extents(path)
+                .into_iter()
+                .map(move |(start, length)| (start, start + length, index))
+        })
+        .collect();
+    ranges.sort_unstable();
+
+    let mut partners: Vec<Vec<usize>> = vec![Vec::new(); paths.len()];
+    for (i, &(_, end, index)) in ranges.iter().enumerate() {
+        for &(other_start, _, other) in &ranges[i + 1..] {
+            if other_start >= end {
+                break;
+            }
+            if other != index {
+                partners[index].push(other);
+                partners[other].push(index);
+            }
+        }
+    }
+
+    let mut has_shared = vec![false; paths.len()];
+    for &(.., index) in &ranges {
+        has_shared[index] = true;
+    }
+
+    partners
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut others)| {
+            others.sort_unstable();
+            others.dedup();
+            if !others.is_empty() {
+                let names: Vec<String> = others
+                    .into_iter()
+                    .filter_map(|other| paths[other].file_name())
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .collect();
+                format!("⇄ {}", names.join(", "))
+            } else if has_shared[index] {
+                "shared".to_string()
+            } else {
+                String::new()
+            }
+        })
+        .collect()
+}
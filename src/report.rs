@@ -0,0 +1,229 @@
+//! `ds report`: a self-contained HTML page of a directory's listing, with
+//! totals, charts of the largest entries and of the space each type takes,
+//! and a table sortable by any column, for sharing with people who don't
+//! live in a terminal.
+
+use ds::{Binary, Row, Types, Units, human_readable_size};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+// How many bars each chart shows
+const CHART_ENTRIES: usize = 10;
+
+fn bytes_of(binary: &Binary) -> u64 {
+    binary.size.parse::<u64>().unwrap_or(0)
+}
+
+/// Bars of `label`, bytes and the value shown beside, the longest for the
+/// most bytes.
+fn chart(bars: &[(String, u64, String)]) -> String {
+    let max_bytes = bars
+        .iter()
+        .map(|(_, bytes, _)| *bytes)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut chart = String::new();
+    for (label, bytes, value) in bars {
+        let percent = *bytes as f64 / max_bytes as f64 * 100.0;
+        let _ = writeln!(
+            chart,
+            "<div class=\"bar\"><span class=\"label\">{}</span>\
+             <span class=\"fill\" style=\"width:{:.1}%\"></span>\
+             <span class=\"value\">{}</span></div>",
+            escape(label),
+            percent,
+            escape(value),
+        );
+    }
+    chart
+}
+
+/// What an entry is counted as in the chart by type: its extension for
+/// files, its kind for the rest, like symlinks.
+fn kind(basic: &ds::Basic) -> String {
+    match basic.types {
+        Types::File => match Path::new(&basic.name).extension() {
+            Some(extension) => format!(".{}", extension.to_string_lossy().to_lowercase()),
+            None => "no extension".to_string(),
+        },
+        ref other => other.to_string(),
+    }
+}
+
+pub fn render(path: &Path, files: &[Row], units: Units) -> String {
+    let dirs = files
+        .iter()
+        .filter(|(basic, ..)| matches!(basic.types, Types::Dir))
+        .count();
+    // What directories take themselves isn't space of their files
+    let non_dirs: Vec<&Row> = files
+        .iter()
+        .filter(|(basic, ..)| !matches!(basic.types, Types::Dir))
+        .collect();
+    let total_bytes: u64 = non_dirs
+        .iter()
+        .map(|(_, _, binary, ..)| bytes_of(binary))
+        .sum();
+
+    let mut largest = non_dirs.clone();
+    largest.sort_by_key(|(_, _, binary, ..)| std::cmp::Reverse(bytes_of(binary)));
+    largest.truncate(CHART_ENTRIES);
+    let largest: Vec<(String, u64, String)> = largest
+        .into_iter()
+        .map(|(basic, size, binary, ..)| (basic.name.clone(), bytes_of(binary), size.size.clone()))
+        .collect();
+
+    let mut types: HashMap<String, (u64, usize)> = HashMap::new();
+    for (basic, _, binary, ..) in &non_dirs {
+        let (bytes, count) = types.entry(kind(basic)).or_default();
+        *bytes += bytes_of(binary);
+        *count += 1;
+    }
+    let mut types: Vec<(String, (u64, usize))> = types.into_iter().collect();
+    types.sort_by(|(a, (a_bytes, _)), (b, (b_bytes, _))| b_bytes.cmp(a_bytes).then(a.cmp(b)));
+    types.truncate(CHART_ENTRIES);
+    let types: Vec<(String, u64, String)> = types
+        .into_iter()
+        .map(|(kind, (bytes, count))| {
+            let value = format!("{} in {count}", human_readable_size(bytes, units));
+            (kind, bytes, value)
+        })
+        .collect();
+
+    let mut rows = String::new();
+    for (basic, size, binary, group_and_owner, mac, permission) in files {
+        let _ = writeln!(
+            rows,
+            "<tr><td>{}</td><td>{}</td><td data-sort=\"{}\">{}</td><td>{}</td>\
+             <td>{}</td><td data-date>{}</td><td><code>{}</code></td></tr>",
+            escape(&basic.name),
+            basic.types,
+            bytes_of(binary),
+            escape(&size.size),
+            escape(&group_and_owner.owner),
+            escape(&group_and_owner.group),
+            escape(&mac.modified),
+            permission.permission,
+        );
+    }
+
+    fill(
+        TEMPLATE,
+        &[
+            ("title", escape(&path.display().to_string())),
+            (
+                "generated",
+                chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+            ),
+            ("entries", files.len().to_string()),
+            ("files", (files.len() - dirs).to_string()),
+            ("dirs", dirs.to_string()),
+            ("total", human_readable_size(total_bytes, units)),
+            ("units", units.to_string()),
+            ("largest", chart(&largest)),
+            ("types", chart(&types)),
+            ("rows", rows),
+        ],
+    )
+}
+
+/// `template` with each `{name}` of `values` replaced in one pass, so what's
+/// put in is never replaced again, like a file named `{rows}`. Other braces,
+/// like those of the CSS, are kept.
+fn fill(template: &str, values: &[(&str, String)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            let (_, value) = values.iter().find(|(name, _)| *name == &rest[1..end])?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                text.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const TEMPLATE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>ds report: {title}</title>
+<style>
+  body { font-family: sans-serif; margin: 2em; color: #222; }
+  h1 { font-size: 1.4em; }
+  .meta { color: #777; }
+  .totals span { display: inline-block; margin-right: 2em; }
+  .bar { display: flex; align-items: center; margin: 2px 0; }
+  .bar .label { width: 16em; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+  .bar .fill { background: #b05fd0; height: 1em; margin: 0 0.5em; }
+  .bar .value { color: #777; }
+  table { border-collapse: collapse; margin-top: 1em; }
+  th { cursor: pointer; text-align: left; color: #777; user-select: none; }
+  th, td { padding: 2px 1em 2px 0; }
+  tr:hover td { background: #f3f3f3; }
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p class="meta">Generated by ds on {generated}</p>
+<p class="totals">
+  <span><b>{entries}</b> entries</span>
+  <span><b>{files}</b> files</span>
+  <span><b>{dirs}</b> directories</span>
+  <span><b>{total}</b> total</span>
+</p>
+<h2>Largest entries</h2>
+{largest}
+<h2>Space by type</h2>
+{types}
+<h2>Listing</h2>
+<table id="listing">
+<thead><tr><th>Name</th><th>Type</th><th>Size ({units})</th><th>Owner</th><th>Group</th><th>Date Modified</th><th>Permission</th></tr></thead>
+<tbody>
+{rows}</tbody>
+</table>
+<script>
+  // Click a header to sort by that column, click again to reverse
+  document.querySelectorAll("#listing th").forEach((th, column) => {
+    let ascending = true;
+    th.addEventListener("click", () => {
+      const body = document.querySelector("#listing tbody");
+      const key = (row) => {
+        const cell = row.children[column];
+        if (cell.dataset.sort !== undefined) return Number(cell.dataset.sort);
+        if (cell.dataset.date !== undefined) return Date.parse(cell.textContent) || 0;
+        return cell.textContent.toLowerCase();
+      };
+      const rows = Array.from(body.rows).sort((a, b) => {
+        const [x, y] = [key(a), key(b)];
+        return (x < y ? -1 : x > y ? 1 : 0) * (ascending ? 1 : -1);
+      });
+      ascending = !ascending;
+      rows.forEach((row) => body.appendChild(row));
+    });
+  });
+</script>
+</body>
+</html>
+"##;
@@ -0,0 +1,150 @@
+//! `ds retention --keep 30d`: the files of a tree a retention policy would
+//! delete, previewed as a table and only deleted with `--apply` once confirmed.
+
+use crate::color::ColorScheme;
+use chrono::{DateTime, Local};
+use ds::{DirLister, Units, escape_control, human_readable_size};
+use glob::Pattern;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use tabled::{
+    Table, Tabled,
+    settings::{Style, object::Columns, object::Rows},
+};
+
+#[derive(Tabled)]
+struct Doomed {
+    #[tabled(rename = "File")]
+    file: String,
+    #[tabled(rename = "Date Modified")]
+    modified: String,
+    #[tabled(rename = "Age")]
+    age: String,
+    #[tabled(rename = "Size")]
+    size: String,
+}
+
+pub struct Policy {
+    /// Files modified before this are deleted
+    pub cutoff: DateTime<Local>,
+    /// The newest files of each pattern are kept however old they are
+    pub keep_latest: usize,
+    /// Only files whose names match these are considered, each pattern
+    /// keeping its own latest files. No patterns means every file, as one.
+    pub patterns: Vec<Pattern>,
+}
+
+/// The files under `path` the policy deletes, oldest first.
+fn doomed(path: &Path, policy: &Policy, show_hidden: bool) -> Vec<(PathBuf, DateTime<Local>, u64)> {
+    let lister = DirLister {
+        all: show_hidden,
+        ..DirLister::default()
+    };
+    let patterns = match policy.patterns.is_empty() {
+        true => vec![Pattern::new("*").expect("'*' is a valid pattern")],
+        false => policy.patterns.clone(),
+    };
+    // Files of each pattern, as (path, modified, bytes)
+    let mut groups: Vec<Vec<(PathBuf, DateTime<Local>, u64)>> = vec![Vec::new(); patterns.len()];
+    for (_, entry) in lister.recursive(lister.list(path), usize::MAX) {
+        let Ok(modified) = entry.meta.modified() else {
+            continue;
+        };
+        if !entry.meta.is_file() {
+            continue;
+        }
+        let name = entry.name().to_string_lossy().into_owned();
+        // A file belongs to the first pattern it matches
+        if let Some(group) = patterns.iter().position(|pattern| pattern.matches(&name)) {
+            groups[group].push((entry.path, modified.into(), entry.meta.len()));
+        }
+    }
+
+    let mut doomed: Vec<(PathBuf, DateTime<Local>, u64)> = groups
+        .into_iter()
+        .flat_map(|mut files| {
+            files.sort_by_key(|(_, modified, _)| std::cmp::Reverse(*modified));
+            files
+                .into_iter()
+                .skip(policy.keep_latest)
+                .filter(|(_, modified, _)| *modified < policy.cutoff)
+        })
+        .collect();
+    doomed.sort_by_key(|(_, modified, _)| *modified);
+    doomed
+}
+
+pub fn run(
+    path: &Path,
+    policy: &Policy,
+    show_hidden: bool,
+    apply: bool,
+    units: Units,
+    colors: &ColorScheme,
+) -> Result<(), String> {
+    if !path.is_dir() {
+        return Err(format!("'{}' is not a directory", path.display()));
+    }
+    let interrupts = crate::catch_interrupts();
+    let doomed = doomed(path, policy, show_hidden);
+    drop(interrupts);
+    if doomed.is_empty() {
+        println!("Nothing is older than the policy keeps.");
+        return Ok(());
+    }
+
+    let now = Local::now();
+    let rows = doomed.iter().map(|(file, modified, bytes)| Doomed {
+        file: escape_control(&file.strip_prefix(path).unwrap_or(file).to_string_lossy()),
+        modified: modified.format("%Y-%m-%d %H:%M").to_string(),
+        age: format!("{}d", (now - *modified).num_days()),
+        size: human_readable_size(*bytes, units),
+    });
+    let mut table = Table::new(rows);
+    table.with(Style::empty());
+    table.modify(Columns::one(1), colors.time.clone());
+    table.modify(Columns::one(2), colors.alert.clone());
+    table.modify(Columns::last(), colors.size.clone());
+    table.modify(Rows::first(), colors.dim.clone());
+    println!("{table}");
+    let total: u64 = doomed.iter().map(|(_, _, bytes)| bytes).sum();
+    println!(
+        "{} files, {} would be deleted.",
+        doomed.len(),
+        human_readable_size(total, units)
+    );
+
+    // Nothing is deleted after a scan cut short
+    if !apply || ds::interrupted() {
+        return Ok(());
+    }
+    print!("Delete {} files? [y/N] ", doomed.len());
+    io::stdout().flush().map_err(|err| err.to_string())?;
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|err| err.to_string())?;
+    if !matches!(answer.trim(), "y" | "Y" | "yes") {
+        println!("Nothing deleted.");
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for (file, ..) in &doomed {
+        if let Err(err) = fs::remove_file(file) {
+            println!(
+                "{}",
+                colors.alert.colorize(format!("{}: {err}", file.display()))
+            );
+            failed += 1;
+        }
+    }
+    println!(
+        "Deleted {} of {} files.",
+        doomed.len() - failed,
+        doomed.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,270 @@
+//! Read-only HTTP server for browsing directories remotely.
+//!
+//! * `GET /` serves a small HTML browser.
+//! * `GET /api/list?path=sub/dir` returns the entries of `sub/dir` (relative to
+//!   the served root) as JSON. Paths escaping the root are refused.
+//!
+//! With several directories, each is a root of its own under its name, and
+//! `path=` lists them.
+
+use ds::{DirLister, Entry, SortField, entry_type, group_and_owner_mode, permission_mode};
+use serde_json::{Value, json};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use tiny_http::{Header, Response, Server};
+
+/// The directories served, by the name they're reached with; a single one
+/// has no name and is reached directly.
+struct Roots(Vec<(String, PathBuf)>);
+
+impl Roots {
+    fn new(dirs: &[PathBuf]) -> Result<Roots, String> {
+        let mut roots: Vec<(String, PathBuf)> = Vec::new();
+        for dir in dirs {
+            let dir = dir
+                .canonicalize()
+                .map_err(|err| format!("{}: {err}", dir.display()))?;
+            let name = match dirs.len() {
+                1 => String::new(),
+                _ => dir
+                    .file_name()
+                    .unwrap_or(dir.as_os_str())
+                    .to_string_lossy()
+                    .into_owned(),
+            };
+            if roots.iter().any(|(other, _)| *other == name) {
+                return Err(format!("two of the directories are named '{name}'"));
+            }
+            roots.push((name, dir));
+        }
+        Ok(Roots(roots))
+    }
+
+    /// The root `relative` is in, and the path inside it.
+    fn find<'a>(&self, relative: &'a str) -> Option<(&Path, &'a str)> {
+        let relative = relative.trim_start_matches('/');
+        if let [(_, root)] = self.0.as_slice() {
+            return Some((root, relative));
+        }
+        let (name, inside) = relative.split_once('/').unwrap_or((relative, ""));
+        let (_, root) = self.0.iter().find(|(root, _)| root == name)?;
+        Some((root, inside))
+    }
+
+    /// The served directories, as entries of a directory above them.
+    fn list(&self) -> Value {
+        self.0
+            .iter()
+            .map(|(name, root)| {
+                json!({
+                    "name": name,
+                    "type": "dir",
+                    "directory": true,
+                    "size": 0,
+                    "modified": root.metadata().map(|meta| meta.mtime()).unwrap_or(0),
+                    "permission": "",
+                    "owner": "",
+                    "group": "",
+                })
+            })
+            .collect()
+    }
+}
+
+pub fn serve(dirs: &[PathBuf], listen: &str, show_hidden: bool) -> Result<(), String> {
+    let roots = Roots::new(dirs)?;
+    let server = Server::http(listen).map_err(|err| err.to_string())?;
+    for (_, root) in &roots.0 {
+        println!("Serving {} on http://{}", root.display(), listen);
+    }
+
+    for request in server.incoming_requests() {
+        let url = request.url().to_string();
+        let (route, query) = url.split_once('?').unwrap_or((&url, ""));
+
+        let response = match route {
+            "/" => Response::from_string(PAGE).with_header(content_type("text/html")),
+            "/api/list" => {
+                let relative = query
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix("path="))
+                    .map(percent_decode)
+                    .unwrap_or_default();
+                let found = roots.find(&relative);
+                // The served directories themselves may be hidden
+                let hidden = found
+                    .is_some_and(|(_, inside)| inside.split('/').any(|part| part.starts_with('.')));
+                let dir = found.and_then(|(root, inside)| resolve(root, inside));
+                match dir.filter(|_| show_hidden || !hidden) {
+                    _ if roots.0.len() > 1 && relative.trim_matches('/').is_empty() => {
+                        Response::from_string(roots.list().to_string())
+                            .with_header(content_type("application/json"))
+                    }
+                    Some(dir) if dir.is_dir() => {
+                        Response::from_string(list(&dir, show_hidden).to_string())
+                            .with_header(content_type("application/json"))
+                    }
+                    Some(_) => Response::from_string("not a directory").with_status_code(404),
+                    None => Response::from_string("forbidden").with_status_code(403),
+                }
+            }
+            _ => Response::from_string("not found").with_status_code(404),
+        };
+        let _ = request.respond(response);
+    }
+    Ok(())
+}
+
+/// `relative` inside `root`, or `None` when it would leave the served tree.
+/// Symlinks are resolved first, so they can't be used to escape either.
+fn resolve(root: &Path, relative: &str) -> Option<PathBuf> {
+    let path = root
+        .join(relative.trim_start_matches('/'))
+        .canonicalize()
+        .ok()?;
+    path.starts_with(root).then_some(path)
+}
+
+fn list(dir: &Path, show_hidden: bool) -> Value {
+    let lister = DirLister {
+        all: show_hidden,
+        sort: SortField::FileType,
+        ..DirLister::default()
+    };
+    let entries = lister.list(dir);
+    entries
+        .iter()
+        .map(|Entry { path, meta }| {
+            let group_and_owner = group_and_owner_mode(meta);
+            json!({
+                "name": path.file_name().unwrap_or_default().to_string_lossy(),
+                "type": entry_type(meta).to_string().to_lowercase(),
+                // Links to directories can be entered too
+                "directory": path.is_dir(),
+                "size": meta.len(),
+                "modified": meta.mtime(),
+                "permission": permission_mode(meta).permission,
+                "owner": group_and_owner.owner,
+                "group": group_and_owner.group,
+            })
+        })
+        .collect()
+}
+
+fn content_type(value: &str) -> Header {
+    Header::from_bytes("Content-Type", format!("{value}; charset=utf-8"))
+        .expect("static header is valid")
+}
+
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(if bytes[i] == b'+' { b' ' } else { bytes[i] });
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// `text` with what isn't allowed in a query string as is escaped, the other
+/// way round from `percent_decode`.
+pub fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+const PAGE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>ds</title>
+<style>
+  body { font-family: sans-serif; margin: 2em; color: #222; }
+  a { color: #8e44ad; text-decoration: none; cursor: pointer; }
+  table { border-collapse: collapse; margin-top: 1em; }
+  th { text-align: left; color: #777; }
+  th, td { padding: 2px 1.5em 2px 0; }
+  td.size { text-align: right; }
+</style>
+</head>
+<body>
+<h1 id="path"></h1>
+<table>
+<thead><tr><th>Name</th><th>Type</th><th>Size</th><th>Owner</th><th>Date Modified</th><th>Permission</th></tr></thead>
+<tbody id="entries"></tbody>
+</table>
+<script>
+  const units = ["B", "K", "M", "G", "T", "P"];
+  const human = (bytes) => {
+    let i = 0;
+    while (bytes >= 1024 && i < units.length - 1) { bytes /= 1024; i++; }
+    return (bytes >= 10 || i === 0 ? bytes.toFixed(0) : bytes.toFixed(1)) + units[i];
+  };
+  const cell = (row, text, className) => {
+    const td = row.insertCell();
+    td.textContent = text;
+    if (className) td.className = className;
+    return td;
+  };
+
+  async function show() {
+    const path = decodeURIComponent(location.hash.slice(1));
+    document.getElementById("path").textContent = "/" + path;
+    const response = await fetch("/api/list?path=" + encodeURIComponent(path));
+    const body = document.getElementById("entries");
+    body.replaceChildren();
+    if (!response.ok) {
+      cell(body.insertRow(), await response.text());
+      return;
+    }
+    if (path) {
+      const up = cell(body.insertRow(), "");
+      up.innerHTML = "<a>..</a>";
+      up.firstChild.href = "#" + encodeURIComponent(path.split("/").slice(0, -1).join("/"));
+    }
+    for (const entry of await response.json()) {
+      const row = body.insertRow();
+      const name = cell(row, "");
+      if (entry.directory) {
+        const link = document.createElement("a");
+        link.textContent = entry.name + "/";
+        link.href = "#" + encodeURIComponent(path ? path + "/" + entry.name : entry.name);
+        name.appendChild(link);
+      } else {
+        name.textContent = entry.name;
+      }
+      cell(row, entry.type);
+      cell(row, human(entry.size), "size");
+      cell(row, entry.owner + ":" + entry.group);
+      cell(row, new Date(entry.modified * 1000).toDateString());
+      cell(row, entry.permission);
+    }
+  }
+
+  window.addEventListener("hashchange", show);
+  show();
+</script>
+</body>
+</html>
+"##;
@@ -0,0 +1,70 @@
+//! `--age-spark`: a sparkline of how old the files below each directory are,
+//! from the oldest bucket on the left to the newest on the right, to spot
+//! stale subtrees.
+
+use ds::{DirLister, Entry};
+use std::time::{Duration, SystemTime};
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+const DAY: u64 = 24 * 60 * 60;
+/// Upper age bounds of the buckets, newest first; older files go in a last one
+const BUCKETS: [u64; 7] = [
+    DAY,
+    7 * DAY,
+    30 * DAY,
+    91 * DAY,
+    182 * DAY,
+    365 * DAY,
+    730 * DAY,
+];
+
+pub const HEADER: &str = "File Ages (2y+ → 1d)";
+
+/// Which bucket a file modified at `modified` falls in, newest first.
+fn bucket(now: SystemTime, modified: SystemTime) -> usize {
+    let age = now.duration_since(modified).unwrap_or(Duration::ZERO);
+    BUCKETS
+        .iter()
+        .position(|bound| age.as_secs() < *bound)
+        .unwrap_or(BUCKETS.len())
+}
+
+fn sparkline(counts: &[usize]) -> String {
+    let most = counts.iter().copied().max().unwrap_or(0);
+    if most == 0 {
+        return String::new();
+    }
+    // Oldest on the left; empty buckets stay blank so gaps show
+    counts
+        .iter()
+        .rev()
+        .map(|count| match count {
+            0 => ' ',
+            count => BARS[(count * BARS.len()).div_ceil(most) - 1],
+        })
+        .collect()
+}
+
+pub fn annotations(entries: &[Entry], show_hidden: bool) -> Vec<String> {
+    let lister = DirLister {
+        all: show_hidden,
+        ..DirLister::default()
+    };
+    let now = SystemTime::now();
+    entries
+        .iter()
+        .map(|entry| {
+            if !entry.meta.is_dir() {
+                return String::new();
+            }
+            let mut counts = [0; BUCKETS.len() + 1];
+            for (_, Entry { meta, .. }) in lister.recursive(lister.list(&entry.path), usize::MAX) {
+                if let (true, Ok(modified)) = (meta.is_file(), meta.modified()) {
+                    counts[bucket(now, modified)] += 1;
+                }
+            }
+            sparkline(&counts)
+        })
+        .collect()
+}
@@ -0,0 +1,122 @@
+//! `--stats-json`: what a listing scanned and how long it took, as one JSON
+//! object on stderr whatever stdout gets, for wrappers in cron jobs and
+//! monitoring.
+
+use ds::{ReadErrors, Row, Types, Units, human_readable_size, interrupted};
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+#[derive(Default, Clone, Copy)]
+struct Counts {
+    entries: usize,
+    dirs: usize,
+    files: usize,
+    symlinks: usize,
+    other: usize,
+    bytes: u64,
+}
+
+/// Collects the statistics while listing and prints them when dropped, so
+/// every way out of the listing reports them.
+pub struct Stats {
+    enabled: bool,
+    start: Instant,
+    /// How long reading the entries took, once they are read
+    scan: Option<Duration>,
+    /// What every listed path added up to, once one is read
+    counts: Option<Counts>,
+    /// What the listing couldn't read
+    errors: ReadErrors,
+}
+
+impl Stats {
+    pub fn start(enabled: bool, errors: &ReadErrors) -> Stats {
+        Stats {
+            enabled,
+            start: Instant::now(),
+            scan: None,
+            counts: None,
+            errors: errors.clone(),
+        }
+    }
+
+    /// Mark the end of a scan, which read `rows`. Listing several paths adds
+    /// them up.
+    pub fn scanned(&mut self, rows: &[Row]) {
+        self.scan = Some(self.start.elapsed());
+        let counts = self.counts.get_or_insert_default();
+        for (basic, _, binary, ..) in rows {
+            counts.entries += 1;
+            match basic.types {
+                Types::Dir => counts.dirs += 1,
+                Types::File => {
+                    counts.files += 1;
+                    counts.bytes += binary.size.parse::<u64>().unwrap_or(0);
+                }
+                Types::Symlink => counts.symlinks += 1,
+                _ => counts.other += 1,
+            }
+        }
+    }
+
+    /// One line adding up everything listed, like
+    /// `12 entries (3 dirs, 9 files), 1.2K in 2 paths, 1 path without access`.
+    pub fn summary(&self, paths: usize, units: Units) -> String {
+        let counts = self.counts.unwrap_or_default();
+        let count = |count: usize, one: &str, many: &str| match count {
+            1 => format!("1 {one}"),
+            count => format!("{count} {many}"),
+        };
+        let mut summary = format!(
+            "{} ({}, {}), {} in {}",
+            count(counts.entries, "entry", "entries"),
+            count(counts.dirs, "dir", "dirs"),
+            count(counts.files, "file", "files"),
+            human_readable_size(counts.bytes, units),
+            count(paths, "path", "paths"),
+        );
+        // What's below them is missing from the counts
+        let denied = self.errors.denied().len();
+        if denied > 0 {
+            summary.push_str(&format!(
+                ", {} without access",
+                count(denied, "path", "paths")
+            ));
+        }
+        summary
+    }
+}
+
+impl Drop for Stats {
+    fn drop(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        let total = self.start.elapsed();
+        let milliseconds = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let errors: Vec<Value> = self
+            .errors
+            .all()
+            .into_iter()
+            .map(|(path, error)| json!({ "path": path.to_string_lossy(), "error": error }))
+            .collect();
+        let stats = json!({
+            "counts": self.counts.as_ref().map_or(json!({}), |counts| json!({
+                "entries": counts.entries,
+                "dirs": counts.dirs,
+                "files": counts.files,
+                "symlinks": counts.symlinks,
+                "other": counts.other,
+                "bytes": counts.bytes,
+            })),
+            "durations_ms": {
+                "scan": self.scan.map(milliseconds),
+                "render": self.scan.map(|scan| milliseconds(total - scan)),
+                "total": milliseconds(total),
+            },
+            "errors": errors,
+            "interrupted": interrupted(),
+        });
+        eprintln!("{stats}");
+    }
+}
@@ -0,0 +1,100 @@
+//! Enabled and active state of systemd unit files, asked of systemctl.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const UNIT_TYPES: [&str; 11] = [
+    "service",
+    "socket",
+    "timer",
+    "target",
+    "path",
+    "mount",
+    "automount",
+    "swap",
+    "slice",
+    "scope",
+    "device",
+];
+
+/// The unit name of a file, unless it isn't one or is a template like
+/// `getty@.service`, which has no state of its own.
+fn unit_name(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy();
+    let (stem, kind) = name.rsplit_once('.')?;
+    (UNIT_TYPES.contains(&kind) && !stem.is_empty() && !stem.ends_with('@'))
+        .then(|| name.to_string())
+}
+
+/// Units under a `systemd/user` directory belong to the user manager.
+fn is_user_dir(path: &Path) -> bool {
+    let components: Vec<_> = path.components().map(|part| part.as_os_str()).collect();
+    components
+        .windows(2)
+        .any(|pair| pair[0] == "systemd" && pair[1] == "user")
+}
+
+/// One annotation per path: "enabled, active" and the like for unit files,
+/// nothing for other files, and whether the unit failed.
+pub fn annotations(paths: &[PathBuf]) -> Result<Vec<(String, bool)>, String> {
+    let names: Vec<Option<String>> = paths.iter().map(|path| unit_name(path)).collect();
+    let queried: Vec<&String> = names.iter().flatten().collect();
+    if queried.is_empty() {
+        return Ok(vec![(String::new(), false); paths.len()]);
+    }
+
+    let mut command = Command::new("systemctl");
+    if paths
+        .first()
+        .and_then(|path| path.parent())
+        .is_some_and(is_user_dir)
+    {
+        command.arg("--user");
+    }
+    let output = command
+        .args([
+            "show",
+            "--property=LoadState,UnitFileState,ActiveState",
+            "--",
+        ])
+        .args(&queried)
+        .output()
+        .map_err(|err| format!("can't run systemctl: {err}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr
+            .lines()
+            .last()
+            .unwrap_or("systemctl failed")
+            .to_string());
+    }
+
+    // One block of `Property=value` lines per unit, in the order asked
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut states = stdout.split("\n\n").map(|block| {
+        let property = |name: &str| {
+            block
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+                .unwrap_or_default()
+        };
+        match (
+            property("LoadState"),
+            property("UnitFileState"),
+            property("ActiveState"),
+        ) {
+            ("not-found", _, _) => ("not loaded".to_string(), false),
+            ("masked", _, _) => ("masked".to_string(), false),
+            (_, "", active) => (active.to_string(), active == "failed"),
+            (_, enabled, active) => (format!("{enabled}, {active}"), active == "failed"),
+        }
+    });
+
+    Ok(names
+        .iter()
+        .map(|name| match name {
+            Some(_) => states.next().unwrap_or_default(),
+            None => (String::new(), false),
+        })
+        .collect())
+}
@@ -0,0 +1,260 @@
+//! `[theme]` in config.toml: colors of the columns, and of names by file type
+//! or suffix on top of `LS_COLORS`. Colors are written like `bold blue`,
+//! `bright-red`, `#e69f00` or `208`.
+//!
+//! ```toml
+//! [theme]
+//! size = "bright-yellow"
+//! time = "#e69f00"
+//! alert = "bold red"
+//!
+//! [theme.names]
+//! dir = "bold blue"
+//! executable = "green"
+//! "*.rs" = "#dea584"
+//! ```
+//!
+//! `ds theme preview` shows them on a made-up listing with every kind of file.
+
+use crate::color::{ColorScheme, LsColors, sgr};
+use crate::config;
+use ds::{Column, Types, permission_string};
+use tabled::settings::Color;
+
+/// Name keys and the `LS_COLORS` keys they stand for
+const NAMES: &[(&str, &str)] = &[
+    ("file", "fi"),
+    ("dir", "di"),
+    ("symlink", "ln"),
+    ("broken", "or"),
+    ("executable", "ex"),
+    ("fifo", "pi"),
+    ("socket", "so"),
+    ("block-device", "bd"),
+    ("char-device", "cd"),
+];
+
+fn codes(key: &str, value: &toml::Value) -> Result<String, String> {
+    value
+        .as_str()
+        .and_then(sgr)
+        .ok_or_else(|| format!("invalid color for '{key}' in the theme: {value}"))
+}
+
+/// Put the colors of the theme in config.toml over `colors` and `names`.
+pub fn apply(colors: &mut ColorScheme, names: &mut Option<LsColors>) -> Result<(), String> {
+    let mut theme = config::section(&config::load()?, "theme")?;
+    let name_colors = match theme.remove("names") {
+        Some(toml::Value::Table(table)) => table,
+        Some(_) => return Err("'theme.names' in config.toml must be a table".into()),
+        None => toml::Table::new(),
+    };
+
+    for (key, value) in &theme {
+        let color = Color::new(format!("\x1b[{}m", codes(key, value)?), "\x1b[0m");
+        let column = match key.as_str() {
+            "type" => &mut colors.kind,
+            "size" => &mut colors.size,
+            "owner" => &mut colors.owner,
+            "time" => &mut colors.time,
+            "permission" => &mut colors.permission,
+            "note" => &mut colors.note,
+            "project" => &mut colors.project,
+            "crate" => &mut colors.crate_name,
+            "alert" => &mut colors.alert,
+            "dim" => &mut colors.dim,
+            _ => return Err(format!("unknown theme color '{key}'")),
+        };
+        *column = color;
+    }
+
+    if name_colors.is_empty() {
+        return Ok(());
+    }
+    // Written as LS_COLORS after the user's own, so the theme wins
+    let mut ls_colors = std::env::var("LS_COLORS").unwrap_or_default();
+    for (key, value) in &name_colors {
+        let ls_key = match key.strip_prefix('*') {
+            Some(_) => key.as_str(),
+            None => NAMES
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, ls_key)| *ls_key)
+                .ok_or_else(|| format!("unknown theme name color '{key}'"))?,
+        };
+        ls_colors.push_str(&format!(":{ls_key}={}", codes(key, value)?));
+    }
+    *names = Some(LsColors::parse(&ls_colors));
+    Ok(())
+}
+
+/// A made-up entry of the preview
+struct Sample {
+    name: &'static str,
+    kind: Types,
+    /// The type letter of `ls -l`
+    letter: char,
+    mode: u32,
+    size: &'static str,
+    /// The key of `[theme.names]` that colors the name
+    key: &'static str,
+}
+
+const SAMPLES: &[Sample] = &[
+    Sample {
+        name: "src",
+        kind: Types::Dir,
+        letter: 'd',
+        mode: 0o755,
+        size: "4.0 KiB",
+        key: "dir",
+    },
+    Sample {
+        name: "README.md",
+        kind: Types::File,
+        letter: '-',
+        mode: 0o644,
+        size: "2.1 KiB",
+        key: "file",
+    },
+    Sample {
+        name: "main.rs",
+        kind: Types::File,
+        letter: '-',
+        mode: 0o644,
+        size: "12.5 KiB",
+        key: "*.rs",
+    },
+    Sample {
+        name: "photo.jpg",
+        kind: Types::File,
+        letter: '-',
+        mode: 0o644,
+        size: "3.4 MiB",
+        key: "*.jpg",
+    },
+    Sample {
+        name: "build.sh",
+        kind: Types::File,
+        letter: '-',
+        mode: 0o755,
+        size: "512 B",
+        key: "executable",
+    },
+    Sample {
+        name: "latest",
+        kind: Types::Symlink,
+        letter: 'l',
+        mode: 0o777,
+        size: "13 B",
+        key: "symlink",
+    },
+    Sample {
+        name: "dangling",
+        kind: Types::Symlink,
+        letter: 'l',
+        mode: 0o777,
+        size: "9 B",
+        key: "broken",
+    },
+    Sample {
+        name: "events",
+        kind: Types::Fifo,
+        letter: 'p',
+        mode: 0o644,
+        size: "0 B",
+        key: "fifo",
+    },
+    Sample {
+        name: "agent.sock",
+        kind: Types::Socket,
+        letter: 's',
+        mode: 0o755,
+        size: "0 B",
+        key: "socket",
+    },
+    Sample {
+        name: "sda",
+        kind: Types::BlockDev,
+        letter: 'b',
+        mode: 0o660,
+        size: "0 B",
+        key: "block-device",
+    },
+    Sample {
+        name: "null",
+        kind: Types::CharDev,
+        letter: 'c',
+        mode: 0o666,
+        size: "0 B",
+        key: "char-device",
+    },
+];
+
+/// A listing of made-up entries of every kind in `colors` and `names`,
+/// followed by the colors that mark other things.
+pub fn preview(colors: &ColorScheme, names: &Option<LsColors>) -> String {
+    let cells = |cell: fn(&Sample) -> String| SAMPLES.iter().map(cell).collect::<Vec<_>>();
+    let broken: Vec<bool> = SAMPLES
+        .iter()
+        .map(|sample| sample.key == "broken")
+        .collect();
+    let name = Column::plain("Name", cells(|sample| sample.name.to_string()));
+    let name = match names {
+        Some(names) => name.paint(
+            &SAMPLES
+                .iter()
+                .zip(&broken)
+                .map(|(sample, broken)| {
+                    names.color(sample.name, &sample.kind, sample.mode, *broken)
+                })
+                .collect::<Vec<_>>(),
+        ),
+        None => name.highlight(&broken, colors.alert.clone()),
+    };
+    let table = ds::table(
+        &[
+            name,
+            Column::new(
+                "Type",
+                cells(|sample| sample.kind.to_string()),
+                colors.kind.clone(),
+            ),
+            Column::new(
+                "Size",
+                cells(|sample| sample.size.to_string()),
+                colors.size.clone(),
+            ),
+            Column::new("Owner", cells(|_| "user".to_string()), colors.owner.clone()),
+            Column::new(
+                "Date Modified",
+                cells(|_| "Mon Jan  6 2025".to_string()),
+                colors.time.clone(),
+            ),
+            Column::new(
+                "Permission",
+                cells(|sample| permission_string(sample.letter, sample.mode)),
+                colors.permission.clone(),
+            ),
+            Column::new(
+                "Theme Key",
+                cells(|sample| format!("names.{}", sample.key)),
+                colors.dim.clone(),
+            ),
+        ],
+        colors.dim.clone(),
+    );
+
+    let others = [
+        ("note", &colors.note),
+        ("project", &colors.project),
+        ("crate", &colors.crate_name),
+        ("alert", &colors.alert),
+        ("dim", &colors.dim),
+    ]
+    .iter()
+    .map(|(key, color)| color.colorize(key))
+    .collect::<Vec<_>>()
+    .join("  ");
+    format!("{table}\n\n{others}")
+}
@@ -0,0 +1,89 @@
+//! `ds usage --by owner|group|ext`: the bytes of the files under a directory,
+//! added up per owner, group or extension and ranked, to see who or what
+//! fills a shared volume.
+
+use crate::color::ColorScheme;
+use clap::ValueEnum;
+use ds::{DirLister, Entry, Units, group_and_owner_mode, human_readable_size};
+use std::collections::HashMap;
+use std::path::Path;
+use tabled::{
+    Table, Tabled,
+    settings::{Format, Style, object::Columns, object::Rows},
+};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum By {
+    Owner,
+    Group,
+    #[value(alias = "extension")]
+    Ext,
+}
+
+#[derive(Tabled)]
+struct Share {
+    #[tabled(rename = "#")]
+    rank: usize,
+    #[tabled(rename = "Key")]
+    key: String,
+    #[tabled(rename = "Files")]
+    files: usize,
+    #[tabled(rename = "Size")]
+    size: String,
+    #[tabled(rename = "Share")]
+    share: String,
+}
+
+pub fn render(path: &Path, by: By, show_hidden: bool, units: Units, colors: &ColorScheme) -> Table {
+    let lister = DirLister {
+        all: show_hidden,
+        ..DirLister::default()
+    };
+    // Key -> (files, bytes)
+    let mut totals: HashMap<String, (usize, u64)> = HashMap::new();
+    for (_, Entry { path: file, meta }) in lister.recursive(lister.list(path), usize::MAX) {
+        if !meta.is_file() {
+            continue;
+        }
+        let key = match by {
+            By::Owner => group_and_owner_mode(&meta).owner,
+            By::Group => group_and_owner_mode(&meta).group,
+            By::Ext => file
+                .extension()
+                .map(|extension| format!(".{}", extension.to_string_lossy().to_lowercase()))
+                .unwrap_or_else(|| "(none)".into()),
+        };
+        let total = totals.entry(key).or_default();
+        total.0 += 1;
+        total.1 += meta.len();
+    }
+
+    let all: u64 = totals.values().map(|(_, bytes)| bytes).sum();
+    let mut totals: Vec<(String, (usize, u64))> = totals.into_iter().collect();
+    totals.sort_by(|(a_key, (_, a)), (b_key, (_, b))| b.cmp(a).then(a_key.cmp(b_key)));
+    let shares = totals
+        .into_iter()
+        .enumerate()
+        .map(|(index, (key, (files, bytes)))| Share {
+            rank: index + 1,
+            key,
+            files,
+            size: human_readable_size(bytes, units),
+            share: format!("{:.1}%", bytes as f64 / all.max(1) as f64 * 100.0),
+        });
+
+    let mut table = Table::new(shares);
+    table.with(Style::empty());
+    table.modify(Columns::one(1), colors.owner.clone());
+    table.modify(Columns::one(3), colors.size.clone());
+    table.modify(Columns::last(), colors.permission.clone());
+    table.modify(Rows::first(), colors.dim.clone());
+    // Name the key column after what it holds
+    let header = match by {
+        By::Owner => "Owner",
+        By::Group => "Group",
+        By::Ext => "Extension",
+    };
+    table.modify((0, 1), Format::content(|_| header.to_string()));
+    table
+}
@@ -0,0 +1,127 @@
+//! `--save-view`: the sort and columns of a directory, remembered in
+//! `~/.config/ds/views.toml` by its absolute path and used whenever it is
+//! listed without choosing any.
+//!
+//! ```toml
+//! ["/home/me/Downloads"]
+//! sort = "modified"
+//! reverse = true
+//! columns = ["name", "size", "modified"]
+//! ```
+
+use crate::columns::Field;
+use crate::config;
+use clap::ValueEnum;
+use ds::SortField;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+pub struct View {
+    pub sort: SortField,
+    pub reverse: bool,
+    pub columns: Option<Vec<Field>>,
+}
+
+fn file() -> Result<PathBuf, String> {
+    config::dir()
+        .map(|dir| dir.join("views.toml"))
+        .ok_or_else(|| "no config directory, HOME isn't set".to_string())
+}
+
+fn key(dir: &Path) -> Result<String, String> {
+    let dir = dir
+        .canonicalize()
+        .map_err(|err| format!("{}: {err}", dir.display()))?;
+    Ok(dir.to_string_lossy().into_owned())
+}
+
+fn read(file: &Path) -> Result<Table, String> {
+    match fs::read_to_string(file) {
+        Ok(text) => text
+            .parse()
+            .map_err(|err: toml::de::Error| format!("{}: {err}", file.display())),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Table::new()),
+        Err(err) => Err(format!("{}: {err}", file.display())),
+    }
+}
+
+fn write(file: &Path, views: &Table) -> Result<(), String> {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    }
+    fs::write(file, views.to_string()).map_err(|err| format!("{}: {err}", file.display()))
+}
+
+fn name(value: impl ValueEnum) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// The view saved for `dir`, if any. Directories that don't exist have none.
+pub fn load(dir: &Path) -> Result<Option<View>, String> {
+    let (Ok(file), Ok(key)) = (file(), key(dir)) else {
+        return Ok(None);
+    };
+    let views = read(&file)?;
+    let Some(view) = views.get(&key) else {
+        return Ok(None);
+    };
+    let invalid = |what: &str| format!("{}: invalid {what} for {}", file.display(), dir.display());
+
+    let sort = match view.get("sort") {
+        Some(Value::String(sort)) => sort.parse::<SortField>().map_err(|_| invalid("sort"))?,
+        None => SortField::Name,
+        Some(_) => return Err(invalid("sort")),
+    };
+    let reverse = match view.get("reverse") {
+        Some(reverse) => reverse.as_bool().ok_or_else(|| invalid("reverse"))?,
+        None => false,
+    };
+    let columns = match view.get("columns") {
+        Some(Value::Array(columns)) => Some(
+            columns
+                .iter()
+                .map(|column| {
+                    let column = column.as_str().ok_or_else(|| invalid("columns"))?;
+                    Field::from_str(column, true).map_err(|_| invalid("columns"))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        None => None,
+        Some(_) => return Err(invalid("columns")),
+    };
+    Ok(Some(View {
+        sort,
+        reverse,
+        columns,
+    }))
+}
+
+pub fn save(dir: &Path, view: &View) -> Result<(), String> {
+    let file = file()?;
+    let mut views = read(&file)?;
+    let mut saved = Table::new();
+    saved.insert("sort".into(), Value::String(view.sort.to_string()));
+    saved.insert("reverse".into(), Value::Boolean(view.reverse));
+    if let Some(columns) = &view.columns {
+        let columns = columns.iter().map(|column| Value::String(name(*column)));
+        saved.insert("columns".into(), Value::Array(columns.collect()));
+    }
+    views.insert(key(dir)?, Value::Table(saved));
+    write(&file, &views)
+}
+
+/// Drop the view saved for `dir`; false when there was none.
+pub fn forget(dir: &Path) -> Result<bool, String> {
+    let file = file()?;
+    let mut views = read(&file)?;
+    if views.remove(&key(dir)?).is_none() {
+        return Ok(false);
+    }
+    write(&file, &views)?;
+    Ok(true)
+}
@@ -0,0 +1,55 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn verify_mtime_catches_content_changed_behind_a_kept_mtime() {
+    let dir = tempfile::tempdir().unwrap();
+    let tree = dir.path().join("tree");
+    fs::create_dir(&tree).unwrap();
+    fs::write(tree.join("kept"), "same").unwrap();
+    fs::write(tree.join("forged"), "before").unwrap();
+    let baseline = dir.path().join("baseline.json");
+    let ds = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_ds"))
+            .arg("baseline")
+            .args(args)
+            .arg(&tree)
+            .arg("--file")
+            .arg(&baseline)
+            .env("NO_COLOR", "1")
+            .output()
+            .unwrap()
+    };
+    assert!(ds(&["create"]).status.success());
+
+    // Change the content, then put the old modification time back
+    let reference = dir.path().join("reference");
+    let touch = |args: &[&std::path::Path]| {
+        let status = Command::new("touch").arg("-r").args(args).status().unwrap();
+        assert!(status.success());
+    };
+    touch(&[&tree.join("forged"), &reference]);
+    fs::write(tree.join("forged"), "after!").unwrap();
+    touch(&[&reference, &tree.join("forged")]);
+
+    let output = ds(&["verify", "--verify-mtime"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let changes: Vec<(&str, String)> = stdout
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            let path = words.next()?;
+            let change = words.take_while(|word| !word.starts_with(char::is_numeric));
+            Some((path, change.collect::<Vec<_>>().join(" ")))
+        })
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            ("forged", "mtime kept".to_string()),
+            ("forged", "content".to_string())
+        ]
+    );
+}
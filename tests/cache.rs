@@ -0,0 +1,50 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn ds(cache: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .env("XDG_CACHE_HOME", cache)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn cached_totals_last_until_the_directory_changes() {
+    let cache = tempfile::tempdir().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("big/nested")).unwrap();
+    fs::write(dir.path().join("big/nested/data"), vec![0; 1_000]).unwrap();
+    let path = dir.path().to_str().unwrap();
+    let totals = |args: &[&str]| {
+        let mut all = vec![path, "--total-size", "--format", "{name} {binary}"];
+        all.extend(args);
+        ds(cache.path(), &all)
+    };
+
+    assert_eq!(totals(&["--cache"]), "big 1000\n");
+    // Deeper down than `big` itself, so its cached total is kept
+    fs::write(dir.path().join("big/nested/data"), vec![0; 2_000]).unwrap();
+    assert_eq!(totals(&["--cache"]), "big 1000\n");
+    assert_eq!(totals(&["--cache", "--no-cache"]), "big 2000\n");
+    assert_eq!(totals(&[]), "big 2000\n");
+
+    fs::write(dir.path().join("big/more"), vec![0; 500]).unwrap();
+    assert_eq!(totals(&["--cache"]), "big 2500\n");
+
+    let removed = format!(
+        "Removed {}.\n",
+        cache.path().join("ds/cache.json").display()
+    );
+    fs::write(dir.path().join("big/more"), vec![0; 100]).unwrap();
+    assert_eq!(ds(cache.path(), &["cache", "clear"]), removed);
+    assert_eq!(totals(&["--cache"]), "big 2100\n");
+    assert_eq!(ds(cache.path(), &["cache", "clear"]), removed);
+    assert_eq!(
+        ds(cache.path(), &["cache", "clear"]),
+        "Nothing is cached.\n"
+    );
+}
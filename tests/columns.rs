@@ -0,0 +1,51 @@
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::process::Command;
+
+fn ds(dir: &std::path::Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir)
+        .args(["--color", "never"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn columns_of_options_can_be_picked_and_placed() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("file"), "data").unwrap();
+    symlink("file", dir.path().join("link")).unwrap();
+
+    // An option's column goes after the ones asked for, unless it's one of them
+    let stdout = ds(dir.path(), &["--link-target", "--columns", "name,type"]);
+    let header = stdout.lines().nth(1).unwrap();
+    assert_eq!(
+        header.split_whitespace().collect::<Vec<_>>(),
+        ["Name", "Type", "Link", "Target"]
+    );
+    let stdout = ds(dir.path(), &["--columns", "link-target,name"]);
+    let header = stdout.lines().nth(1).unwrap();
+    assert_eq!(
+        header.split_whitespace().collect::<Vec<_>>(),
+        ["Link", "Target", "Name"]
+    );
+    assert!(stdout.contains("link -> file"));
+
+    let stdout = ds(dir.path(), &["--format", "{name}|{link-target}|{type}"]);
+    assert_eq!(stdout, "file||File\nlink|link -> file|Symlink\n");
+}
+
+#[test]
+fn columns_that_need_an_option_say_so() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("file"), "data").unwrap();
+
+    let stdout = ds(dir.path(), &["--columns", "name,matches"]);
+    assert!(stdout.contains("The matches column needs --match-count"));
+    assert!(stdout.contains(" file"));
+    let stdout = ds(dir.path(), &["--match-count", "at", "--columns", "name"]);
+    assert!(stdout.contains("Matches"));
+}
@@ -0,0 +1,216 @@
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn names(output: &[u8]) -> Vec<String> {
+    let listing: Value = serde_json::from_slice(output).unwrap();
+    listing
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn config_sets_defaults_the_command_line_overrides() {
+    let config = tempfile::tempdir().unwrap();
+    fs::create_dir(config.path().join("ds")).unwrap();
+    fs::write(
+        config.path().join("ds/config.toml"),
+        "all = true\nsort = \"size\"\n",
+    )
+    .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(".hidden"), "1").unwrap();
+    fs::write(dir.path().join("big"), "12345").unwrap();
+    fs::write(dir.path().join("small"), "12").unwrap();
+
+    let list = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .env("XDG_CONFIG_HOME", config.path())
+            .arg(dir.path())
+            .args(["--output", "json"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        names(&output.stdout)
+    };
+    assert_eq!(list(&[]), [".hidden", "small", "big"]);
+    assert_eq!(list(&["--sort", "name"]), [".hidden", "big", "small"]);
+}
+
+#[test]
+fn ds_opts_sits_between_config_and_command_line() {
+    let config = tempfile::tempdir().unwrap();
+    fs::create_dir(config.path().join("ds")).unwrap();
+    fs::write(config.path().join("ds/config.toml"), "sort = \"size\"\n").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a"), "12345").unwrap();
+    fs::write(dir.path().join("b"), "12").unwrap();
+
+    let list = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .env("XDG_CONFIG_HOME", config.path())
+            .env("DS_OPTS", "--sort name --reverse")
+            .arg(dir.path())
+            .args(["--output", "json"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        names(&output.stdout)
+    };
+    assert_eq!(list(&[]), ["b", "a"]);
+    assert_eq!(list(&["--sort", "size"]), ["a", "b"]);
+}
+
+#[test]
+fn theme_colors_are_downgraded_for_256_color_terminals() {
+    let config = tempfile::tempdir().unwrap();
+    fs::create_dir(config.path().join("ds")).unwrap();
+    fs::write(
+        config.path().join("ds/config.toml"),
+        "[theme]\nsize = \"#e69f00\"\n",
+    )
+    .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("file"), "1").unwrap();
+
+    let list = |term: &str, colorterm: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .env("XDG_CONFIG_HOME", config.path())
+            .env("TERM", term)
+            .env("COLORTERM", colorterm)
+            .env("COLORFGBG", "15;0")
+            .arg(dir.path())
+            .args(["--color", "always", "--size"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    assert!(list("xterm-256color", "truecolor").contains("\x1b[38;2;230;159;0m"));
+    assert!(list("xterm-256color", "").contains("\x1b[38;5;178m"));
+    assert!(list("xterm", "").contains("\x1b[93m"));
+}
+
+#[test]
+fn init_writes_the_answers_to_a_config_listings_use() {
+    let config = tempfile::tempdir().unwrap();
+    let init = |answers: &str, args: &[&str]| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .env("XDG_CONFIG_HOME", config.path())
+            .arg("init")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(answers.as_bytes())
+            .unwrap();
+        assert!(child.wait().unwrap().success());
+        fs::read_to_string(config.path().join("ds/config.toml")).unwrap()
+    };
+    // A wrong answer is asked again
+    let text = init("y\n\nname,sise\nname,size\n", &[]);
+    assert!(text.contains("\nicons = true\n"));
+    assert!(text.contains("\n# palette = \"default\"\n"));
+    assert!(text.contains("\ncolumns = [\"name\", \"size\"]\n"));
+    // Only --force replaces it
+    assert_eq!(init("n\n", &[]), text);
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("file"), "12345").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .env("XDG_CONFIG_HOME", config.path())
+        .arg(dir.path())
+        .args(["--color", "never"])
+        .output()
+        .unwrap();
+    let listing = String::from_utf8_lossy(&output.stdout);
+    assert!(listing.contains("Size"));
+    assert!(!listing.contains("Type"));
+
+    let text = init("", &["--force"]);
+    assert!(text.contains("\n# icons = true\n"));
+    assert!(text.contains("\n# columns = [\"name\", \"type\", \"size\"]\n"));
+}
+
+#[test]
+fn theme_preview_shows_every_kind_in_the_theme() {
+    let config = tempfile::tempdir().unwrap();
+    fs::create_dir(config.path().join("ds")).unwrap();
+    fs::write(
+        config.path().join("ds/config.toml"),
+        "[theme]\nsize = \"#e69f00\"\n\n[theme.names]\n\"*.rs\" = \"bold red\"\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .env("XDG_CONFIG_HOME", config.path())
+        .env("TERM", "xterm-256color")
+        .env("COLORTERM", "truecolor")
+        .env("COLORFGBG", "15;0")
+        .env_remove("LS_COLORS")
+        .args(["theme", "preview", "--color", "always"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let preview = String::from_utf8_lossy(&output.stdout);
+    for kind in [
+        "Dir", "File", "Symlink", "Fifo", "Socket", "BlockDev", "CharDev",
+    ] {
+        assert!(preview.contains(&format!("m{kind}\x1b")), "{kind}");
+    }
+    assert!(preview.contains("\x1b[1;31mmain.rs"));
+    assert!(preview.contains("\x1b[38;2;230;159;0m"));
+}
+
+#[test]
+fn views_without_columns_keep_the_configured_ones() {
+    let config = tempfile::tempdir().unwrap();
+    fs::create_dir(config.path().join("ds")).unwrap();
+    fs::write(
+        config.path().join("ds/config.toml"),
+        "columns = [\"name\", \"size\"]\n",
+    )
+    .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a"), "12345").unwrap();
+    fs::write(dir.path().join("b"), "12").unwrap();
+
+    let list = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .env("XDG_CONFIG_HOME", config.path())
+            .arg(dir.path())
+            .args(["--color", "never"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    // Saved with only a sort
+    fs::write(
+        config.path().join("ds/views.toml"),
+        format!(
+            "[\"{}\"]\nsort = \"size\"\nreverse = false\n",
+            dir.path().canonicalize().unwrap().display()
+        ),
+    )
+    .unwrap();
+    let listing = list(&[]);
+    let header = listing.lines().nth(1).unwrap();
+    assert_eq!(
+        header.split_whitespace().collect::<Vec<_>>(),
+        ["Name", "Size", "(binary)"]
+    );
+    // The view's sort still applies
+    assert!(listing.find(" b ").unwrap() < listing.find(" a ").unwrap());
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// `ds daemon` indexing a directory on a free port, stopped when dropped.
+struct Daemon(Child, String);
+
+impl Daemon {
+    fn start(dir: &Path, cache: &Path) -> Daemon {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let listen = format!("127.0.0.1:{port}");
+        let child = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .arg("daemon")
+            .arg(dir)
+            .args(["--listen", &listen, "--interval", "1"])
+            .env("XDG_CACHE_HOME", cache)
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        Daemon(child, listen)
+    }
+
+    /// What `ds query` prints with `args`, once it's printed `until`.
+    fn query(&self, args: &[&str], until: &str) -> String {
+        let mut stdout = String::new();
+        for _ in 0..50 {
+            let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+                .args(["query", "--connect", &self.1])
+                .args(args)
+                .output()
+                .unwrap();
+            stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+            if stdout.contains(until) {
+                return stdout;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        panic!("the daemon never answered '{until}', last: {stdout}");
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+#[test]
+fn query_lists_and_searches_the_index_and_sees_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("root");
+    fs::create_dir_all(root.join("src/deep")).unwrap();
+    fs::write(root.join("Cargo.toml"), "").unwrap();
+    fs::write(root.join("src/main.rs"), "").unwrap();
+    fs::write(root.join("src/deep/lib.rs"), "").unwrap();
+    fs::write(root.join(".hidden.rs"), "").unwrap();
+    let daemon = Daemon::start(&root, &dir.path().join("cache"));
+
+    let root = root.canonicalize().unwrap();
+    let listing = daemon.query(&[root.to_str().unwrap()], "Cargo.toml");
+    assert_eq!(listing, "Cargo.toml\nsrc/\n");
+    let found = daemon.query(&["--search", "*.rs"], "main.rs");
+    assert_eq!(
+        found,
+        format!("{0}/src/main.rs\n{0}/src/deep/lib.rs\n", root.display())
+    );
+
+    // A new file shows up once the daemon has looked again
+    fs::write(root.join("src/deep/new.rs"), "").unwrap();
+    let listing = daemon.query(&[root.join("src/deep").to_str().unwrap()], "new.rs");
+    assert_eq!(listing, "lib.rs\nnew.rs\n");
+}
+
+#[test]
+fn query_without_a_daemon_says_so() {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .args(["query", "--connect", &format!("127.0.0.1:{port}")])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("no daemon answers on"), "{stdout}");
+}
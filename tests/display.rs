@@ -0,0 +1,99 @@
+use std::fs;
+use std::os::unix::fs::{PermissionsExt, symlink};
+use std::process::Command;
+
+fn ds(dir: &std::path::Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn tree() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("run.sh"), "").unwrap();
+    fs::set_permissions(dir.path().join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(dir.path().join("data"), vec![0; 2_000]).unwrap();
+    symlink("data", dir.path().join("link")).unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    dir
+}
+
+#[test]
+fn grid_puts_names_side_by_side() {
+    let dir = tree();
+    assert_eq!(ds(dir.path(), &["--grid"]), "data  link  run.sh  sub\n");
+}
+
+#[test]
+fn classify_marks_types_like_ls() {
+    let dir = tree();
+    assert_eq!(
+        ds(dir.path(), &["-1", "-F"]),
+        "data\nlink@\nrun.sh*\nsub/\n"
+    );
+}
+
+#[test]
+fn units_label_the_size_column() {
+    let dir = tree();
+    let sizes = |units: &str| -> Vec<String> {
+        ds(dir.path(), &["--units", units, "--columns", "name,size"])
+            .lines()
+            .filter(|line| line.contains("Size") || line.contains("data"))
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    };
+    assert_eq!(sizes("decimal"), ["Name Size (decimal)", "data 2.0k"]);
+    assert_eq!(sizes("binary"), ["Name Size (binary)", "data 2.0K"]);
+}
+
+#[test]
+fn a_seed_always_samples_the_same_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    for index in 0..20 {
+        fs::write(dir.path().join(format!("file{index}")), "").unwrap();
+    }
+    let sample = ds(dir.path(), &["-1", "--sample", "5", "--seed", "7"]);
+    assert_eq!(sample.lines().count(), 5);
+    assert_eq!(
+        ds(dir.path(), &["-1", "--sample", "5", "--seed", "7"]),
+        sample
+    );
+}
+
+#[test]
+fn usage_ranks_extensions_by_size() {
+    let dir = tree();
+    fs::write(dir.path().join("sub/notes.txt"), vec![0; 500]).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .args(["usage", "--by", "ext"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let ranks: Vec<Vec<String>> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().take(3).map(String::from).collect())
+        .collect();
+    assert_eq!(
+        ranks,
+        [["1", "(none)", "1"], ["2", ".txt", "1"], ["3", ".sh", "1"]]
+    );
+}
+
+#[test]
+fn completions_know_the_subcommands() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .args(["completions", "bash"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let script = String::from_utf8_lossy(&output.stdout);
+    assert!(script.contains("complete -F _ds"));
+    assert!(script.contains("serve"));
+}
@@ -0,0 +1,28 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn total_size_sums_directories_and_sorts_by_the_sums() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("big/nested")).unwrap();
+    fs::write(dir.path().join("big/nested/data"), vec![0; 10_000]).unwrap();
+    fs::write(dir.path().join("big/more"), vec![0; 500]).unwrap();
+    fs::write(dir.path().join("medium"), vec![0; 5_000]).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args([
+            "--total-size",
+            "--sort",
+            "size",
+            "--format",
+            "{name} {binary}",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "medium 5000\nbig 10500\n"
+    );
+}
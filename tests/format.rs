@@ -0,0 +1,55 @@
+use std::fs;
+use std::os::unix::fs::{MetadataExt, PermissionsExt, symlink};
+use std::process::Command;
+
+fn format(dir: &std::path::Path, template: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir)
+        .args(["--color", "never", "--format", template])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn format_fills_metadata_placeholders() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("file");
+    fs::write(&file, "data").unwrap();
+    fs::set_permissions(&file, fs::Permissions::from_mode(0o640)).unwrap();
+    fs::hard_link(&file, dir.path().join("hard")).unwrap();
+    symlink("file", dir.path().join("link")).unwrap();
+    let meta = fs::metadata(&file).unwrap();
+
+    let stdout = format(
+        dir.path(),
+        "{name}\\t{binary}\\t{mode}\\t{links}\\t{inode}\\t{uid}:{gid}\\t{{{target}}}",
+    );
+    let lines: Vec<&str> = stdout.lines().collect();
+    let ids = format!("{}:{}", meta.uid(), meta.gid());
+    let ino = meta.ino().to_string();
+    assert_eq!(
+        lines[0].split('\t').collect::<Vec<_>>(),
+        ["file", "4", "640", "2", &ino, &ids, "{}"]
+    );
+    assert!(lines[1].starts_with(&format!("hard\t4\t640\t2\t{ino}\t")));
+    assert!(lines[2].starts_with("link\t"));
+    assert!(lines[2].ends_with("\t{file}"));
+
+    let stdout = format(dir.path(), "{name} {changed}");
+    assert!(stdout.starts_with("file "));
+    assert!(stdout.lines().next().unwrap().len() > "file ".len());
+}
+
+#[test]
+fn format_rejects_unknown_and_unterminated_placeholders() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("file"), "data").unwrap();
+
+    let stdout = format(dir.path(), "{name");
+    assert!(stdout.contains("unterminated placeholder '{name'"));
+    assert!(!stdout.contains("file"));
+    let stdout = format(dir.path(), "{nope}");
+    assert!(stdout.contains("unknown placeholder '{nope}'"));
+}
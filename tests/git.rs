@@ -0,0 +1,151 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn git_shows_staged_untracked_and_ignored_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir.path())
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+    for name in ["a.log", "b.txt", "c.txt"] {
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+    git(&["add", "b.txt"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .arg("--git")
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let status = |name: &str| {
+        stdout
+            .lines()
+            .find(|line| line.split_whitespace().next() == Some(name))
+            .and_then(|line| line.split_whitespace().last())
+            .map(str::to_owned)
+    };
+    assert_eq!(status("a.log").as_deref(), Some("-I"));
+    assert_eq!(status("b.txt").as_deref(), Some("N-"));
+    assert_eq!(status("c.txt").as_deref(), Some("-N"));
+}
+
+#[test]
+fn git_header_shows_the_branch_and_uncommitted_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let status = Command::new("git")
+        .args(["init", "-q", "-b", "trunk"])
+        .arg(dir.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    fs::write(dir.path().join("new.txt"), "").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .arg("--git-header")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().nth(1), Some("Branch: trunk *"));
+}
+
+#[test]
+fn git_lists_lfs_pointers_at_the_real_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir.path())
+        .args(["init", "-q"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    // As checked out with GIT_LFS_SKIP_SMUDGE=1
+    fs::write(
+        dir.path().join("video.mp4"),
+        "version https://git-lfs.github.com/spec/v1\n\
+         oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\
+         size 12345\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("notes.txt"), "size 12345\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["--git", "--format", "{name} {binary}"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "notes.txt 11\nvideo.mp4 12345\n"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .arg("--git")
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let video = stdout
+        .lines()
+        .find(|line| line.contains("video.mp4"))
+        .unwrap();
+    assert!(video.contains("LFS"));
+    let notes = stdout
+        .lines()
+        .find(|line| line.contains("notes.txt"))
+        .unwrap();
+    assert!(!notes.contains("LFS"));
+}
+
+#[test]
+fn at_lists_directories_deleted_since_the_revision() {
+    let dir = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir.path())
+            .args(["-c", "user.name=ds", "-c", "user.email=ds@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    fs::create_dir_all(dir.path().join("old/deep")).unwrap();
+    fs::create_dir(dir.path().join("kept")).unwrap();
+    fs::write(dir.path().join("old/a.txt"), "a").unwrap();
+    fs::write(dir.path().join("old/deep/b.txt"), "b").unwrap();
+    fs::write(dir.path().join("kept/c.txt"), "c").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-qm", "Add old"]);
+    git(&["rm", "-rq", "old"]);
+    git(&["commit", "-qm", "Remove old"]);
+
+    let list = |current: &str, path: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .current_dir(dir.path().join(current))
+            .args([path, "--at", "HEAD~1", "--format", "{name}"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    assert_eq!(list(".", "old"), "a.txt\ndeep\n");
+    assert_eq!(list(".", "old/deep"), "b.txt\n");
+    assert_eq!(list("kept", "../old"), "a.txt\ndeep\n");
+    assert_eq!(list("kept", ".."), "kept\nold\n");
+}
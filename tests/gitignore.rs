@@ -0,0 +1,52 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn git_ignore_follows_gitignore_and_exclude_rules() {
+    let dir = tempfile::tempdir().unwrap();
+    let status = Command::new("git")
+        .arg("init")
+        .arg("-q")
+        .arg(dir.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+    fs::write(dir.path().join(".git/info/exclude"), "secret\n").unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    for name in ["a.log", "b.txt", "secret", "sub/c.log", "sub/d.txt"] {
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["-i", "-R", "-1"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "b.txt\nsub\nsub/d.txt\n"
+    );
+}
+
+#[test]
+fn no_ignore_undoes_dot_ignore_files() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(".ignore"), "*.tmp\n").unwrap();
+    fs::write(dir.path().join("a.tmp"), "").unwrap();
+    fs::write(dir.path().join("b.txt"), "").unwrap();
+
+    let listing = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .arg(dir.path())
+            .args(["-1"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    assert_eq!(listing(&["-i"]), "b.txt\n");
+    assert_eq!(listing(&["-i", "--no-ignore"]), "a.tmp\nb.txt\n");
+}
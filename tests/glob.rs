@@ -0,0 +1,61 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn glob_keeps_matching_files_and_walks_every_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    for name in ["main.rs", "notes.txt", "src/lib.rs", "src/data.json"] {
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["-R", "-1", "--glob", "*.rs"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "main.rs\nsrc\nsrc/lib.rs\n"
+    );
+}
+
+#[test]
+fn glob_leaves_out_directories_that_dont_match_without_a_walk() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::create_dir(dir.path().join("old.rs")).unwrap();
+    fs::write(dir.path().join("main.rs"), "").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["-1", "--glob", "*.rs"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "main.rs\nold.rs\n");
+}
+
+#[test]
+fn iglob_and_iext_ignore_case_unlike_glob_and_ext() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["a.JPG", "b.jpg", "c.png", "d.Rs"] {
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+    let listing = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .arg(dir.path())
+            .arg("-1")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(listing(&["--ext", "jpg"]), "b.jpg\n");
+    assert_eq!(listing(&["--iext", "jpg,.PNG"]), "a.JPG\nb.jpg\nc.png\n");
+    assert_eq!(listing(&["--glob", "*.rs"]), "");
+    assert_eq!(listing(&["--iglob", "*.rs"]), "d.Rs\n");
+}
@@ -0,0 +1,256 @@
+use serde_json::Value;
+use std::fs::{self, File, FileTimes};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+fn at(seconds: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+#[test]
+fn json_timestamps_map_to_their_fields() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = File::create(dir.path().join("alpha")).unwrap();
+    file.set_times(
+        FileTimes::new()
+            .set_accessed(at(3_000))
+            .set_modified(at(1_000)),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["--output", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let listing: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let entry = &listing[0];
+    assert_eq!(entry["name"], "alpha");
+    assert_eq!(entry["mtime"], 1_000);
+    assert_eq!(entry["mtime_iso"], "1970-01-01T00:16:40+00:00");
+    assert_eq!(entry["atime"], 3_000);
+    assert_eq!(entry["atime_iso"], "1970-01-01T00:50:00+00:00");
+    // Setting the times is itself a status change, so ctime is the present
+    assert!(entry["ctime"].as_i64().unwrap() > 1_000_000_000);
+}
+
+#[test]
+fn json_marks_broken_symlinks() {
+    let dir = tempfile::tempdir().unwrap();
+    File::create(dir.path().join("target")).unwrap();
+    std::os::unix::fs::symlink("target", dir.path().join("alive")).unwrap();
+    std::os::unix::fs::symlink("missing", dir.path().join("dead")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["--output", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let listing: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let broken = |name: &str| {
+        let entry = listing
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|entry| entry["name"] == name)
+            .unwrap();
+        entry["broken"].as_bool().unwrap()
+    };
+    assert!(!broken("alive"));
+    assert!(broken("dead"));
+    assert!(!broken("target"));
+}
+
+#[test]
+fn stats_json_goes_to_stderr() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("alpha"), "12345").unwrap();
+    fs::create_dir(dir.path().join("beta")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["--output", "json", "--stats-json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // stdout stays the listing alone
+    let listing: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(listing.as_array().unwrap().len(), 2);
+    let stats: Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(stats["counts"]["entries"], 2);
+    assert_eq!(stats["counts"]["files"], 1);
+    assert_eq!(stats["counts"]["dirs"], 1);
+    assert_eq!(stats["counts"]["bytes"], 5);
+    assert!(stats["durations_ms"]["scan"].is_number());
+    assert_eq!(stats["errors"], Value::Array(Vec::new()));
+}
+
+#[test]
+fn from_json_sorts_a_saved_listing_again() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("small"), "a").unwrap();
+    fs::write(dir.path().join("large"), "a".repeat(100)).unwrap();
+    let saved = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["--output", "json"])
+        .output()
+        .unwrap();
+    let file = dir.path().join("listing.json");
+    fs::write(&file, saved.stdout).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg("--from-json")
+        .arg(&file)
+        .args(["--sort", "size", "-1"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "small\nlarge\n");
+}
+
+#[test]
+fn fields_limit_the_keys_of_each_entry() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = File::create(dir.path().join("alpha")).unwrap();
+    file.set_times(FileTimes::new().set_modified(at(1_000)))
+        .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["--output", "json", "--fields", "name,bytes,mtime"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let listing: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        listing,
+        serde_json::json!([{ "name": "alpha", "bytes": 0, "mtime": 1_000 }])
+    );
+}
+
+#[test]
+fn csv_and_tsv_have_every_time_like_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = File::create(dir.path().join("alpha")).unwrap();
+    file.set_times(
+        FileTimes::new()
+            .set_accessed(at(3_000))
+            .set_modified(at(1_000)),
+    )
+    .unwrap();
+
+    for (output, separator) in [("csv", ","), ("tsv", "\t")] {
+        let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .arg(dir.path())
+            .args(["--output", output])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines().map(|line| line.split(separator));
+        let header: Vec<&str> = lines.next().unwrap().collect();
+        let row: Vec<&str> = lines.next().unwrap().collect();
+        assert_eq!(
+            header,
+            [
+                "Name",
+                "Type",
+                "mtime",
+                "mtime_iso",
+                "atime",
+                "atime_iso",
+                "ctime",
+                "ctime_iso",
+                "btime",
+                "btime_iso"
+            ]
+        );
+        assert_eq!(
+            row[..6],
+            [
+                "alpha",
+                "File",
+                "1000",
+                "1970-01-01T00:16:40+00:00",
+                "3000",
+                "1970-01-01T00:50:00+00:00"
+            ]
+        );
+        // Setting the times is itself a status change, so ctime is the present
+        assert!(row[6].parse::<i64>().unwrap() > 1_000_000_000);
+        assert!(row[7].starts_with("20"));
+    }
+}
+
+#[test]
+fn copied_paths_of_a_saved_listing_are_undecorated() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    fs::write(dir.path().join("a-rather-long-name"), "a").unwrap();
+    let saved = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["--output", "json"])
+        .output()
+        .unwrap();
+    let file = dir.path().join("listing.json");
+    fs::write(&file, saved.stdout).unwrap();
+    let copied = dir.path().join("copied");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg("--from-json")
+        .arg(&file)
+        .args(["--classify", "--max-width", "8", "--copy=paths"])
+        .env("DS_COPY_COMMAND", format!("cat > '{}'", copied.display()))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(&copied).unwrap(),
+        "./a-rather-long-name\n./sub\n"
+    );
+}
+
+#[test]
+fn from_json_reads_listings_saved_with_fields_and_shuffles_with_the_seed() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["a", "b", "c", "d", "e", "f", "g", "h"] {
+        fs::write(dir.path().join(name), name).unwrap();
+    }
+    let saved = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["--output", "json", "--fields", "name,path,bytes"])
+        .output()
+        .unwrap();
+    let file = dir.path().join("listing.json");
+    fs::write(&file, saved.stdout).unwrap();
+    let read = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .arg("--from-json")
+            .arg(&file)
+            .args(["--format", "{name} {type}"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let listed = read(&[]);
+    assert!(listed.starts_with("a File\nb File\n"));
+    let shuffled: Vec<String> = (1..=5)
+        .map(|seed| read(&["--sort", "random", "--seed", &seed.to_string()]))
+        .collect();
+    assert_eq!(shuffled[0], read(&["--sort", "random", "--seed", "1"]));
+    assert!(shuffled.iter().any(|shuffled| *shuffled != listed));
+    for shuffled in shuffled {
+        let mut lines: Vec<&str> = shuffled.lines().collect();
+        lines.sort();
+        assert_eq!(lines, listed.lines().collect::<Vec<_>>());
+    }
+}
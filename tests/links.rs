@@ -0,0 +1,46 @@
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::process::Command;
+
+#[test]
+fn link_targets_of_the_current_directory_name_it() {
+    let dir = tempfile::tempdir().unwrap();
+    symlink("nowhere", dir.path().join("broken")).unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    symlink("../elsewhere", dir.path().join("sub/up")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .current_dir(dir.path())
+        .args(["--link-targets", "--recursive", "--color", "never"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let table: Vec<Vec<&str>> = stdout
+        .lines()
+        .skip_while(|line| !line.contains("Target Directory"))
+        .skip(1)
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    // Both links point into the listed directory itself
+    let listed = dir.path().display().to_string();
+    assert_eq!(table, [vec![listed.as_str(), "2"]]);
+}
+
+#[test]
+fn dangling_links_get_only_the_link_badge() {
+    let dir = tempfile::tempdir().unwrap();
+    symlink("nowhere", dir.path().join("broken")).unwrap();
+    fs::write(dir.path().join("empty"), "").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["--format", "{name} {badges}"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "broken 🔗\nempty 🕳\n"
+    );
+}
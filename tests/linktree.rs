@@ -0,0 +1,38 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn link_tree(src: &Path, dst: &Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg("link-tree")
+        .args([src, dst])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn link_tree_runs_again_over_its_mirror() {
+    let src = tempfile::tempdir().unwrap();
+    fs::create_dir(src.path().join("sub")).unwrap();
+    fs::write(src.path().join("sub/file"), "data").unwrap();
+    let dst = tempfile::tempdir().unwrap();
+    let mirror = dst.path().join("mirror");
+
+    assert_eq!(link_tree(src.path(), &mirror), "Created 2 entries.\n");
+    // What's there is kept, what's new is added
+    fs::write(mirror.join("sub/own"), "mine").unwrap();
+    fs::write(src.path().join("sub/new"), "data").unwrap();
+    assert_eq!(link_tree(src.path(), &mirror), "Created 1 entries.\n");
+    assert!(
+        fs::symlink_metadata(mirror.join("sub/new"))
+            .unwrap()
+            .is_symlink()
+    );
+    assert_eq!(fs::read_to_string(mirror.join("sub/own")).unwrap(), "mine");
+    assert_eq!(
+        fs::read_link(mirror.join("sub/file")).unwrap(),
+        src.path().canonicalize().unwrap().join("sub/file")
+    );
+}
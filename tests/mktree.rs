@@ -0,0 +1,42 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn mktree_creates_the_spec() {
+    let dir = tempfile::tempdir().unwrap();
+    let spec = dir.path().join("spec.yaml");
+    fs::write(
+        &spec,
+        "src/:\n  main.rs: \"fn main() {}\\n\"\n  big.bin: { size: 4096, mtime: 1000 }\n\
+         run.sh: { content: \"#!/bin/sh\\n\", mode: 755 }\nlatest: { symlink: src/main.rs }\n",
+    )
+    .unwrap();
+    let root = dir.path().join("tree");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg("mktree")
+        .arg(&spec)
+        .arg(&root)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert_eq!(
+        fs::read_to_string(root.join("src/main.rs")).unwrap(),
+        "fn main() {}\n"
+    );
+    let big = fs::metadata(root.join("src/big.bin")).unwrap();
+    assert_eq!(big.len(), 4096);
+    assert_eq!(
+        big.modified().unwrap(),
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1000)
+    );
+    let script = fs::metadata(root.join("run.sh")).unwrap();
+    assert_eq!(script.permissions().mode() & 0o7777, 0o755);
+    assert_eq!(
+        fs::read_link(root.join("latest")).unwrap(),
+        std::path::Path::new("src/main.rs")
+    );
+}
@@ -0,0 +1,128 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn several_paths_list_files_first_then_each_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("one")).unwrap();
+    fs::create_dir(dir.path().join("two")).unwrap();
+    fs::write(dir.path().join("one/alpha"), "a").unwrap();
+    fs::write(dir.path().join("two/beta"), "bb").unwrap();
+    fs::write(dir.path().join("loose"), "ccc").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .current_dir(dir.path())
+        .args(["-1", "--summary", "one", "loose", "two"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "loose\n\none:\nalpha\n\ntwo:\nbeta\n\n3 entries (0 dirs, 3 files), 6B in 3 paths\n"
+    );
+}
+
+#[test]
+fn dedupe_lists_hard_links_and_overlapping_paths_once() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("one/inner")).unwrap();
+    fs::create_dir(dir.path().join("two")).unwrap();
+    fs::write(dir.path().join("one/alpha"), "a").unwrap();
+    fs::hard_link(dir.path().join("one/alpha"), dir.path().join("two/beta")).unwrap();
+    fs::write(dir.path().join("one/inner/gamma"), "g").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .current_dir(dir.path())
+        .args(["-1", "-R", "--dedupe", "one", "two", "one/inner"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "one:\nalpha\ninner\ninner/gamma\n\ntwo:\n\none/inner:\n"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .current_dir(dir.path())
+        .env("NO_COLOR", "1")
+        .args(["--dedupe", "one", "two"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let alpha = stdout.lines().find(|line| line.contains("alpha")).unwrap();
+    assert!(alpha.trim_end().ends_with("two/beta"));
+}
+
+#[test]
+fn several_paths_make_one_json_or_csv_document() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("one")).unwrap();
+    fs::create_dir(dir.path().join("two")).unwrap();
+    fs::write(dir.path().join("one/alpha"), "a").unwrap();
+    fs::write(dir.path().join("two/beta"), "bb").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .current_dir(dir.path())
+        .args(["--output", "json", "one", "two"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let paths: Vec<&str> = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, ["one/alpha", "two/beta"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .current_dir(dir.path())
+        .args(["--output", "csv", "one", "two"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    // Up to the times that follow
+    let rows: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.splitn(4, ',').take(3).collect::<Vec<_>>().join(","))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            "Path,Name,Type",
+            "one/alpha,alpha,File",
+            "two/beta,beta,File"
+        ]
+    );
+}
+
+#[test]
+fn oneline_pipes_names_as_they_are() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("two\nlines"), "").unwrap();
+    fs::write(dir.path().join("plain"), "").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .current_dir(dir.path())
+        .args(["-1", "plain", "two\nlines"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "plain\ntwo\nlines\n"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg("-1")
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "plain\ntwo\nlines\n"
+    );
+}
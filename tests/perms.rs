@@ -0,0 +1,28 @@
+use std::fs::{self, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use std::process::Command;
+
+#[test]
+fn check_leaves_out_hidden_and_ignored_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(".ignore"), "build.log\n").unwrap();
+    for name in ["script.sh", "build.log", ".secret"] {
+        fs::write(dir.path().join(name), "").unwrap();
+        fs::set_permissions(dir.path().join(name), Permissions::from_mode(0o755)).unwrap();
+    }
+    fs::set_permissions(dir.path(), Permissions::from_mode(0o755)).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .args(["perms", "check", "--git-ignore", "--expect", "644/755"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let paths: Vec<&str> = stdout
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .collect();
+    assert_eq!(paths, ["script.sh"]);
+}
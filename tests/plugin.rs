@@ -0,0 +1,77 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::process::Command;
+
+#[test]
+fn plugins_get_nul_separated_paths_and_may_answer_while_reading() {
+    let dir = tempfile::tempdir().unwrap();
+    let bin = dir.path().join("bin");
+    let listed = dir.path().join("listed");
+    fs::create_dir(&bin).unwrap();
+    fs::create_dir(&listed).unwrap();
+    // Answers each path as it comes, so its output fills the pipe before its
+    // input is all written
+    let plugin = bin.join("ds-column-echo");
+    fs::write(
+        &plugin,
+        "#!/bin/sh\ntr '\\0' '\\n' | awk 'BEGIN { printf \"[\" } \
+         { printf \"%s\\\"%s\\\"\", (NR > 1 ? \",\" : \"\"), $0 } END { print \"]\" }'\n",
+    )
+    .unwrap();
+    fs::set_permissions(&plugin, fs::Permissions::from_mode(0o755)).unwrap();
+    let names: Vec<String> = (0..10000).map(|index| format!("{index:0>60}")).collect();
+    for name in &names {
+        fs::write(listed.join(name), "").unwrap();
+    }
+
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(&listed)
+        .args(["--plugin", "echo", "--output", "csv"])
+        .env("PATH", path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    // The times come after the plugin's column
+    assert!(lines.next().unwrap().starts_with("Name,Type,echo,mtime,"));
+    for (line, name) in lines.zip(&names) {
+        let path = listed.join(name);
+        assert!(line.starts_with(&format!("{name},File,{},", path.display())));
+    }
+}
+
+#[test]
+fn hooks_filter_and_color_entries() {
+    let config = tempfile::tempdir().unwrap();
+    fs::create_dir(config.path().join("ds")).unwrap();
+    fs::write(
+        config.path().join("ds/config.toml"),
+        r#"[hooks]
+script = '''
+fn filter(entry) { entry.type == "dir" || entry.name.ends_with(".rs") }
+fn color(entry) { if entry.path.contains("src/") && entry.bytes > 0 { "red" } }
+'''
+"#,
+    )
+    .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    fs::write(dir.path().join("build.rs"), "fn main() {}").unwrap();
+    fs::write(dir.path().join("README.md"), "").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .env("XDG_CONFIG_HOME", config.path())
+        .env_remove("LS_COLORS")
+        .arg(dir.path())
+        .args(["--recursive", "--color", "always", "--columns", "name"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[31msrc/main.rs"));
+    assert!(stdout.contains(" build.rs"));
+    assert!(!stdout.contains("README.md"));
+}
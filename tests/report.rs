@@ -0,0 +1,37 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn report_charts_sizes_and_types_of_files_and_keeps_names_as_they_are() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("{rows}.txt"), vec![0; 3_000]).unwrap();
+    fs::write(dir.path().join("notes.txt"), vec![0; 1_000]).unwrap();
+    fs::write(dir.path().join("main.rs"), vec![0; 500]).unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    let report = dir.path().join("sub/report.html");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg("report")
+        .arg(dir.path())
+        .arg("--output")
+        .arg(&report)
+        .env("HOME", dir.path().join("sub"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let html = fs::read_to_string(&report).unwrap();
+
+    // The name is in the chart of the largest entries and the table, and
+    // the table isn't pasted in again where it's mentioned
+    assert_eq!(html.matches("{rows}.txt").count(), 2);
+    assert_eq!(html.matches("<tbody>").count(), 1);
+    assert!(html.contains("<h2>Space by type</h2>"));
+    assert!(html.contains("<span class=\"label\">.txt</span>"));
+    assert!(html.contains("<span class=\"label\">.rs</span>"));
+    // Directories are in neither chart nor the total
+    assert!(!html.contains("<span class=\"label\">Dir</span>"));
+    assert!(!html.contains("<span class=\"label\">sub</span>"));
+    assert!(html.contains("<b>4.4K</b> total"));
+    // The braces of the stylesheet are left alone
+    assert!(html.contains("body { font-family"));
+}
@@ -0,0 +1,35 @@
+use std::fs::{File, FileTimes};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+fn aged(dir: &Path, name: &str, days: u64) {
+    let file = File::create(dir.join(name)).unwrap();
+    let modified = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+    file.set_times(FileTimes::new().set_modified(modified))
+        .unwrap();
+}
+
+#[test]
+fn retention_previews_old_files_beyond_the_latest_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    aged(dir.path(), "a.log", 40);
+    aged(dir.path(), "b.log", 50);
+    aged(dir.path(), "c.log", 5);
+    aged(dir.path(), "notes.txt", 90);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg("retention")
+        .arg(dir.path())
+        .args(["--keep", "30d", "--keep-latest", "2", "--pattern", "*.log"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // c.log and a.log are the two newest logs, notes.txt isn't a log
+    assert!(stdout.contains("b.log"));
+    assert!(!stdout.contains("a.log"));
+    assert!(!stdout.contains("notes.txt"));
+    assert!(stdout.contains("1 files, 0B would be deleted."));
+    assert!(dir.path().join("b.log").exists());
+}
@@ -0,0 +1,110 @@
+use serde_json::Value;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::fs::symlink;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// `ds serve` on a free port, stopped when dropped.
+struct Server(Child, u16);
+
+impl Server {
+    fn start(dirs: &[&std::path::Path]) -> Server {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let child = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .arg("serve")
+            .args(dirs)
+            .args(["--listen", &format!("127.0.0.1:{port}")])
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        Server(child, port)
+    }
+
+    fn list(&self, path: &str) -> Value {
+        for _ in 0..50 {
+            let Ok(mut stream) = TcpStream::connect(("127.0.0.1", self.1)) else {
+                thread::sleep(Duration::from_millis(100));
+                continue;
+            };
+            write!(
+                stream,
+                "GET /api/list?path={path} HTTP/1.0\r\nHost: localhost\r\n\r\n"
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            let (_, body) = response.split_once("\r\n\r\n").unwrap();
+            return serde_json::from_str(body).unwrap();
+        }
+        panic!("the server didn't start");
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn kinds(listing: &Value) -> Vec<(String, String, bool)> {
+    listing
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            (
+                entry["name"].as_str().unwrap().to_string(),
+                entry["type"].as_str().unwrap().to_string(),
+                entry["directory"].as_bool().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn serve_tells_links_apart_and_serves_several_directories() {
+    let one = tempfile::tempdir().unwrap();
+    let two = tempfile::tempdir().unwrap();
+    fs::create_dir(one.path().join("sub")).unwrap();
+    fs::write(one.path().join("sub/file"), "data").unwrap();
+    symlink("sub", one.path().join("link")).unwrap();
+
+    let server = Server::start(&[one.path()]);
+    assert_eq!(
+        kinds(&server.list("")),
+        [
+            ("sub".to_string(), "dir".to_string(), true),
+            ("link".to_string(), "symlink".to_string(), true),
+        ]
+    );
+    assert_eq!(
+        kinds(&server.list("link")),
+        [("file".to_string(), "file".to_string(), false)]
+    );
+    drop(server);
+
+    let server = Server::start(&[one.path(), two.path()]);
+    let names: Vec<String> = kinds(&server.list(""))
+        .into_iter()
+        .map(|(name, ..)| name)
+        .collect();
+    let name = |dir: &tempfile::TempDir| {
+        dir.path()
+            .canonicalize()
+            .unwrap()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned()
+    };
+    assert_eq!(names, [name(&one), name(&two)]);
+    assert_eq!(kinds(&server.list(&format!("{}/sub", name(&one)))).len(), 1);
+}
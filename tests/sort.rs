@@ -0,0 +1,114 @@
+use std::fs::{self, File, FileTimes};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+fn at(seconds: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+fn touch(dir: &Path, name: &str, accessed: SystemTime, modified: SystemTime) {
+    let file = File::create(dir.join(name)).unwrap();
+    file.set_times(
+        FileTimes::new()
+            .set_accessed(accessed)
+            .set_modified(modified),
+    )
+    .unwrap();
+}
+
+/// Names of the listed entries in the order `ds` printed them.
+fn listing(dir: &Path, sort: &str) -> Vec<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir)
+        .args(["--sort", sort])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .skip(2) // "Path:" banner and table header
+        .filter_map(|line| line.split_whitespace().next().map(String::from))
+        .collect()
+}
+
+/// `alpha` is older by mtime but newer by atime and ctime than `beta`.
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    touch(dir.path(), "alpha", at(3_000), at(1_000));
+    touch(dir.path(), "beta", at(500), at(2_000));
+
+    // chmod only bumps the status change time
+    thread::sleep(Duration::from_millis(20));
+    fs::set_permissions(dir.path().join("alpha"), fs::Permissions::from_mode(0o600)).unwrap();
+    dir
+}
+
+#[test]
+fn sort_modified_uses_mtime() {
+    let dir = fixture();
+    assert_eq!(listing(dir.path(), "modified"), ["alpha", "beta"]);
+}
+
+#[test]
+fn sort_accessed_uses_atime() {
+    let dir = fixture();
+    assert_eq!(listing(dir.path(), "accessed"), ["beta", "alpha"]);
+}
+
+#[test]
+fn sort_changed_uses_ctime() {
+    let dir = fixture();
+    assert_eq!(listing(dir.path(), "changed"), ["beta", "alpha"]);
+}
+
+#[test]
+fn collate_base_sorts_accents_with_their_letters() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["zebra", "éclair", "eagle", "über", "ufo"] {
+        File::create(dir.path().join(name)).unwrap();
+    }
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["-1", "--collate", "base"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "eagle\néclair\nüber\nufo\nzebra\n"
+    );
+}
+
+#[test]
+fn recursive_listings_sort_by_path_and_depth() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("a/b")).unwrap();
+    fs::create_dir(dir.path().join("c")).unwrap();
+    for name in ["z", "a/y", "a/b/x", "c/w"] {
+        File::create(dir.path().join(name)).unwrap();
+    }
+    let walk = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+            .arg(dir.path())
+            .args(["-R", "-1"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(
+        walk(&["--sort", "path", "-r"]),
+        "z\nc/w\nc\na/y\na/b/x\na/b\na\n"
+    );
+    assert_eq!(
+        walk(&["--sort", "depth"]),
+        "a\nc\nz\na/b\na/y\nc/w\na/b/x\n"
+    );
+}
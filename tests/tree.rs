@@ -0,0 +1,31 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn tree_limit_and_max_width_keep_trees_short() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    for name in [
+        "a",
+        "b",
+        "c",
+        "src/one",
+        "src/two",
+        "src/a_rather_long_name.rs",
+    ] {
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ds"))
+        .arg(dir.path())
+        .args(["--tree", "--sort", "file-type", "--format", "{name}"])
+        .args(["--tree-limit", "2", "--max-width", "16"])
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "├── src\n│   ├── a_rathe…\n│   ├── one\n│   └── … 1 more\n├── a\n└── … 2 more\n"
+    );
+}